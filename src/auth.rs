@@ -0,0 +1,496 @@
+//! GitHub OAuth device flow login.
+//!
+//! Instead of pasting a personal access token, `auth login` asks GitHub for a device
+//! code, shows the user a short code to enter in the browser, then polls until the
+//! authorization completes and stores the resulting token in the keyring. `auth status`
+//! reports whether the resolved token still works.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{validate_github_token_with_scopes, ConfigError};
+
+/// Host serving the OAuth device flow endpoints
+pub const GITHUB_LOGIN_BASE: &str = "https://github.com";
+
+/// Environment variable (read at runtime, or at build time) naming the OAuth App client ID
+pub const CLIENT_ID_ENV: &str = "CURSOR_RULES_OAUTH_CLIENT_ID";
+
+/// Scope requested for new tokens, needed to read private `cursor-rules` repositories
+pub const LOGIN_SCOPE: &str = "repo";
+
+const DEVICE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Extra delay GitHub asks for on `slow_down` when it doesn't send a new interval
+const SLOW_DOWN_STEP: Duration = Duration::from_secs(5);
+
+/// OAuth App client ID from the environment, falling back to the one baked in at build time
+pub fn client_id() -> Option<String> {
+    std::env::var(CLIENT_ID_ENV)
+        .ok()
+        .or_else(|| option_env!("CURSOR_RULES_OAUTH_CLIENT_ID").map(str::to_string))
+        .filter(|id| !id.trim().is_empty())
+}
+
+/// Device and user codes issued at the start of the flow
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DeviceCode {
+    pub device_code: String,
+    /// Short code the user types at `verification_uri`
+    pub user_code: String,
+    pub verification_uri: String,
+    /// Seconds until the codes expire
+    pub expires_in: u64,
+    /// Minimum seconds between polls
+    pub interval: u64,
+}
+
+/// Result of a single poll of the access token endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PollOutcome {
+    /// The user has not finished authorizing yet
+    Pending,
+    /// Polling too fast; wait for the new interval in seconds when GitHub sends one
+    SlowDown(Option<u64>),
+    /// Authorization finished with this access token
+    Authorized(String),
+    /// The device code expired before the user authorized it
+    Expired,
+    /// The user cancelled the authorization
+    Denied,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+    interval: Option<u64>,
+}
+
+impl TokenResponse {
+    fn into_outcome(self) -> Result<PollOutcome> {
+        if let Some(token) = self.access_token {
+            return Ok(PollOutcome::Authorized(token));
+        }
+
+        match self.error.as_deref() {
+            Some("authorization_pending") => Ok(PollOutcome::Pending),
+            Some("slow_down") => Ok(PollOutcome::SlowDown(self.interval)),
+            Some("expired_token") => Ok(PollOutcome::Expired),
+            Some("access_denied") => Ok(PollOutcome::Denied),
+            Some(error) => anyhow::bail!(
+                "GitHub login failed: {}",
+                self.error_description.as_deref().unwrap_or(error)
+            ),
+            None => anyhow::bail!("GitHub login failed: unexpected token response"),
+        }
+    }
+}
+
+/// Client for GitHub's OAuth device flow
+pub struct DeviceFlow {
+    client: reqwest::Client,
+    base_url: String,
+    client_id: String,
+}
+
+impl DeviceFlow {
+    /// Create a flow against `base_url` (normally [`GITHUB_LOGIN_BASE`]) for an OAuth App
+    pub fn new(base_url: &str, client_id: &str) -> Self {
+        Self {
+            client: crate::github::client::http().unwrap_or_default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client_id: client_id.to_string(),
+        }
+    }
+
+    /// Ask GitHub for a device code and the user code to show
+    pub async fn request_code(&self, scope: &str) -> Result<DeviceCode> {
+        let body = self
+            .post(
+                "/login/device/code",
+                &[("client_id", self.client_id.as_str()), ("scope", scope)],
+            )
+            .await?;
+        serde_json::from_str(&body).context("Unexpected device code response from GitHub")
+    }
+
+    /// Poll the access token endpoint once
+    pub async fn poll_once(&self, code: &DeviceCode) -> Result<PollOutcome> {
+        let body = self
+            .post(
+                "/login/oauth/access_token",
+                &[
+                    ("client_id", self.client_id.as_str()),
+                    ("device_code", code.device_code.as_str()),
+                    ("grant_type", DEVICE_GRANT_TYPE),
+                ],
+            )
+            .await?;
+        let response: TokenResponse =
+            serde_json::from_str(&body).context("Unexpected token response from GitHub")?;
+        response.into_outcome()
+    }
+
+    /// Poll at the requested interval until the user authorizes, the code expires or
+    /// the request is denied
+    pub async fn wait_for_token(&self, code: &DeviceCode) -> Result<String> {
+        let deadline = Instant::now() + Duration::from_secs(code.expires_in);
+        let mut interval = Duration::from_secs(code.interval);
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            match self.poll_once(code).await? {
+                PollOutcome::Authorized(token) => return Ok(token),
+                PollOutcome::Pending => {}
+                PollOutcome::SlowDown(new_interval) => {
+                    interval = new_interval
+                        .map(Duration::from_secs)
+                        .unwrap_or(interval + SLOW_DOWN_STEP);
+                }
+                PollOutcome::Expired => break,
+                PollOutcome::Denied => anyhow::bail!("GitHub login was cancelled"),
+            }
+
+            if Instant::now() >= deadline {
+                break;
+            }
+        }
+
+        anyhow::bail!("The login code expired; run `cursor-rules auth login` again")
+    }
+
+    async fn post(&self, path: &str, form: &[(&str, &str)]) -> Result<String> {
+        let url = format!("{}{path}", self.base_url);
+        let response = self
+            .client
+            .post(&url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(form)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {url}"))?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            anyhow::bail!("GitHub returned {status} for {path}: {body}");
+        }
+        Ok(body)
+    }
+}
+
+/// Who the resolved token belongs to and how much API budget is left
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuthStatus {
+    pub authenticated: bool,
+    pub login: Option<String>,
+    pub scopes: Vec<String>,
+    pub rate_limit_remaining: Option<u64>,
+}
+
+/// Check `token` against the GitHub API (honouring `OCTO_BASE`)
+///
+/// A missing or rejected token is reported as unauthenticated, with the anonymous rate
+/// limit, rather than as an error.
+pub async fn auth_status(token: Option<&str>) -> Result<AuthStatus> {
+    let mut status = AuthStatus {
+        authenticated: false,
+        login: None,
+        scopes: Vec::new(),
+        rate_limit_remaining: None,
+    };
+
+    let valid_token = match token {
+        Some(token) => match validate_github_token_with_scopes(token).await {
+            Ok(scopes) => {
+                status.scopes = scopes.unwrap_or_default();
+                Some(token)
+            }
+            Err(ConfigError::InvalidValue(_)) => None,
+            Err(e) => return Err(e.into()),
+        },
+        None => None,
+    };
+
+    let octocrab = crate::github::client::octocrab(valid_token)?;
+    if valid_token.is_some() {
+        let user = octocrab
+            .current()
+            .user()
+            .await
+            .context("Failed to fetch the authenticated user")?;
+        status.authenticated = true;
+        status.login = Some(user.login);
+    }
+
+    status.rate_limit_remaining = rate_limit_remaining(&octocrab).await?;
+
+    Ok(status)
+}
+
+/// Core API requests left for `octocrab`'s token (or this IP when anonymous)
+pub async fn rate_limit_remaining(octocrab: &octocrab::Octocrab) -> Result<Option<u64>> {
+    let rate_limit: serde_json::Value = octocrab
+        .get("/rate_limit", None::<&()>)
+        .await
+        .context("Failed to fetch rate limit")?;
+    Ok(rate_limit["resources"]["core"]["remaining"]
+        .as_u64()
+        .or_else(|| rate_limit["rate"]["remaining"].as_u64()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server, ServerGuard};
+
+    fn device_code(expires_in: u64) -> DeviceCode {
+        DeviceCode {
+            device_code: "device-123".to_string(),
+            user_code: "ABCD-1234".to_string(),
+            verification_uri: "https://github.com/login/device".to_string(),
+            expires_in,
+            interval: 0,
+        }
+    }
+
+    /// Queue one token endpoint response; earlier mocks are served first
+    async fn queue_poll(server: &mut ServerGuard, body: &str) -> mockito::Mock {
+        server
+            .mock("POST", "/login/oauth/access_token")
+            .match_header("accept", "application/json")
+            .match_body(Matcher::UrlEncoded(
+                "device_code".into(),
+                "device-123".into(),
+            ))
+            .with_status(200)
+            .with_body(body)
+            .expect(1)
+            .create_async()
+            .await
+    }
+
+    #[test]
+    fn token_responses_map_to_outcomes() {
+        let parse = |body: &str| {
+            serde_json::from_str::<TokenResponse>(body)
+                .unwrap()
+                .into_outcome()
+                .unwrap()
+        };
+
+        assert_eq!(
+            parse(r#"{"error": "authorization_pending"}"#),
+            PollOutcome::Pending
+        );
+        assert_eq!(
+            parse(r#"{"error": "slow_down", "interval": 10}"#),
+            PollOutcome::SlowDown(Some(10))
+        );
+        assert_eq!(parse(r#"{"error": "expired_token"}"#), PollOutcome::Expired);
+        assert_eq!(parse(r#"{"error": "access_denied"}"#), PollOutcome::Denied);
+        assert_eq!(
+            parse(r#"{"access_token": "gho_abc", "token_type": "bearer"}"#),
+            PollOutcome::Authorized("gho_abc".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn request_code_parses_device_code() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/login/device/code")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("client_id".into(), "client".into()),
+                Matcher::UrlEncoded("scope".into(), "repo".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{"device_code": "device-123", "user_code": "ABCD-1234",
+                    "verification_uri": "https://github.com/login/device",
+                    "expires_in": 900, "interval": 5}"#,
+            )
+            .create_async()
+            .await;
+
+        let flow = DeviceFlow::new(&server.url(), "client");
+        let code = flow.request_code(LOGIN_SCOPE).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(code.user_code, "ABCD-1234");
+        assert_eq!(code.interval, 5);
+    }
+
+    #[tokio::test]
+    async fn polling_waits_through_pending_and_slow_down() {
+        let mut server = Server::new_async().await;
+        let pending = queue_poll(&mut server, r#"{"error": "authorization_pending"}"#).await;
+        let slow = queue_poll(&mut server, r#"{"error": "slow_down", "interval": 0}"#).await;
+        let success = queue_poll(&mut server, r#"{"access_token": "gho_token"}"#).await;
+
+        let flow = DeviceFlow::new(&server.url(), "client");
+        let token = flow.wait_for_token(&device_code(900)).await.unwrap();
+
+        assert_eq!(token, "gho_token");
+        pending.assert_async().await;
+        slow.assert_async().await;
+        success.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn polling_stops_when_code_expires() {
+        let mut server = Server::new_async().await;
+        queue_poll(&mut server, r#"{"error": "authorization_pending"}"#).await;
+        queue_poll(&mut server, r#"{"error": "expired_token"}"#).await;
+
+        let flow = DeviceFlow::new(&server.url(), "client");
+        let err = flow.wait_for_token(&device_code(900)).await.unwrap_err();
+
+        assert!(err.to_string().contains("login code expired"));
+    }
+
+    #[tokio::test]
+    async fn polling_reports_denied_login() {
+        let mut server = Server::new_async().await;
+        queue_poll(&mut server, r#"{"error": "access_denied"}"#).await;
+
+        let flow = DeviceFlow::new(&server.url(), "client");
+        let err = flow.wait_for_token(&device_code(900)).await.unwrap_err();
+
+        assert!(err.to_string().contains("cancelled"));
+    }
+
+    /// `/user` response with the fields octocrab requires for an `Author`
+    fn user_body(login: &str) -> String {
+        let url = format!("https://api.github.com/users/{login}");
+        serde_json::json!({
+            "login": login,
+            "id": 1,
+            "node_id": "MDQ6VXNlcjE=",
+            "avatar_url": "https://avatars.githubusercontent.com/u/1",
+            "gravatar_id": "",
+            "url": url,
+            "html_url": format!("https://github.com/{login}"),
+            "followers_url": format!("{url}/followers"),
+            "following_url": format!("{url}/following"),
+            "gists_url": format!("{url}/gists"),
+            "starred_url": format!("{url}/starred"),
+            "subscriptions_url": format!("{url}/subscriptions"),
+            "organizations_url": format!("{url}/orgs"),
+            "repos_url": format!("{url}/repos"),
+            "events_url": format!("{url}/events"),
+            "received_events_url": format!("{url}/received_events"),
+            "type": "User",
+            "site_admin": false
+        })
+        .to_string()
+    }
+
+    fn rate_limit_body(remaining: u64) -> String {
+        serde_json::json!({
+            "resources": {"core": {"limit": 5000, "remaining": remaining, "reset": 0, "used": 0}},
+            "rate": {"limit": 5000, "remaining": remaining, "reset": 0, "used": 0}
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn status_reports_authenticated_user() {
+        let mut server = Server::new_async().await;
+        let user = server
+            .mock("GET", "/user")
+            .match_header("authorization", "Bearer good")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("x-oauth-scopes", "repo, read:org")
+            .with_body(user_body("octocat"))
+            .expect_at_least(1)
+            .create_async()
+            .await;
+        let rate_limit = server
+            .mock("GET", "/rate_limit")
+            .match_header("authorization", "Bearer good")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(rate_limit_body(4999))
+            .create_async()
+            .await;
+
+        std::env::set_var("OCTO_BASE", server.url());
+        let status = auth_status(Some("good")).await;
+        std::env::remove_var("OCTO_BASE");
+
+        user.assert_async().await;
+        rate_limit.assert_async().await;
+        assert_eq!(
+            status.unwrap(),
+            AuthStatus {
+                authenticated: true,
+                login: Some("octocat".to_string()),
+                scopes: vec!["repo".to_string(), "read:org".to_string()],
+                rate_limit_remaining: Some(4999),
+            }
+        );
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn status_without_token_uses_anonymous_limit() {
+        let mut server = Server::new_async().await;
+        let user = server.mock("GET", "/user").expect(0).create_async().await;
+        let rate_limit = server
+            .mock("GET", "/rate_limit")
+            .match_header("authorization", Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(rate_limit_body(60))
+            .create_async()
+            .await;
+
+        std::env::set_var("OCTO_BASE", server.url());
+        let status = auth_status(None).await;
+        std::env::remove_var("OCTO_BASE");
+
+        user.assert_async().await;
+        rate_limit.assert_async().await;
+        let status = status.unwrap();
+        assert!(!status.authenticated);
+        assert_eq!(status.login, None);
+        assert_eq!(status.rate_limit_remaining, Some(60));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn status_treats_rejected_token_as_unauthenticated() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/user")
+            .with_status(401)
+            .with_body(r#"{"message": "Bad credentials"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/rate_limit")
+            .match_header("authorization", Matcher::Missing)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(rate_limit_body(60))
+            .create_async()
+            .await;
+
+        std::env::set_var("OCTO_BASE", server.url());
+        let status = auth_status(Some("revoked")).await;
+        std::env::remove_var("OCTO_BASE");
+
+        let status = status.unwrap();
+        assert!(!status.authenticated);
+        assert!(status.scopes.is_empty());
+        assert_eq!(status.rate_limit_remaining, Some(60));
+    }
+}
@@ -10,9 +10,11 @@ use keyring::{Entry, Error as KeyringError};
 use serde::de::Error as DeError;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+use crate::ui::theme::Theme;
+
 /// Errors that can occur during config operations
 #[derive(Error, Debug)]
 pub enum ConfigError {
@@ -32,6 +34,10 @@ pub enum ConfigError {
     #[error("Failed to serialize config: {0}")]
     SerializeError(#[from] toml::ser::Error),
 
+    /// A value failed validation
+    #[error("{0}")]
+    InvalidValue(String),
+
     /// Keyring operation failed
     #[error("Keyring operation failed: {0}")]
     KeyringError(String),
@@ -50,13 +56,34 @@ pub struct Config {
     /// Default repository name (defaults to 'cursor-rules')
     pub repo: Option<String>,
 
+    /// Default branch to fetch from (defaults to 'main')
+    pub branch: Option<String>,
+
     /// Default output directory for copied rules
     pub out_dir: Option<String>,
 
     /// Whether telemetry is enabled
     pub telemetry: Option<bool>,
+
+    /// Number of files copied concurrently
+    pub jobs: Option<usize>,
+
+    /// Hours a cached repository tree stays fresh (0 = always refetch)
+    pub cache_ttl_hours: Option<u64>,
+
+    /// Colour theme for the interactive browser
+    pub theme: Option<Theme>,
+
+    /// Whether to check once a day for a newer release (off unless set)
+    pub update_check: Option<bool>,
+
+    /// Repository directory holding quick-add manifests (defaults to `quick-add`)
+    pub manifest_dir: Option<String>,
 }
 
+/// Copy concurrency used when neither `--jobs` nor the `jobs` config key is set
+pub const DEFAULT_JOBS: usize = 4;
+
 /// Service name for keyring entries
 const KEYRING_SERVICE: &str = "cursor-rules-cli";
 
@@ -136,16 +163,128 @@ pub fn config_file_path() -> Result<PathBuf, ConfigError> {
     Ok(app_config_dir.join("config.toml"))
 }
 
-/// Load configuration from file
+/// Where the tool keeps its files, for `config path`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StoragePaths {
+    /// Global config file, which may not exist yet
+    pub config: PathBuf,
+    /// Cache directory for repository trees and rule content
+    pub cache: PathBuf,
+}
+
+/// Locations of the global config file and the cache directory
+pub fn storage_paths() -> Result<StoragePaths, ConfigError> {
+    Ok(StoragePaths {
+        config: config_file_path()?,
+        cache: crate::github::cache::get_cache_directory()?,
+    })
+}
+
+/// Name of the project-local config file that overrides the global one
+pub const LOCAL_CONFIG_FILE: &str = ".cursor-rules.toml";
+
+impl Config {
+    /// Layer `other` over `self`, keeping `self`'s value wherever `other` is unset
+    pub fn merge(self, other: Config) -> Config {
+        Config {
+            owner: other.owner.or(self.owner),
+            repo: other.repo.or(self.repo),
+            branch: other.branch.or(self.branch),
+            out_dir: other.out_dir.or(self.out_dir),
+            telemetry: other.telemetry.or(self.telemetry),
+            jobs: other.jobs.or(self.jobs),
+            cache_ttl_hours: other.cache_ttl_hours.or(self.cache_ttl_hours),
+            theme: other.theme.or(self.theme),
+            update_check: other.update_check.or(self.update_check),
+            manifest_dir: other.manifest_dir.or(self.manifest_dir),
+        }
+    }
+}
+
+/// Load effective configuration: the global config file with any project-local
+/// `.cursor-rules.toml` layered on top
 pub fn load_config() -> Result<Config, ConfigError> {
-    let config_path = config_file_path()?;
+    let cwd = std::env::current_dir()?;
+    load_merged_config(&config_file_path()?, &cwd)
+}
+
+/// Load only the global configuration file, which is what `config set`/`delete` edit
+pub fn load_global_config() -> Result<Config, ConfigError> {
+    load_config_file(&config_file_path()?)
+}
+
+/// Config files that contribute to [`load_config`], global first, so they can be displayed
+pub fn config_sources() -> Result<Vec<PathBuf>, ConfigError> {
+    let mut sources = vec![config_file_path()?];
+    sources.extend(find_local_config(&std::env::current_dir()?));
+    Ok(sources)
+}
+
+/// Load `global_path` and merge the local config found from `start_dir` over it
+fn load_merged_config(global_path: &Path, start_dir: &Path) -> Result<Config, ConfigError> {
+    let global = load_config_file(global_path)?;
+    match find_local_config(start_dir) {
+        Some(local_path) => Ok(global.merge(load_config_file(&local_path)?)),
+        None => Ok(global),
+    }
+}
 
+/// Find `.cursor-rules.toml` in `start_dir` or a parent, stopping at the git root.
+/// Outside a git repository only `start_dir` itself is checked.
+fn find_local_config(start_dir: &Path) -> Option<PathBuf> {
+    let inside_git = start_dir.ancestors().any(|dir| dir.join(".git").exists());
+
+    for dir in start_dir.ancestors() {
+        let candidate = dir.join(LOCAL_CONFIG_FILE);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if !inside_git || dir.join(".git").exists() {
+            break;
+        }
+    }
+
+    None
+}
+
+/// Find the project rules belong to from `start_dir`: the nearest directory holding a
+/// `.cursor` directory, or else the git root. The home directory never matches, since
+/// `~/.cursor` holds Cursor's own settings rather than a project's rules.
+pub fn find_cursor_root(start_dir: &Path) -> Option<PathBuf> {
+    let home = dirs::home_dir();
+
+    for dir in start_dir.ancestors() {
+        if dir.join(".cursor").is_dir() && home.as_deref() != Some(dir) {
+            return Some(dir.to_path_buf());
+        }
+        if dir.join(".git").exists() {
+            return Some(dir.to_path_buf());
+        }
+    }
+
+    None
+}
+
+/// Output directory when neither `--out` nor `out_dir` is set: `.cursor/rules` under
+/// [`find_cursor_root`] of the working directory, or `./.cursor/rules` without one
+pub fn default_out_dir() -> String {
+    let detected = std::env::current_dir().ok().and_then(|cwd| {
+        let root = find_cursor_root(&cwd)?;
+        (root != cwd).then(|| root.join(".cursor").join("rules"))
+    });
+    detected
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|| "./.cursor/rules".to_string())
+}
+
+/// Load a single config file, treating a missing or empty file as the default config
+pub(crate) fn load_config_file(config_path: &Path) -> Result<Config, ConfigError> {
     if !config_path.exists() {
         // Return default config if file doesn't exist
         return Ok(Config::default());
     }
 
-    let content = fs::read_to_string(&config_path)
+    let content = fs::read_to_string(config_path)
         .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
 
     if content.trim().is_empty() {
@@ -174,34 +313,348 @@ pub fn save_config(config: &Config) -> Result<(), ConfigError> {
     Ok(())
 }
 
-/// Get GitHub token following priority: CLI flag → env var → keyring → none
+/// Starting contents for a new config file: every known key, commented out, with its default
+pub fn config_template() -> String {
+    [
+        "# cursor-rules configuration",
+        "# Uncomment a setting to change it. The GitHub token is kept in the OS keyring,",
+        "# not here: use `cursor-rules config set token <TOKEN>` or `cursor-rules auth login`.",
+        "",
+        "# Default GitHub owner to fetch rules from (detected from git or gh when unset)",
+        "# owner = \"your-github-user\"",
+        "",
+        "# Repository holding the rules",
+        "# repo = \"cursor-rules\"",
+        "",
+        "# Branch, tag or commit to fetch from",
+        "# branch = \"main\"",
+        "",
+        "# Directory rules are copied into",
+        "# out_dir = \"./.cursor/rules\"",
+        "",
+        "# Send anonymous usage telemetry",
+        "# telemetry = false",
+        "",
+        "# Number of files copied concurrently",
+        &format!("# jobs = {DEFAULT_JOBS}"),
+        "",
+        "# Hours a cached repository tree stays fresh; 0 always refetches",
+        "# cache_ttl_hours = 24",
+        "",
+        "# Browser colours: default, high-contrast or no-color",
+        "# theme = \"default\"",
+        "",
+        "# Check once a day for a newer release",
+        "# update_check = false",
+        "",
+        "# Repository directory holding quick-add manifests",
+        "# manifest_dir = \"quick-add\"",
+        "",
+    ]
+    .join("\n")
+}
+
+/// Editor for `config edit`: `$VISUAL`, then `$EDITOR`, then the platform's usual default
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+/// Open the global config file in the user's editor, creating it from [`config_template`]
+/// first if needed, then check that the edited file still parses. A file that no longer
+/// parses is left exactly as the user saved it.
+pub fn edit_config() -> Result<PathBuf, ConfigError> {
+    use is_terminal::IsTerminal;
+
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Err(ConfigError::InvalidValue(
+            "`config edit` needs an interactive terminal; use `config set <key> <value>` instead"
+                .to_string(),
+        ));
+    }
+
+    let config_path = config_file_path()?;
+    if !config_path.exists() {
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create config directory: {}", parent.display())
+            })?;
+        }
+        fs::write(&config_path, config_template())
+            .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
+    }
+
+    // The editor may carry arguments, e.g. `code --wait`
+    let editor = editor_command();
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(&config_path)
+        .status()
+        .with_context(|| format!("Failed to start editor `{editor}`"))?;
+    if !status.success() {
+        return Err(ConfigError::InvalidValue(format!(
+            "Editor `{editor}` exited with {status}"
+        )));
+    }
+
+    load_config_file(&config_path).map_err(|e| {
+        ConfigError::InvalidValue(format!(
+            "{} is not valid: {e}\nYour edits were kept; run `cursor-rules config edit` again to fix them",
+            config_path.display()
+        ))
+    })?;
+    Ok(config_path)
+}
+
+/// Settings written by `cursor-rules init`; `None` leaves a key unset
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InitOptions {
+    pub owner: Option<String>,
+    pub repo: Option<String>,
+    pub out_dir: Option<String>,
+    /// Token to store in the keyring
+    pub token: Option<String>,
+}
+
+/// Ask for every setting `options` does not already carry. Answers left blank stay unset.
+pub fn prompt_init_options(mut options: InitOptions) -> Result<InitOptions, ConfigError> {
+    use inquire::{Confirm, Password, Text};
+
+    let cancelled = |_| ConfigError::InvalidValue("Setup cancelled".to_string());
+    let ask = |message: &str, help: &str| -> Result<Option<String>, ConfigError> {
+        let answer = Text::new(message)
+            .with_help_message(help)
+            .prompt()
+            .map_err(cancelled)?;
+        let answer = answer.trim();
+        Ok((!answer.is_empty()).then(|| answer.to_string()))
+    };
+
+    if options.owner.is_none() {
+        options.owner = ask(
+            "Default GitHub owner:",
+            "Leave blank to detect it from git or gh each time",
+        )?;
+    }
+    if options.repo.is_none() {
+        options.repo = ask("Rules repository:", "Leave blank for `cursor-rules`")?;
+    }
+    if options.out_dir.is_none() {
+        options.out_dir = ask("Copy rules into:", "Leave blank for `./.cursor/rules`")?;
+    }
+    if options.token.is_none()
+        && Confirm::new("Store a GitHub token in the OS keyring?")
+            .with_default(false)
+            .with_help_message("Needed for private repositories and higher rate limits")
+            .prompt()
+            .map_err(cancelled)?
+    {
+        let token = Password::new("GitHub Personal Access Token:")
+            .with_help_message("Create one at https://github.com/settings/tokens")
+            .prompt()
+            .map_err(cancelled)?;
+        options.token = Some(token.trim().to_string()).filter(|token| !token.is_empty());
+    }
+    Ok(options)
+}
+
+/// The global config path, unless a config file already exists there and `force` is unset
+pub fn check_init_allowed(force: bool) -> Result<PathBuf, ConfigError> {
+    let config_path = config_file_path()?;
+    if config_path.exists() && !force {
+        return Err(ConfigError::InvalidValue(format!(
+            "{} already exists; use `cursor-rules init --force` to replace it or `config set` to change single values",
+            config_path.display()
+        )));
+    }
+    Ok(config_path)
+}
+
+/// Write a fresh global config holding `options`, storing the token in `secret_store`.
+/// An existing config file is only replaced with `force`.
+pub fn write_initial_config(
+    options: &InitOptions,
+    force: bool,
+    secret_store: &dyn SecretStore,
+) -> Result<PathBuf, ConfigError> {
+    let config_path = check_init_allowed(force)?;
+    save_config(&Config::default())?;
+    for (key, value) in [
+        ("owner", &options.owner),
+        ("repo", &options.repo),
+        ("out_dir", &options.out_dir),
+    ] {
+        if let Some(value) = value {
+            update_config_value(key, value)?;
+        }
+    }
+    if let Some(token) = &options.token {
+        secret_store.set_token(token)?;
+    }
+    Ok(config_path)
+}
+
+/// Where an effective setting came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueSource {
+    /// Passed on the command line
+    Flag,
+    /// Read from the file passed with `--token-file`
+    TokenFile,
+    /// Read from the named environment variable
+    Env(&'static str),
+    /// Stored in the config file
+    ConfigFile,
+    /// Stored in the OS keyring
+    Keyring,
+    /// Built-in default
+    Default,
+}
+
+impl std::fmt::Display for ValueSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueSource::Flag => write!(f, "flag"),
+            ValueSource::TokenFile => write!(f, "token file"),
+            ValueSource::Env(name) => write!(f, "env {name}"),
+            ValueSource::ConfigFile => write!(f, "config file"),
+            ValueSource::Keyring => write!(f, "keyring"),
+            ValueSource::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// Pick a setting following priority: CLI flag → env var → config file, and report which won
+pub fn resolve_with_source<T>(
+    flag: Option<T>,
+    env: Option<(&'static str, T)>,
+    config: Option<T>,
+) -> Option<(T, ValueSource)> {
+    flag.map(|value| (value, ValueSource::Flag))
+        .or_else(|| env.map(|(name, value)| (value, ValueSource::Env(name))))
+        .or_else(|| config.map(|value| (value, ValueSource::ConfigFile)))
+}
+
+/// Environment variables checked for a token, in order; `GH_TOKEN` is what the `gh` CLI uses
+const TOKEN_ENV_VARS: [&str; 2] = ["GITHUB_TOKEN", "GH_TOKEN"];
+
+/// Get GitHub token following priority: CLI flag → token file → env var → keyring → none
 pub fn resolve_github_token(
     cli_token: Option<&str>,
+    token_file: Option<&Path>,
     secret_store: &dyn SecretStore,
 ) -> Result<Option<String>, ConfigError> {
-    // 1. CLI flag has highest priority
+    Ok(
+        resolve_github_token_with_source(cli_token, token_file, secret_store)?
+            .map(|(token, _)| token),
+    )
+}
+
+/// Like [`resolve_github_token`], but also report where the token came from
+pub fn resolve_github_token_with_source(
+    cli_token: Option<&str>,
+    token_file: Option<&Path>,
+    secret_store: &dyn SecretStore,
+) -> Result<Option<(String, ValueSource)>, ConfigError> {
     if let Some(token) = cli_token {
-        return Ok(Some(token.to_string()));
+        return Ok(Some((token.to_string(), ValueSource::Flag)));
     }
 
-    // 2. Environment variable
-    if let Ok(token) = std::env::var("GITHUB_TOKEN") {
-        if !token.trim().is_empty() {
-            return Ok(Some(token));
+    // An unreadable token file falls through to the remaining sources
+    if let Some(path) = token_file {
+        match read_token_file(path) {
+            Ok(Some(token)) => return Ok(Some((token, ValueSource::TokenFile))),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Ignoring --token-file: {e:#}"),
         }
     }
 
-    // 3. Keyring storage
-    secret_store.get_token()
+    let env_token = TOKEN_ENV_VARS.iter().find_map(|&name| {
+        std::env::var(name)
+            .ok()
+            .filter(|token| !token.trim().is_empty())
+            .map(|token| (name, token))
+    });
+
+    if let Some(resolved) = resolve_with_source(None, env_token, None) {
+        return Ok(Some(resolved));
+    }
+
+    // Keyring storage is the last resort
+    Ok(secret_store
+        .get_token()?
+        .map(|token| (token, ValueSource::Keyring)))
+}
+
+/// Read a token from `path`, trimming trailing whitespace; an empty file means no token
+pub fn read_token_file(path: &Path) -> Result<Option<String>, ConfigError> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read token file {}", path.display()))?;
+    let token = content.trim_end();
+
+    Ok((!token.trim().is_empty()).then(|| token.to_string()))
+}
+
+/// Parse a job count, rejecting zero
+pub fn parse_jobs(value: &str) -> Result<usize, ConfigError> {
+    match value.trim().parse::<usize>() {
+        Ok(0) => Err(ConfigError::InvalidValue(
+            "jobs must be at least 1".to_string(),
+        )),
+        Ok(jobs) => Ok(jobs),
+        Err(_) => Err(ConfigError::InvalidValue(format!(
+            "Invalid job count: {value}"
+        ))),
+    }
+}
+
+/// Get the owner and repo following priority: CLI flags → config. A repo named by a URL
+/// or `owner/repo` in `--owner` counts as a CLI flag, so it beats `repo` from the config.
+pub fn resolve_owner_and_repo(
+    cli_owner: Option<&str>,
+    cli_repo: Option<String>,
+    config: &Config,
+) -> (Option<String>, Option<String>) {
+    let (owner, owner_repo) = match cli_owner {
+        Some(value) => {
+            let (owner, repo) = crate::github::split_owner_flag(value);
+            (Some(owner), repo)
+        }
+        None => (None, None),
+    };
+    (
+        owner.or_else(|| config.owner.clone()),
+        cli_repo.or(owner_repo).or_else(|| config.repo.clone()),
+    )
+}
+
+/// Get the copy concurrency following priority: CLI flag → config → default. A hand-edited
+/// `jobs = 0` is treated as 1, since copying with no permits would never finish.
+pub fn resolve_jobs(cli_jobs: Option<usize>, config: &Config) -> usize {
+    cli_jobs.or(config.jobs).unwrap_or(DEFAULT_JOBS).max(1)
+}
+
+/// Get the browser theme following priority: CLI flag → `NO_COLOR` → config → default
+pub fn resolve_theme(cli_theme: Option<Theme>, config: &Config) -> Theme {
+    cli_theme
+        .or(crate::output::no_color_requested().then_some(Theme::NoColor))
+        .or(config.theme)
+        .unwrap_or_default()
 }
 
 /// Update a single config value
 pub fn update_config_value(key: &str, value: &str) -> Result<(), ConfigError> {
-    let mut config = load_config()?;
+    let mut config = load_global_config()?;
 
     match key {
         "owner" => config.owner = Some(value.to_string()),
         "repo" => config.repo = Some(value.to_string()),
+        "branch" => config.branch = Some(value.to_string()),
         "out_dir" => config.out_dir = Some(value.to_string()),
         "telemetry" => {
             config.telemetry =
@@ -209,6 +662,28 @@ pub fn update_config_value(key: &str, value: &str) -> Result<(), ConfigError> {
                     ConfigError::ParseError(DeError::custom("Invalid boolean value"))
                 })?);
         }
+        "jobs" => config.jobs = Some(parse_jobs(value)?),
+        "cache_ttl_hours" => {
+            config.cache_ttl_hours = Some(value.trim().parse::<u64>().map_err(|_| {
+                ConfigError::InvalidValue(format!("Invalid cache TTL in hours: {value}"))
+            })?);
+        }
+        "theme" => config.theme = Some(value.parse().map_err(ConfigError::InvalidValue)?),
+        "update_check" => {
+            config.update_check =
+                Some(value.parse::<bool>().map_err(|_| {
+                    ConfigError::ParseError(DeError::custom("Invalid boolean value"))
+                })?);
+        }
+        "manifest_dir" => {
+            let dir = value.trim().trim_matches('/');
+            if dir.is_empty() {
+                return Err(ConfigError::InvalidValue(
+                    "manifest_dir must name a directory".to_string(),
+                ));
+            }
+            config.manifest_dir = Some(dir.to_string());
+        }
         _ => {
             return Err(ConfigError::ParseError(DeError::custom(format!(
                 "Unknown config key: {key}"
@@ -221,13 +696,19 @@ pub fn update_config_value(key: &str, value: &str) -> Result<(), ConfigError> {
 
 /// Delete a config value (set it to None)
 pub fn delete_config_value(key: &str) -> Result<(), ConfigError> {
-    let mut config = load_config()?;
+    let mut config = load_global_config()?;
 
     match key {
         "owner" => config.owner = None,
         "repo" => config.repo = None,
+        "branch" => config.branch = None,
         "out_dir" => config.out_dir = None,
         "telemetry" => config.telemetry = None,
+        "jobs" => config.jobs = None,
+        "cache_ttl_hours" => config.cache_ttl_hours = None,
+        "theme" => config.theme = None,
+        "update_check" => config.update_check = None,
+        "manifest_dir" => config.manifest_dir = None,
         _ => {
             return Err(ConfigError::ParseError(DeError::custom(format!(
                 "Unknown config key: {key}"
@@ -238,26 +719,50 @@ pub fn delete_config_value(key: &str) -> Result<(), ConfigError> {
     save_config(&config)
 }
 
-/// Validate GitHub token and check scopes
-#[allow(dead_code)] // Planned for FR-4 auth validation features
-pub async fn validate_github_token_with_scopes(token: &str) -> Result<Vec<String>, ConfigError> {
-    let octocrab = octocrab::Octocrab::builder()
-        .personal_token(token.to_string())
-        .build()
-        .map_err(|e| ConfigError::Other(e.into()))?;
+/// Validate a GitHub token with an authenticated `/user` request and return the OAuth
+/// scopes GitHub reports for it in `X-OAuth-Scopes`. Tokens without that header, such
+/// as fine-grained tokens, yield `None` since their access cannot be told from scopes.
+pub async fn validate_github_token_with_scopes(
+    token: &str,
+) -> Result<Option<Vec<String>>, ConfigError> {
+    let octocrab = crate::github::client::octocrab(Some(token))?;
 
-    // Make a test API call to validate the token
-    let _user = octocrab
-        .current()
-        .user()
+    let response = octocrab
+        ._get_with_headers("/user", None)
         .await
-        .map_err(|e| ConfigError::KeyringError(format!("Token validation failed: {e}")))?;
+        .map_err(|e| ConfigError::Other(e.into()))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ConfigError::InvalidValue(format!(
+            "GitHub rejected the token ({status})"
+        )));
+    }
 
-    // Try to get token scopes from headers (this is a simplified approach)
-    // In practice, you might need to make a specific API call to check scopes
-    let scopes = vec![]; // Placeholder - real implementation would check actual scopes
+    Ok(parse_oauth_scopes(response.headers()))
+}
+
+/// Split the comma-separated `X-OAuth-Scopes` header into individual scopes, or `None`
+/// when the header is absent
+fn parse_oauth_scopes(headers: &http::HeaderMap) -> Option<Vec<String>> {
+    headers
+        .get("x-oauth-scopes")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|scope| !scope.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+}
 
-    Ok(scopes)
+/// Whether `scopes` grant read access to repository contents
+pub fn has_repo_scope(scopes: &[String]) -> bool {
+    scopes
+        .iter()
+        .any(|scope| scope == "repo" || scope == "public_repo")
 }
 
 /// Handle 401 errors by prompting for new token (interactive only)
@@ -299,11 +804,23 @@ pub async fn handle_auth_error_interactive(
         Ok(_scopes) => {
             // Store the validated token
             secret_store.set_token(&token)?;
-            println!("✓ Token validated and stored securely.");
+            println!(
+                "{}",
+                crate::output::status(
+                    crate::output::Status::Ok,
+                    "Token validated and stored securely."
+                )
+            );
             Ok(Some(token))
         }
         Err(e) => {
-            eprintln!("⚠ Token validation failed: {e}");
+            eprintln!(
+                "{}",
+                crate::output::status_err(
+                    crate::output::Status::Warning,
+                    format!("Token validation failed: {e}")
+                )
+            );
             Ok(None)
         }
     }
@@ -359,27 +876,27 @@ mod tests {
         env::set_var("GITHUB_TOKEN", "env_token");
         mock_store.set_token("keyring_token").unwrap();
 
-        let result = resolve_github_token(Some("cli_token"), &mock_store).unwrap();
+        let result = resolve_github_token(Some("cli_token"), None, &mock_store).unwrap();
         assert_eq!(result, Some("cli_token".to_string()));
 
         // Test 2: Environment variable when no CLI token (with fresh mock store)
         let mock_store = MockSecretStore::new();
         mock_store.set_token("keyring_token").unwrap();
         env::set_var("GITHUB_TOKEN", "env_token");
-        let result = resolve_github_token(None, &mock_store).unwrap();
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
         assert_eq!(result, Some("env_token".to_string()));
 
         // Test 3: Keyring when no CLI token or env var (with fresh mock store)
         let mock_store = MockSecretStore::new();
         mock_store.set_token("keyring_token").unwrap();
         env::remove_var("GITHUB_TOKEN");
-        let result = resolve_github_token(None, &mock_store).unwrap();
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
         assert_eq!(result, Some("keyring_token".to_string()));
 
         // Test 4: None when no sources available (with fresh mock store)
         let mock_store = MockSecretStore::new();
         env::remove_var("GITHUB_TOKEN");
-        let result = resolve_github_token(None, &mock_store).unwrap();
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
         assert!(result.is_none());
 
         // Restore original state
@@ -394,8 +911,14 @@ mod tests {
         let config = Config {
             owner: Some("testowner".to_string()),
             repo: Some("testrepo".to_string()),
+            branch: None,
             out_dir: Some("./test".to_string()),
             telemetry: Some(false),
+            jobs: None,
+            cache_ttl_hours: None,
+            theme: None,
+            update_check: None,
+            manifest_dir: None,
         };
 
         let serialized = toml::to_string(&config).unwrap();
@@ -407,6 +930,112 @@ mod tests {
         assert_eq!(config.telemetry, deserialized.telemetry);
     }
 
+    #[test]
+    #[serial_test::serial]
+    fn test_storage_paths_are_app_specific() {
+        let cache_home = tempfile::TempDir::new().unwrap();
+        let original = std::env::var("XDG_CACHE_HOME").ok();
+        std::env::set_var("XDG_CACHE_HOME", cache_home.path());
+        let paths = storage_paths();
+        match original {
+            Some(value) => std::env::set_var("XDG_CACHE_HOME", value),
+            None => std::env::remove_var("XDG_CACHE_HOME"),
+        }
+
+        let paths = paths.unwrap();
+        assert!(paths.config.to_string_lossy().contains("cursor-rules-cli"));
+        assert!(paths.cache.to_string_lossy().contains("cursor-rules-cli"));
+        assert!(paths.config.ends_with("config.toml"));
+    }
+
+    #[test]
+    fn test_config_template_parses_with_every_key() {
+        let template = config_template();
+        let config: Config = toml::from_str(&template).unwrap();
+        assert!(config.owner.is_none() && config.jobs.is_none());
+
+        // Uncommenting the settings must also give a valid file covering every key
+        let uncommented: String = template
+            .lines()
+            .map(|line| match line.strip_prefix("# ") {
+                Some(setting) if setting.contains(" = ") => setting,
+                _ => line,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config: Config = toml::from_str(&uncommented).unwrap();
+        let table: toml::Table = toml::from_str(&uncommented).unwrap();
+        let known = serde_json::to_value(Config::default()).unwrap();
+        let known: Vec<&String> = known.as_object().unwrap().keys().collect();
+        assert_eq!(table.len(), known.len());
+        assert!(known.iter().all(|key| table.contains_key(key.as_str())));
+        assert_eq!(config.repo.as_deref(), Some("cursor-rules"));
+        assert_eq!(config.jobs, Some(DEFAULT_JOBS));
+        assert_eq!(config.theme, Some(Theme::Default));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_editor_command_prefers_visual_then_editor() {
+        let original = (std::env::var("VISUAL").ok(), std::env::var("EDITOR").ok());
+        std::env::set_var("VISUAL", "code --wait");
+        std::env::set_var("EDITOR", "nano");
+        let visual = editor_command();
+        std::env::set_var("VISUAL", "");
+        let editor = editor_command();
+        std::env::remove_var("VISUAL");
+        std::env::remove_var("EDITOR");
+        let fallback = editor_command();
+        for (var, value) in [("VISUAL", original.0), ("EDITOR", original.1)] {
+            if let Some(value) = value {
+                std::env::set_var(var, value);
+            }
+        }
+
+        assert_eq!(visual, "code --wait");
+        assert_eq!(editor, "nano");
+        assert_eq!(fallback, if cfg!(windows) { "notepad" } else { "vi" });
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_write_initial_config_without_prompts() {
+        let config_home = tempfile::TempDir::new().unwrap();
+        let original = std::env::var("XDG_CONFIG_HOME").ok();
+        std::env::set_var("XDG_CONFIG_HOME", config_home.path());
+
+        let store = MockSecretStore::new();
+        let options = InitOptions {
+            owner: Some("octo".into()),
+            repo: Some("team-rules".into()),
+            out_dir: None,
+            token: Some("ghp_init".into()),
+        };
+        let written = write_initial_config(&options, false, &store);
+        let first = load_global_config();
+        let refused = write_initial_config(&InitOptions::default(), false, &store);
+        let replaced = write_initial_config(&InitOptions::default(), true, &store);
+        let second = load_global_config();
+        match original {
+            Some(value) => std::env::set_var("XDG_CONFIG_HOME", value),
+            None => std::env::remove_var("XDG_CONFIG_HOME"),
+        }
+
+        assert!(written.unwrap().starts_with(config_home.path()));
+        let first = first.unwrap();
+        assert_eq!(first.owner.as_deref(), Some("octo"));
+        assert_eq!(first.repo.as_deref(), Some("team-rules"));
+        assert_eq!(first.out_dir, None);
+        assert_eq!(store.get_token().unwrap().as_deref(), Some("ghp_init"));
+
+        let refused = refused.unwrap_err().to_string();
+        assert!(refused.contains("--force"), "{refused}");
+        replaced.unwrap();
+        let second = second.unwrap();
+        assert_eq!(second.owner, None);
+        assert_eq!(second.repo, None);
+    }
+
     #[test]
     fn test_config_default() {
         let config = Config::default();
@@ -468,7 +1097,7 @@ mod tests {
         // Set environment variable
         env::set_var("GITHUB_TOKEN", "env_token");
 
-        let result = resolve_github_token(None, &mock_store).unwrap();
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
         assert_eq!(result, Some("env_token".to_string()));
 
         // Restore original state
@@ -491,7 +1120,7 @@ mod tests {
         // Ensure no environment variable
         env::remove_var("GITHUB_TOKEN");
 
-        let result = resolve_github_token(None, &mock_store).unwrap();
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
         assert!(result.is_none());
 
         // Restore original state
@@ -544,8 +1173,14 @@ mod tests {
         let test_config = Config {
             owner: Some("testowner".to_string()),
             repo: Some("testrepo".to_string()),
+            branch: None,
             out_dir: Some("./testdir".to_string()),
             telemetry: Some(true),
+            jobs: None,
+            cache_ttl_hours: None,
+            theme: None,
+            update_check: None,
+            manifest_dir: None,
         };
 
         // Serialize to TOML
@@ -703,7 +1338,7 @@ mod tests {
         // Set empty environment variable
         env::set_var("GITHUB_TOKEN", "   "); // whitespace only
 
-        let result = resolve_github_token(None, &mock_store).unwrap();
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
         assert!(result.is_none());
 
         // Restore original state
@@ -713,6 +1348,117 @@ mod tests {
         }
     }
 
+    #[test]
+    #[serial_test::serial]
+    fn test_resolve_github_token_file_precedence() {
+        use std::env;
+        use tempfile::TempDir;
+
+        let original_token = env::var("GITHUB_TOKEN").ok();
+        let temp_dir = TempDir::new().unwrap();
+        let token_file = temp_dir.path().join("token");
+        fs::write(&token_file, "file_token\n\n").unwrap();
+
+        let mock_store = MockSecretStore::new();
+        mock_store.set_token("keyring_token").unwrap();
+        env::set_var("GITHUB_TOKEN", "env_token");
+
+        // The flag still wins over the file
+        let result =
+            resolve_github_token_with_source(Some("cli_token"), Some(&token_file), &mock_store)
+                .unwrap();
+        assert_eq!(result, Some(("cli_token".to_string(), ValueSource::Flag)));
+
+        // The file wins over the environment, with trailing newlines trimmed
+        let result =
+            resolve_github_token_with_source(None, Some(&token_file), &mock_store).unwrap();
+        assert_eq!(
+            result,
+            Some(("file_token".to_string(), ValueSource::TokenFile))
+        );
+
+        // An empty file falls through to the environment
+        fs::write(&token_file, " \n\t\n").unwrap();
+        let result = resolve_github_token(None, Some(&token_file), &mock_store).unwrap();
+        assert_eq!(result, Some("env_token".to_string()));
+
+        // So does a missing file, and then the keyring
+        env::remove_var("GITHUB_TOKEN");
+        let missing = temp_dir.path().join("missing");
+        let result = resolve_github_token(None, Some(&missing), &mock_store).unwrap();
+        assert_eq!(result, Some("keyring_token".to_string()));
+
+        match original_token {
+            Some(token) => env::set_var("GITHUB_TOKEN", token),
+            None => env::remove_var("GITHUB_TOKEN"),
+        }
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_resolve_github_token_gh_token_fallback() {
+        use std::env;
+
+        let original_github = env::var("GITHUB_TOKEN").ok();
+        let original_gh = env::var("GH_TOKEN").ok();
+        let mock_store = MockSecretStore::new();
+        mock_store.set_token("keyring_token").unwrap();
+
+        // GH_TOKEN is used when GITHUB_TOKEN is unset or blank
+        env::remove_var("GITHUB_TOKEN");
+        env::set_var("GH_TOKEN", "gh_token");
+        let result = resolve_github_token_with_source(None, None, &mock_store).unwrap();
+        assert_eq!(
+            result,
+            Some(("gh_token".to_string(), ValueSource::Env("GH_TOKEN")))
+        );
+
+        env::set_var("GITHUB_TOKEN", "  ");
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
+        assert_eq!(result, Some("gh_token".to_string()));
+
+        // GITHUB_TOKEN wins when both are set
+        env::set_var("GITHUB_TOKEN", "github_token");
+        let result = resolve_github_token_with_source(None, None, &mock_store).unwrap();
+        assert_eq!(
+            result,
+            Some(("github_token".to_string(), ValueSource::Env("GITHUB_TOKEN")))
+        );
+
+        // A blank GH_TOKEN still falls back to the keyring
+        env::remove_var("GITHUB_TOKEN");
+        env::set_var("GH_TOKEN", "");
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
+        assert_eq!(result, Some("keyring_token".to_string()));
+
+        for (name, original) in [("GITHUB_TOKEN", original_github), ("GH_TOKEN", original_gh)] {
+            match original {
+                Some(token) => env::set_var(name, token),
+                None => env::remove_var(name),
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_token_file() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let token_file = temp_dir.path().join("token");
+
+        fs::write(&token_file, "ghp_abc  \r\n").unwrap();
+        assert_eq!(
+            read_token_file(&token_file).unwrap(),
+            Some("ghp_abc".to_string())
+        );
+
+        fs::write(&token_file, "").unwrap();
+        assert_eq!(read_token_file(&token_file).unwrap(), None);
+
+        let err = read_token_file(&temp_dir.path().join("missing")).unwrap_err();
+        assert!(err.to_string().contains("Failed to read token file"));
+    }
+
     #[test]
     fn test_load_config_empty_file() {
         // Test empty TOML content handling directly
@@ -819,27 +1565,27 @@ mod tests {
         env::set_var("GITHUB_TOKEN", "env_token");
         mock_store.set_token("keyring_token").unwrap();
 
-        let result = resolve_github_token(Some("cli_token"), &mock_store).unwrap();
+        let result = resolve_github_token(Some("cli_token"), None, &mock_store).unwrap();
         assert_eq!(result, Some("cli_token".to_string()));
 
         // Test 2: Environment variable when no CLI token (with fresh mock store)
         let mock_store = MockSecretStore::new();
         mock_store.set_token("keyring_token").unwrap();
         env::set_var("GITHUB_TOKEN", "env_token");
-        let result = resolve_github_token(None, &mock_store).unwrap();
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
         assert_eq!(result, Some("env_token".to_string()));
 
         // Test 3: Keyring when no CLI token or env var (with fresh mock store)
         let mock_store = MockSecretStore::new();
         mock_store.set_token("keyring_token").unwrap();
         env::remove_var("GITHUB_TOKEN");
-        let result = resolve_github_token(None, &mock_store).unwrap();
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
         assert_eq!(result, Some("keyring_token".to_string()));
 
         // Test 4: None when no sources available (with fresh mock store)
         let mock_store = MockSecretStore::new();
         env::remove_var("GITHUB_TOKEN");
-        let result = resolve_github_token(None, &mock_store).unwrap();
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
         assert!(result.is_none());
 
         // Restore original state
@@ -856,32 +1602,62 @@ mod tests {
             Config {
                 owner: Some("owner".to_string()),
                 repo: None,
+                branch: None,
                 out_dir: None,
                 telemetry: None,
+                jobs: None,
+                cache_ttl_hours: None,
+                theme: None,
+                update_check: None,
+                manifest_dir: None,
             },
             Config {
                 owner: None,
                 repo: Some("repo".to_string()),
+                branch: None,
                 out_dir: None,
                 telemetry: None,
+                jobs: None,
+                cache_ttl_hours: None,
+                theme: None,
+                update_check: None,
+                manifest_dir: None,
             },
             Config {
                 owner: None,
                 repo: None,
+                branch: None,
                 out_dir: Some("./out".to_string()),
                 telemetry: None,
+                jobs: None,
+                cache_ttl_hours: None,
+                theme: None,
+                update_check: None,
+                manifest_dir: None,
             },
             Config {
                 owner: None,
                 repo: None,
+                branch: None,
                 out_dir: None,
                 telemetry: Some(false),
+                jobs: None,
+                cache_ttl_hours: None,
+                theme: None,
+                update_check: None,
+                manifest_dir: None,
             },
             Config {
                 owner: Some("owner".to_string()),
                 repo: Some("repo".to_string()),
+                branch: None,
                 out_dir: Some("./out".to_string()),
                 telemetry: Some(true),
+                jobs: None,
+                cache_ttl_hours: None,
+                theme: None,
+                update_check: None,
+                manifest_dir: None,
             },
         ];
 
@@ -1047,7 +1823,7 @@ out_dir = "/path/with/slashes"
 
         for whitespace in whitespace_cases {
             env::set_var("GITHUB_TOKEN", whitespace);
-            let result = resolve_github_token(None, &mock_store).unwrap();
+            let result = resolve_github_token(None, None, &mock_store).unwrap();
             assert!(
                 result.is_none(),
                 "Should return None for whitespace: {whitespace:?}"
@@ -1095,8 +1871,14 @@ out_dir = "/path/with/slashes"
         let config2 = Config {
             owner: None,
             repo: None,
+            branch: None,
             out_dir: None,
             telemetry: None,
+            jobs: None,
+            cache_ttl_hours: None,
+            theme: None,
+            update_check: None,
+            manifest_dir: None,
         };
 
         assert_eq!(config1.owner, config2.owner);
@@ -1139,39 +1921,39 @@ telemetry = true"#,
         // Scenario 1: Only CLI token
         let mock_store = MockSecretStore::new();
         env::remove_var("GITHUB_TOKEN");
-        let result = resolve_github_token(Some("cli"), &mock_store).unwrap();
+        let result = resolve_github_token(Some("cli"), None, &mock_store).unwrap();
         assert_eq!(result, Some("cli".to_string()));
 
         // Scenario 2: CLI + env (CLI wins)
         let mock_store = MockSecretStore::new();
         env::set_var("GITHUB_TOKEN", "env");
-        let result = resolve_github_token(Some("cli"), &mock_store).unwrap();
+        let result = resolve_github_token(Some("cli"), None, &mock_store).unwrap();
         assert_eq!(result, Some("cli".to_string()));
 
         // Scenario 3: CLI + env + keyring (CLI wins)
         let mock_store = MockSecretStore::new();
         mock_store.set_token("keyring").unwrap();
-        let result = resolve_github_token(Some("cli"), &mock_store).unwrap();
+        let result = resolve_github_token(Some("cli"), None, &mock_store).unwrap();
         assert_eq!(result, Some("cli".to_string()));
 
         // Scenario 4: env + keyring (env wins)
         let mock_store = MockSecretStore::new();
         mock_store.set_token("keyring").unwrap();
         env::set_var("GITHUB_TOKEN", "env");
-        let result = resolve_github_token(None, &mock_store).unwrap();
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
         assert_eq!(result, Some("env".to_string()));
 
         // Scenario 5: Only keyring
         let mock_store = MockSecretStore::new();
         mock_store.set_token("keyring").unwrap();
         env::remove_var("GITHUB_TOKEN");
-        let result = resolve_github_token(None, &mock_store).unwrap();
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
         assert_eq!(result, Some("keyring".to_string()));
 
         // Scenario 6: None available
         let mock_store = MockSecretStore::new();
         env::remove_var("GITHUB_TOKEN");
-        let result = resolve_github_token(None, &mock_store).unwrap();
+        let result = resolve_github_token(None, None, &mock_store).unwrap();
         assert!(result.is_none());
 
         // Restore state
@@ -1196,4 +1978,515 @@ telemetry = true"#,
         // Test that file name is correct
         assert_eq!(path.file_name().unwrap(), "config.toml");
     }
+
+    #[test]
+    fn test_parse_jobs_rejects_zero() {
+        assert_eq!(parse_jobs("8").unwrap(), 8);
+        assert_eq!(parse_jobs(" 1 ").unwrap(), 1);
+        assert!(parse_jobs("0")
+            .unwrap_err()
+            .to_string()
+            .contains("jobs must be at least 1"));
+        assert!(parse_jobs("-2").is_err());
+        assert!(parse_jobs("many").is_err());
+    }
+
+    #[test]
+    fn test_resolve_jobs_priority() {
+        let unset = Config::default();
+        let configured = Config {
+            jobs: Some(2),
+            ..Config::default()
+        };
+
+        // CLI flag wins over config
+        assert_eq!(resolve_jobs(Some(8), &configured), 8);
+        // Config wins over the default
+        assert_eq!(resolve_jobs(None, &configured), 2);
+        // Default when nothing is set
+        assert_eq!(resolve_jobs(None, &unset), DEFAULT_JOBS);
+    }
+
+    #[test]
+    fn test_resolve_owner_and_repo_prefers_a_repo_in_the_owner_flag() {
+        let configured = Config {
+            owner: Some("config-owner".to_string()),
+            repo: Some("config-rules".to_string()),
+            ..Config::default()
+        };
+        let resolve = |owner: Option<&str>, repo: Option<&str>| {
+            resolve_owner_and_repo(owner, repo.map(str::to_string), &configured)
+        };
+        let some = |owner: &str, repo: &str| (Some(owner.to_string()), Some(repo.to_string()));
+
+        // The repo in a pasted URL beats the configured one
+        assert_eq!(
+            resolve(Some("https://github.com/octo/team-rules"), None),
+            some("octo", "team-rules")
+        );
+        assert_eq!(
+            resolve(Some("octo/team-rules"), None),
+            some("octo", "team-rules")
+        );
+        // --repo still beats the URL
+        assert_eq!(
+            resolve(Some("octo/team-rules"), Some("other")),
+            some("octo", "other")
+        );
+        // A bare login keeps the configured repo
+        assert_eq!(resolve(Some("octo"), None), some("octo", "config-rules"));
+        assert_eq!(resolve(None, None), some("config-owner", "config-rules"));
+    }
+
+    #[test]
+    fn test_resolve_jobs_clamps_zero_from_config_file() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(&config_path, "jobs = 0\n").unwrap();
+
+        let config = load_config_file(&config_path).unwrap();
+        assert_eq!(config.jobs, Some(0));
+        assert_eq!(resolve_jobs(None, &config), 1);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_resolve_theme_priority() {
+        let original = std::env::var("NO_COLOR").ok();
+        let configured = Config {
+            theme: Some(Theme::HighContrast),
+            ..Config::default()
+        };
+
+        std::env::remove_var("NO_COLOR");
+        let from_flag = resolve_theme(Some(Theme::Default), &configured);
+        let from_config = resolve_theme(None, &configured);
+        let unset = resolve_theme(None, &Config::default());
+        std::env::set_var("NO_COLOR", "1");
+        let no_color = resolve_theme(None, &configured);
+        let flag_beats_no_color = resolve_theme(Some(Theme::HighContrast), &Config::default());
+        std::env::set_var("NO_COLOR", "");
+        let empty_no_color = resolve_theme(None, &Config::default());
+
+        match original {
+            Some(value) => std::env::set_var("NO_COLOR", value),
+            None => std::env::remove_var("NO_COLOR"),
+        }
+
+        assert_eq!(from_flag, Theme::Default);
+        assert_eq!(from_config, Theme::HighContrast);
+        assert_eq!(unset, Theme::Default);
+        assert_eq!(no_color, Theme::NoColor);
+        assert_eq!(flag_beats_no_color, Theme::HighContrast);
+        assert_eq!(empty_no_color, Theme::Default);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_update_config_value_theme() {
+        use std::env;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let original_home = env::var("HOME").ok();
+        let original_xdg = env::var("XDG_CONFIG_HOME").ok();
+        env::set_var("HOME", temp_dir.path());
+        env::set_var("XDG_CONFIG_HOME", temp_dir.path());
+
+        let unknown = update_config_value("theme", "solarized");
+        let set = update_config_value("theme", "high-contrast");
+        let saved = fs::read_to_string(config_file_path().unwrap()).unwrap_or_default();
+        let loaded = load_config().unwrap();
+
+        match original_home {
+            Some(home) => env::set_var("HOME", home),
+            None => env::remove_var("HOME"),
+        }
+        match original_xdg {
+            Some(xdg) => env::set_var("XDG_CONFIG_HOME", xdg),
+            None => env::remove_var("XDG_CONFIG_HOME"),
+        }
+
+        assert!(unknown.unwrap_err().to_string().contains("Unknown theme"));
+        assert!(set.is_ok());
+        assert!(saved.contains("theme = \"high-contrast\""));
+        assert_eq!(loaded.theme, Some(Theme::HighContrast));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_update_config_value_jobs() {
+        use std::env;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let original_home = env::var("HOME").ok();
+        let original_xdg = env::var("XDG_CONFIG_HOME").ok();
+        env::set_var("HOME", temp_dir.path());
+        env::set_var("XDG_CONFIG_HOME", temp_dir.path());
+
+        let zero = update_config_value("jobs", "0");
+        let set = update_config_value("jobs", "6");
+        let loaded = load_config().unwrap();
+        let deleted = delete_config_value("jobs");
+        let reloaded = load_config().unwrap();
+
+        match original_home {
+            Some(home) => env::set_var("HOME", home),
+            None => env::remove_var("HOME"),
+        }
+        match original_xdg {
+            Some(xdg) => env::set_var("XDG_CONFIG_HOME", xdg),
+            None => env::remove_var("XDG_CONFIG_HOME"),
+        }
+
+        assert!(zero.is_err());
+        assert!(set.is_ok());
+        assert_eq!(loaded.jobs, Some(6));
+        assert!(deleted.is_ok());
+        assert!(reloaded.jobs.is_none());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_update_config_value_manifest_dir() {
+        use std::env;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let original_xdg = env::var("XDG_CONFIG_HOME").ok();
+        env::set_var("XDG_CONFIG_HOME", temp_dir.path());
+
+        let empty = update_config_value("manifest_dir", " / ");
+        let set = update_config_value("manifest_dir", "bundles/");
+        let loaded = load_global_config().unwrap();
+
+        match original_xdg {
+            Some(xdg) => env::set_var("XDG_CONFIG_HOME", xdg),
+            None => env::remove_var("XDG_CONFIG_HOME"),
+        }
+
+        assert!(empty.is_err());
+        assert!(set.is_ok());
+        assert_eq!(loaded.manifest_dir.as_deref(), Some("bundles"));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_update_config_value_cache_ttl_invalid() {
+        use std::env;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let original_xdg = env::var("XDG_CONFIG_HOME").ok();
+        env::set_var("XDG_CONFIG_HOME", temp_dir.path());
+
+        let negative = update_config_value("cache_ttl_hours", "-1");
+        let set = update_config_value("cache_ttl_hours", "0");
+        let loaded = load_config().unwrap();
+
+        match original_xdg {
+            Some(xdg) => env::set_var("XDG_CONFIG_HOME", xdg),
+            None => env::remove_var("XDG_CONFIG_HOME"),
+        }
+
+        assert!(negative
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid cache TTL"));
+        assert!(set.is_ok());
+        assert_eq!(loaded.cache_ttl_hours, Some(0));
+    }
+
+    #[test]
+    fn resolve_with_source_prefers_flag_then_env_then_config() {
+        let env = Some(("CURSOR_RULES_OWNER", "env-owner"));
+
+        assert_eq!(
+            resolve_with_source(Some("flag-owner"), env, Some("config-owner")),
+            Some(("flag-owner", ValueSource::Flag))
+        );
+        assert_eq!(
+            resolve_with_source(None, env, Some("config-owner")),
+            Some(("env-owner", ValueSource::Env("CURSOR_RULES_OWNER")))
+        );
+        assert_eq!(
+            resolve_with_source(None, None, Some("config-owner")),
+            Some(("config-owner", ValueSource::ConfigFile))
+        );
+        assert_eq!(resolve_with_source::<&str>(None, None, None), None);
+    }
+
+    #[test]
+    fn value_source_labels() {
+        assert_eq!(ValueSource::Flag.to_string(), "flag");
+        assert_eq!(
+            ValueSource::Env("GITHUB_TOKEN").to_string(),
+            "env GITHUB_TOKEN"
+        );
+        assert_eq!(ValueSource::ConfigFile.to_string(), "config file");
+        assert_eq!(ValueSource::TokenFile.to_string(), "token file");
+        assert_eq!(ValueSource::Keyring.to_string(), "keyring");
+        assert_eq!(ValueSource::Default.to_string(), "default");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn token_source_reports_keyring_without_env() {
+        let original = std::env::var("GITHUB_TOKEN").ok();
+        std::env::remove_var("GITHUB_TOKEN");
+
+        let store = MockSecretStore::new();
+        store.set_token("secret").unwrap();
+        let resolved = resolve_github_token_with_source(None, None, &store);
+        let flagged = resolve_github_token_with_source(Some("cli"), None, &store);
+
+        if let Some(token) = original {
+            std::env::set_var("GITHUB_TOKEN", token);
+        }
+        assert_eq!(
+            resolved.unwrap(),
+            Some(("secret".to_string(), ValueSource::Keyring))
+        );
+        assert_eq!(
+            flagged.unwrap(),
+            Some(("cli".to_string(), ValueSource::Flag))
+        );
+    }
+
+    #[test]
+    fn test_config_roundtrip_with_branch_and_jobs() {
+        let config = Config {
+            owner: Some("owner".to_string()),
+            branch: Some("develop".to_string()),
+            jobs: Some(8),
+            ..Config::default()
+        };
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        assert!(serialized.contains("branch = \"develop\""));
+        assert!(serialized.contains("jobs = 8"));
+
+        let deserialized: Config = toml::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.branch.as_deref(), Some("develop"));
+        assert_eq!(deserialized.jobs, Some(8));
+        assert_eq!(deserialized.owner.as_deref(), Some("owner"));
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_update_and_delete_branch() {
+        use std::env;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let original_home = env::var("HOME").ok();
+        let original_xdg = env::var("XDG_CONFIG_HOME").ok();
+        env::set_var("HOME", temp_dir.path());
+        env::set_var("XDG_CONFIG_HOME", temp_dir.path().join("config"));
+
+        let set = update_config_value("branch", "develop");
+        let after_set = load_config();
+        let deleted = delete_config_value("branch");
+        let after_delete = load_config();
+        let unknown = update_config_value("brnach", "develop");
+
+        match original_home {
+            Some(home) => env::set_var("HOME", home),
+            None => env::remove_var("HOME"),
+        }
+        match original_xdg {
+            Some(xdg) => env::set_var("XDG_CONFIG_HOME", xdg),
+            None => env::remove_var("XDG_CONFIG_HOME"),
+        }
+
+        set.unwrap();
+        assert_eq!(after_set.unwrap().branch.as_deref(), Some("develop"));
+        deleted.unwrap();
+        assert!(after_delete.unwrap().branch.is_none());
+        assert!(unknown
+            .unwrap_err()
+            .to_string()
+            .contains("Unknown config key: brnach"));
+    }
+
+    #[test]
+    fn test_local_config_overrides_global_field_by_field() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let global_path = temp_dir.path().join("global.toml");
+        std::fs::write(
+            &global_path,
+            "owner = \"global-owner\"\nrepo = \"global-repo\"\njobs = 2\n",
+        )
+        .unwrap();
+
+        let project = temp_dir.path().join("project");
+        let nested = project.join("src").join("deep");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir(project.join(".git")).unwrap();
+        std::fs::write(
+            project.join(LOCAL_CONFIG_FILE),
+            "repo = \"team-rules\"\nout_dir = \"./rules\"\n",
+        )
+        .unwrap();
+
+        let config = load_merged_config(&global_path, &nested).unwrap();
+        assert_eq!(config.owner.as_deref(), Some("global-owner"));
+        assert_eq!(config.repo.as_deref(), Some("team-rules"));
+        assert_eq!(config.out_dir.as_deref(), Some("./rules"));
+        assert_eq!(config.jobs, Some(2));
+        assert!(config.branch.is_none());
+    }
+
+    #[test]
+    fn test_local_config_search_stops_at_git_root() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        // A config above the git root must not leak into the project
+        std::fs::write(
+            temp_dir.path().join(LOCAL_CONFIG_FILE),
+            "owner = \"outer\"\n",
+        )
+        .unwrap();
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir_all(project.join(".git")).unwrap();
+
+        assert_eq!(find_local_config(&project), None);
+
+        let global_path = temp_dir.path().join("missing.toml");
+        let config = load_merged_config(&global_path, &project).unwrap();
+        assert!(config.owner.is_none());
+    }
+
+    #[test]
+    fn test_local_config_outside_git_checks_only_start_dir() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let child = temp_dir.path().join("child");
+        std::fs::create_dir_all(&child).unwrap();
+        std::fs::write(
+            temp_dir.path().join(LOCAL_CONFIG_FILE),
+            "owner = \"parent\"\n",
+        )
+        .unwrap();
+
+        // The temp dir may itself sit inside a git checkout; only assert when it doesn't
+        if !child.ancestors().any(|dir| dir.join(".git").exists()) {
+            assert_eq!(find_local_config(&child), None);
+        }
+        assert_eq!(
+            find_local_config(temp_dir.path()),
+            Some(temp_dir.path().join(LOCAL_CONFIG_FILE))
+        );
+    }
+
+    #[test]
+    fn test_find_cursor_root_prefers_nearest_cursor_dir() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        let nested = project.join("packages").join("web").join("src");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir(project.join(".git")).unwrap();
+
+        // Without a .cursor anywhere the git root is used
+        assert_eq!(find_cursor_root(&nested), Some(project.clone()));
+
+        std::fs::create_dir(project.join(".cursor")).unwrap();
+        assert_eq!(find_cursor_root(&nested), Some(project.clone()));
+        assert_eq!(find_cursor_root(&project), Some(project.clone()));
+
+        // A nested package with its own .cursor wins over the repository root
+        let package = project.join("packages").join("web");
+        std::fs::create_dir(package.join(".cursor")).unwrap();
+        assert_eq!(find_cursor_root(&nested), Some(package));
+    }
+
+    #[test]
+    fn test_find_cursor_root_finds_cursor_dir_outside_git() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        let nested = project.join("a").join("b").join("c");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        // The temp dir may itself sit inside a git checkout; only assert when it doesn't
+        if !nested.ancestors().any(|dir| dir.join(".git").exists()) {
+            assert_eq!(find_cursor_root(&nested), None);
+            std::fs::create_dir(project.join(".cursor")).unwrap();
+            assert_eq!(find_cursor_root(&nested), Some(project));
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_validate_token_parses_oauth_scopes() {
+        let mut server = mockito::Server::new_async().await;
+        let with_scopes = server
+            .mock("GET", "/user")
+            .match_header("authorization", "Bearer scoped")
+            .with_status(200)
+            .with_header("x-oauth-scopes", "repo, read:org,gist")
+            .with_body(r#"{"login": "octocat"}"#)
+            .create_async()
+            .await;
+        let without_scopes = server
+            .mock("GET", "/user")
+            .match_header("authorization", "Bearer fine-grained")
+            .with_status(200)
+            .with_body(r#"{"login": "octocat"}"#)
+            .create_async()
+            .await;
+
+        std::env::set_var("OCTO_BASE", server.url());
+        let scoped = validate_github_token_with_scopes("scoped").await;
+        let unscoped = validate_github_token_with_scopes("fine-grained").await;
+        std::env::remove_var("OCTO_BASE");
+
+        with_scopes.assert_async().await;
+        without_scopes.assert_async().await;
+        let scoped = scoped.unwrap().unwrap();
+        assert_eq!(scoped, vec!["repo", "read:org", "gist"]);
+        assert!(has_repo_scope(&scoped));
+        assert_eq!(unscoped.unwrap(), None);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_validate_token_rejected() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/user")
+            .with_status(401)
+            .with_body(r#"{"message": "Bad credentials"}"#)
+            .create_async()
+            .await;
+
+        std::env::set_var("OCTO_BASE", server.url());
+        let result = validate_github_token_with_scopes("bad").await;
+        std::env::remove_var("OCTO_BASE");
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("GitHub rejected the token (401 Unauthorized)"));
+    }
+
+    #[test]
+    fn test_has_repo_scope() {
+        assert!(has_repo_scope(&["public_repo".to_string()]));
+        assert!(!has_repo_scope(&[
+            "gist".to_string(),
+            "read:org".to_string()
+        ]));
+        assert!(!has_repo_scope(&[]));
+    }
 }
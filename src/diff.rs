@@ -0,0 +1,67 @@
+//! Line diffs between local rule files and their repository versions, for `cursor-rules diff`.
+
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+
+/// Lines context shown around each change in a unified diff
+const CONTEXT_LINES: usize = 3;
+
+/// How one rule differs from the repository version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileDiff {
+    /// Path of the rule in the repository
+    pub source: String,
+    /// Local file it is copied to
+    pub destination: String,
+    /// Whether the local file exists; missing files count every line as added
+    pub exists_locally: bool,
+    /// Lines the repository version adds
+    pub added: usize,
+    /// Lines the repository version removes
+    pub removed: usize,
+}
+
+/// Lines added and removed going from `local` to `remote`
+pub fn summarize(local: &str, remote: &str) -> (usize, usize) {
+    let diff = TextDiff::from_lines(local, remote);
+    diff.iter_all_changes()
+        .fold((0, 0), |(added, removed), change| match change.tag() {
+            ChangeTag::Insert => (added + 1, removed),
+            ChangeTag::Delete => (added, removed + 1),
+            ChangeTag::Equal => (added, removed),
+        })
+}
+
+/// Unified diff from `local` to `remote`, empty when they are identical
+pub fn unified(local: &str, remote: &str, local_label: &str, remote_label: &str) -> String {
+    TextDiff::from_lines(local, remote)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(local_label, remote_label)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_counts_added_and_removed_lines() {
+        let local = "# React\nUse hooks\nPrefer classes\n";
+        let remote = "# React\nUse hooks\nPrefer function components\nUse TypeScript\n";
+        assert_eq!(summarize(local, remote), (2, 1));
+        assert_eq!(summarize(local, local), (0, 0));
+        // A file missing locally is all additions
+        assert_eq!(summarize("", remote), (4, 0));
+    }
+
+    #[test]
+    fn unified_diff_marks_changes_with_labels() {
+        let patch = unified("a\nb\n", "a\nc\n", "local/x.mdc", "repo/x.mdc");
+        assert_eq!(
+            patch,
+            "--- local/x.mdc\n+++ repo/x.mdc\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n"
+        );
+        assert_eq!(unified("same\n", "same\n", "a", "b"), "");
+    }
+}
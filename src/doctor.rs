@@ -0,0 +1,329 @@
+//! Environment diagnostics for `cursor-rules doctor`.
+//!
+//! Each check reports whether it passed, what it found and, on failure, how to fix it.
+//! Critical failures (an unreadable config file, a rejected token, GitHub out of reach)
+//! make the command exit non-zero; the rest are shown as warnings because the CLI can
+//! work around them, e.g. by passing `--owner` or a token through the environment.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::config::{self, ConfigError, SecretStore};
+use crate::output::{self, Status};
+
+/// Outcome of a single diagnostic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    /// Whether a failure of this check makes `doctor` exit non-zero
+    pub critical: bool,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            passed: true,
+            critical: false,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn fail(
+        name: &'static str,
+        critical: bool,
+        detail: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Self {
+            name,
+            passed: false,
+            critical,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn status(&self) -> Status {
+        match (self.passed, self.critical) {
+            (true, _) => Status::Ok,
+            (false, true) => Status::Error,
+            (false, false) => Status::Warning,
+        }
+    }
+}
+
+/// Whether any critical check failed
+pub fn has_critical_failure(checks: &[Check]) -> bool {
+    checks.iter().any(|check| check.critical && !check.passed)
+}
+
+/// One line per check, with the remediation hint indented below failures
+pub fn render(checks: &[Check]) -> String {
+    let mut lines = Vec::new();
+    for check in checks {
+        lines.push(output::status(
+            check.status(),
+            format!("{}: {}", check.name, check.detail),
+        ));
+        if let Some(hint) = &check.hint {
+            lines.push(format!("    {hint}"));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Every config file that would be merged parses
+pub fn check_config(paths: &[PathBuf]) -> Check {
+    const NAME: &str = "Config";
+    for path in paths {
+        if let Err(e) = config::load_config_file(path) {
+            return Check::fail(
+                NAME,
+                true,
+                format!("{} is unreadable: {e}", path.display()),
+                format!("Fix or delete {}", path.display()),
+            );
+        }
+    }
+    match paths.iter().filter(|path| path.exists()).count() {
+        0 => Check::pass(NAME, "no config file yet; using defaults"),
+        count => Check::pass(NAME, format!("{count} file(s) readable")),
+    }
+}
+
+/// The OS keyring can be read
+pub fn check_keyring(store: &dyn SecretStore) -> Check {
+    const NAME: &str = "Keyring";
+    match store.get_token() {
+        Ok(Some(_)) => Check::pass(NAME, "available, token stored"),
+        Ok(None) => Check::pass(NAME, "available, no token stored"),
+        Err(e) => Check::fail(
+            NAME,
+            false,
+            format!("unavailable: {e}"),
+            "Provide a token through GITHUB_TOKEN or --token-file instead",
+        ),
+    }
+}
+
+/// An owner is configured or can be detected from git or the gh CLI
+pub fn check_owner(configured: Option<&str>, detected: Option<String>) -> Check {
+    const NAME: &str = "Owner";
+    match (configured, detected) {
+        (Some(owner), _) => Check::pass(NAME, format!("{owner} (configured)")),
+        (None, Some(owner)) => Check::pass(NAME, format!("{owner} (detected)")),
+        (None, None) => Check::fail(
+            NAME,
+            false,
+            "not configured or detectable",
+            "Run `cursor-rules config set owner <name>` or pass --owner",
+        ),
+    }
+}
+
+/// A token is present and GitHub accepts it
+pub async fn check_token(token: Option<&str>) -> Check {
+    const NAME: &str = "Token";
+    let Some(token) = token else {
+        return Check::fail(
+            NAME,
+            false,
+            "none found; requests are anonymous and limited to 60 per hour",
+            "Run `cursor-rules auth login` or set GITHUB_TOKEN",
+        );
+    };
+    match config::validate_github_token_with_scopes(token).await {
+        Ok(None) => Check::pass(NAME, "valid"),
+        Ok(Some(scopes)) if scopes.is_empty() => Check::pass(NAME, "valid (no scopes)"),
+        Ok(Some(scopes)) => Check::pass(NAME, format!("valid (scopes: {})", scopes.join(", "))),
+        Err(ConfigError::InvalidValue(reason)) => Check::fail(
+            NAME,
+            true,
+            reason,
+            "Create a new token, then run `cursor-rules auth login` or update GITHUB_TOKEN",
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            false,
+            format!("could not be checked: {e}"),
+            "See the GitHub check below",
+        ),
+    }
+}
+
+/// GitHub answers and there is API budget left
+pub async fn check_github(token: Option<&str>) -> Check {
+    const NAME: &str = "GitHub";
+    let remaining = match crate::github::client::octocrab(token) {
+        Ok(octocrab) => crate::auth::rate_limit_remaining(&octocrab).await,
+        Err(e) => Err(e),
+    };
+    match remaining {
+        Ok(Some(0)) => Check::fail(
+            NAME,
+            true,
+            "reachable, but the rate limit is exhausted",
+            "Wait for the limit to reset, or authenticate to get a higher one",
+        ),
+        Ok(Some(remaining)) => Check::pass(NAME, format!("reachable, {remaining} requests left")),
+        Ok(None) => Check::pass(NAME, "reachable"),
+        Err(e) => Check::fail(
+            NAME,
+            true,
+            format!("unreachable: {e:#}"),
+            "Check your network or proxy, raise --timeout, or use --offline with a warm cache",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedStore(Result<Option<String>, String>);
+
+    impl SecretStore for FixedStore {
+        fn get_token(&self) -> Result<Option<String>, ConfigError> {
+            self.0.clone().map_err(ConfigError::KeyringError)
+        }
+
+        fn set_token(&self, _token: &str) -> Result<(), ConfigError> {
+            Ok(())
+        }
+
+        fn delete_token(&self) -> Result<(), ConfigError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn config_check_reports_unparsable_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.toml");
+        let valid = dir.path().join("config.toml");
+        std::fs::write(&valid, "owner = \"octo\"\n").unwrap();
+        let broken = dir.path().join(".cursor-rules.toml");
+        std::fs::write(&broken, "owner = [").unwrap();
+
+        let check = check_config(std::slice::from_ref(&missing));
+        assert!(check.passed);
+        assert_eq!(check.detail, "no config file yet; using defaults");
+
+        assert!(check_config(&[missing.clone(), valid.clone()]).passed);
+
+        let check = check_config(&[valid, broken.clone()]);
+        assert!(!check.passed);
+        assert!(check.critical);
+        assert!(check.detail.contains(".cursor-rules.toml"));
+        assert_eq!(
+            check.hint,
+            Some(format!("Fix or delete {}", broken.display()))
+        );
+    }
+
+    #[test]
+    fn keyring_check_is_a_warning_when_unavailable() {
+        assert!(check_keyring(&FixedStore(Ok(None))).passed);
+        assert_eq!(
+            check_keyring(&FixedStore(Ok(Some("t".into())))).detail,
+            "available, token stored"
+        );
+
+        let check = check_keyring(&FixedStore(Err("no secret service".into())));
+        assert!(!check.passed);
+        assert!(!check.critical);
+        assert!(check.detail.contains("no secret service"));
+    }
+
+    #[test]
+    fn owner_check_prefers_configured_owner() {
+        assert_eq!(
+            check_owner(Some("org"), Some("me".into())).detail,
+            "org (configured)"
+        );
+        assert_eq!(check_owner(None, Some("me".into())).detail, "me (detected)");
+
+        let check = check_owner(None, None);
+        assert!(!check.passed);
+        assert!(check.hint.unwrap().contains("--owner"));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn token_check_distinguishes_missing_and_rejected_tokens() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/user")
+            .match_header("authorization", "Bearer good")
+            .with_status(200)
+            .with_header("x-oauth-scopes", "repo")
+            .with_body(r#"{"login": "octocat"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/user")
+            .match_header("authorization", "Bearer bad")
+            .with_status(401)
+            .with_body(r#"{"message": "Bad credentials"}"#)
+            .create_async()
+            .await;
+        std::env::set_var("OCTO_BASE", server.url());
+
+        let missing = check_token(None).await;
+        let good = check_token(Some("good")).await;
+        let bad = check_token(Some("bad")).await;
+        std::env::remove_var("OCTO_BASE");
+
+        assert!(!missing.passed);
+        assert!(!missing.critical);
+        assert!(good.passed, "{good:?}");
+        assert_eq!(good.detail, "valid (scopes: repo)");
+        assert!(!bad.passed);
+        assert!(bad.critical);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn github_check_reports_budget_and_outages() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/rate_limit")
+            .with_status(200)
+            .with_body(r#"{"resources": {"core": {"remaining": 42}}}"#)
+            .create_async()
+            .await;
+        std::env::set_var("OCTO_BASE", server.url());
+        let reachable = check_github(None).await;
+        // Nothing listens here
+        std::env::set_var("OCTO_BASE", "http://127.0.0.1:9");
+        let unreachable = check_github(None).await;
+        std::env::remove_var("OCTO_BASE");
+
+        assert!(reachable.passed);
+        assert_eq!(reachable.detail, "reachable, 42 requests left");
+        assert!(!unreachable.passed);
+        assert!(unreachable.critical);
+        assert!(has_critical_failure(&[reachable, unreachable]));
+    }
+
+    #[test]
+    fn render_shows_hints_under_failures() {
+        let checks = [
+            Check::pass("Config", "1 file(s) readable"),
+            Check::fail("Owner", false, "not configured", "Pass --owner"),
+        ];
+        // Test output is not a terminal, so the markers are uncoloured
+        assert_eq!(
+            render(&checks),
+            "✓ Config: 1 file(s) readable\n⚠ Owner: not configured\n    Pass --owner"
+        );
+        assert!(!has_critical_failure(&checks));
+    }
+}
@@ -0,0 +1,259 @@
+//! Saved manifests for `cursor-rules favorite`.
+//!
+//! A favorite remembers which repository a quick-add manifest lives in, so
+//! `favorite apply NAME` can re-run it without retyping `--owner`/`--repo`. Favorites are
+//! kept in a JSON file next to the config file.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// File in the config directory holding the favorites
+const FAVORITES_FILE: &str = "favorites.json";
+
+/// A quick-add manifest saved under a short name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Favorite {
+    pub name: String,
+    pub owner: String,
+    pub repo: String,
+    /// Branch to apply from unless `--branch` is given; `main` when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Manifest ID as accepted by `quick-add`
+    pub manifest: String,
+}
+
+impl Favorite {
+    /// Parse `owner/repo[@branch]:manifest`, naming the favorite `name` or, by default,
+    /// after the manifest
+    pub fn parse(spec: &str, name: Option<&str>) -> anyhow::Result<Self> {
+        let usage = || format!("expected OWNER/REPO[@BRANCH]:MANIFEST, got {spec:?}");
+        let (repo_spec, manifest) = spec.split_once(':').with_context(usage)?;
+        let (repo_spec, branch) = match repo_spec.split_once('@') {
+            Some((repo_spec, branch)) => (repo_spec, Some(branch)),
+            None => (repo_spec, None),
+        };
+        let (owner, repo) = repo_spec.split_once('/').with_context(usage)?;
+        let manifest = manifest.trim();
+        if [owner, repo, manifest].iter().any(|part| part.is_empty())
+            || repo.contains('/')
+            || branch.is_some_and(str::is_empty)
+        {
+            anyhow::bail!(usage());
+        }
+
+        let name = match name {
+            Some(name) if name.trim().is_empty() => anyhow::bail!("Favorite name is empty"),
+            Some(name) => name.trim().to_string(),
+            None => default_name(manifest),
+        };
+        Ok(Self {
+            name,
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            branch: branch.map(str::to_string),
+            manifest: manifest.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for Favorite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.owner, self.repo)?;
+        if let Some(branch) = &self.branch {
+            write!(f, "@{branch}")?;
+        }
+        write!(f, ":{}", self.manifest)
+    }
+}
+
+/// The manifest's file name without its extension, e.g. `react` for
+/// `quick-add/react.txt`
+fn default_name(manifest: &str) -> String {
+    let file = manifest.rsplit('/').next().unwrap_or(manifest);
+    let stem = Path::new(file)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(file);
+    stem.to_string()
+}
+
+/// The saved favorites, in the order they were added
+#[derive(Debug)]
+pub struct Favorites {
+    path: PathBuf,
+    entries: Vec<Favorite>,
+}
+
+impl Favorites {
+    /// Load the favorites from the config directory
+    pub fn load() -> anyhow::Result<Self> {
+        let config_file = crate::config::config_file_path()?;
+        let dir = config_file
+            .parent()
+            .context("config file has no parent directory")?;
+        Self::load_from(&dir.join(FAVORITES_FILE))
+    }
+
+    /// Load the favorites kept at `path`; a missing file holds none
+    ///
+    /// Unlike the recent list, an unreadable file is an error: favorites are typed in by
+    /// hand and silently dropping them on the next save would lose them.
+    pub fn load_from(path: &Path) -> anyhow::Result<Self> {
+        let entries = match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .with_context(|| format!("reading favorites from {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("reading {}", path.display()));
+            }
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+
+    pub fn entries(&self) -> &[Favorite] {
+        &self.entries
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Favorite> {
+        self.entries.iter().find(|favorite| favorite.name == name)
+    }
+
+    /// Save `favorite`, replacing one with the same name in place. Returns the entry it
+    /// replaced, if any.
+    pub fn add(&mut self, favorite: Favorite) -> Option<Favorite> {
+        match self
+            .entries
+            .iter_mut()
+            .find(|existing| existing.name == favorite.name)
+        {
+            Some(existing) => Some(std::mem::replace(existing, favorite)),
+            None => {
+                self.entries.push(favorite);
+                None
+            }
+        }
+    }
+
+    /// Forget the favorite called `name`; false when there was none
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|favorite| favorite.name != name);
+        self.entries.len() != before
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let text = serde_json::to_string_pretty(&self.entries)?;
+        std::fs::write(&self.path, text).with_context(|| format!("writing {}", self.path.display()))
+    }
+}
+
+/// One favorite per line: its name and what it applies
+pub fn render(entries: &[Favorite]) -> String {
+    if entries.is_empty() {
+        return "No favorites; save one with `cursor-rules favorite add OWNER/REPO:MANIFEST`"
+            .to_string();
+    }
+    let width = entries
+        .iter()
+        .map(|favorite| favorite.name.len())
+        .max()
+        .unwrap_or(0);
+    entries
+        .iter()
+        .map(|favorite| format!("{:<width$}  {favorite}", favorite.name))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs_parse_with_optional_branch_and_name() {
+        let favorite = Favorite::parse("octo/rules:quick-add/react.txt", None).unwrap();
+        assert_eq!(
+            favorite,
+            Favorite {
+                name: "react".into(),
+                owner: "octo".into(),
+                repo: "rules".into(),
+                branch: None,
+                manifest: "quick-add/react.txt".into(),
+            }
+        );
+        assert_eq!(favorite.to_string(), "octo/rules:quick-add/react.txt");
+
+        let favorite = Favorite::parse("octo/rules@dev:frontend", Some("web")).unwrap();
+        assert_eq!(favorite.name, "web");
+        assert_eq!(favorite.branch.as_deref(), Some("dev"));
+        assert_eq!(favorite.to_string(), "octo/rules@dev:frontend");
+
+        for bad in [
+            "octo/rules",
+            "octo:react",
+            "/rules:react",
+            "octo/rules:",
+            "octo/rules@:react",
+            "octo/a/b:react",
+        ] {
+            assert!(
+                Favorite::parse(bad, None).is_err(),
+                "{bad} should be rejected"
+            );
+        }
+        assert!(Favorite::parse("octo/rules:react", Some(" ")).is_err());
+    }
+
+    #[test]
+    fn add_replaces_same_name_and_remove_forgets() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut favorites = Favorites::load_from(&dir.path().join("favorites.json")).unwrap();
+        assert!(favorites.entries().is_empty());
+
+        let react = Favorite::parse("octo/rules:react", None).unwrap();
+        let vue = Favorite::parse("octo/rules:vue", None).unwrap();
+        assert_eq!(favorites.add(react.clone()), None);
+        assert_eq!(favorites.add(vue.clone()), None);
+
+        // Same name again: replaced in place, not duplicated
+        let moved = Favorite::parse("other/rules@dev:react.yaml", None).unwrap();
+        assert_eq!(favorites.add(moved.clone()), Some(react));
+        assert_eq!(favorites.entries(), [moved.clone(), vue.clone()]);
+        assert_eq!(favorites.get("react"), Some(&moved));
+
+        assert!(favorites.remove("react"));
+        assert!(!favorites.remove("react"));
+        assert_eq!(favorites.entries(), [vue]);
+        assert_eq!(favorites.get("react"), None);
+    }
+
+    #[test]
+    fn saved_favorites_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("favorites.json");
+        let mut favorites = Favorites::load_from(&path).unwrap();
+        favorites.add(Favorite::parse("octo/rules@dev:quick-add/react.txt", None).unwrap());
+        favorites.add(Favorite::parse("octo/rules:backend", Some("api")).unwrap());
+        favorites.save().unwrap();
+
+        let reloaded = Favorites::load_from(&path).unwrap();
+        assert_eq!(reloaded.entries(), favorites.entries());
+        assert_eq!(
+            render(reloaded.entries()),
+            "react  octo/rules@dev:quick-add/react.txt\napi    octo/rules:backend"
+        );
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(Favorites::load_from(&path).is_err());
+    }
+}
@@ -11,12 +11,12 @@ use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use super::{RepoLocator, RepoNode};
 
-/// Cache expiration time (24 hours)
-const CACHE_EXPIRY_HOURS: u64 = 24;
+/// Default cache expiration time (24 hours)
+pub const DEFAULT_CACHE_TTL_HOURS: u64 = 24;
 
 /// Cache metadata stored in meta.json
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +33,60 @@ pub struct CacheMetadata {
     pub branch: String,
 }
 
+/// A repository cache directory removed by [`FileSystemCache::prune`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PrunedRepo {
+    /// `owner/repo`, or the directory name when it has no readable metadata
+    pub name: String,
+    /// Bytes freed by removing the directory
+    pub bytes: u64,
+}
+
+/// Outcome of pruning the cache down to a size limit
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PruneReport {
+    /// Repositories evicted, in eviction order
+    pub evicted: Vec<PrunedRepo>,
+    /// Total bytes freed
+    pub bytes_freed: u64,
+    /// Bytes still used by the cache after pruning
+    pub bytes_remaining: u64,
+}
+
+/// What is wrong with a cached file found by [`FileSystemCache::verify`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheProblemKind {
+    /// `tree.json` cannot be read or parsed
+    CorruptTree,
+    /// A blob's content does not hash to the SHA it is stored under
+    BlobMismatch,
+    /// A blob could not be read
+    UnreadableBlob,
+}
+
+/// A corrupt file in the cache
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CacheProblem {
+    /// `owner/repo`, or the directory name when it has no readable metadata
+    pub repo: String,
+    pub path: PathBuf,
+    pub kind: CacheProblemKind,
+    pub detail: String,
+}
+
+/// Outcome of checking every cached tree and blob
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CacheVerifyReport {
+    /// Trees that parsed
+    pub trees_ok: usize,
+    /// Blobs whose content matches their SHA
+    pub blobs_ok: usize,
+    /// Blobs cached under a path hash because their SHA was not known, which cannot be checked
+    pub blobs_unchecked: usize,
+    pub problems: Vec<CacheProblem>,
+}
+
 /// Persistent cache trait for abstracting cache operations
 pub trait PersistentCache {
     /// Get cached tree data if fresh, otherwise None
@@ -51,11 +105,21 @@ pub trait PersistentCache {
         last_modified: Option<String>,
     ) -> Result<()>;
 
-    /// Get cached blob content if exists
-    async fn get_blob_cache(&self, content_sha: &str) -> Result<Option<String>>;
+    /// Get cached blob content for a repository if it exists. Content is kept as raw
+    /// bytes, so files that are not valid UTF-8 are cached too.
+    async fn get_blob_cache(
+        &self,
+        locator: &RepoLocator,
+        content_sha: &str,
+    ) -> Result<Option<Vec<u8>>>;
 
-    /// Store blob content in cache
-    async fn store_blob_cache(&self, content_sha: &str, content: &str) -> Result<()>;
+    /// Store blob content in a repository's cache
+    async fn store_blob_cache(
+        &self,
+        locator: &RepoLocator,
+        content_sha: &str,
+        content: &[u8],
+    ) -> Result<()>;
 
     /// Check if cache is fresh (within expiry time)
     fn is_cache_fresh(&self, locator: &RepoLocator) -> Result<bool>;
@@ -73,13 +137,23 @@ pub trait PersistentCache {
 /// File system implementation of persistent cache
 pub struct FileSystemCache {
     cache_root: PathBuf,
+    /// Hours a fetched tree stays fresh; 0 means always stale
+    ttl_hours: u64,
 }
 
 impl FileSystemCache {
     /// Create new filesystem cache instance
     pub fn new() -> Result<Self> {
+        Self::with_ttl(DEFAULT_CACHE_TTL_HOURS)
+    }
+
+    /// Create a filesystem cache whose trees expire after `ttl_hours`
+    pub fn with_ttl(ttl_hours: u64) -> Result<Self> {
         let cache_root = get_cache_directory()?;
-        Ok(Self { cache_root })
+        Ok(Self {
+            cache_root,
+            ttl_hours,
+        })
     }
 
     /// Compute SHA-1 hash for cache directory name
@@ -107,7 +181,34 @@ impl FileSystemCache {
             .join("tree.json")
     }
 
-    /// Load cache metadata
+    /// Get blob cache file path
+    fn get_blob_cache_path(&self, locator: &RepoLocator, content_sha: &str) -> PathBuf {
+        self.get_repo_cache_dir(locator)
+            .join("blobs")
+            .join(format!("{content_sha}.mdc"))
+    }
+
+    /// Store blob content by copying an already downloaded file, so large blobs never
+    /// need to be held in memory
+    pub async fn store_blob_cache_file(
+        &self,
+        locator: &RepoLocator,
+        content_sha: &str,
+        source: &Path,
+    ) -> Result<()> {
+        let blob_path = self.get_blob_cache_path(locator, content_sha);
+        let blobs_dir = blob_path.parent().unwrap();
+        fs::create_dir_all(blobs_dir)
+            .with_context(|| format!("Failed to create blobs directory {}", blobs_dir.display()))?;
+
+        fs::copy(source, &blob_path)
+            .with_context(|| format!("Failed to write blob cache to {}", blob_path.display()))?;
+
+        Ok(())
+    }
+
+    /// Load cache metadata. Unparseable metadata is treated like a corrupted tree cache:
+    /// the repository's cache is cleared and `None` returned so it is fetched again.
     fn load_metadata(&self, locator: &RepoLocator) -> Result<Option<CacheMetadata>> {
         let meta_path = self.get_metadata_path(locator);
         if !meta_path.exists() {
@@ -117,10 +218,23 @@ impl FileSystemCache {
         let content = fs::read_to_string(&meta_path)
             .with_context(|| format!("Failed to read metadata from {}", meta_path.display()))?;
 
-        let metadata: CacheMetadata =
-            serde_json::from_str(&content).with_context(|| "Failed to parse cache metadata")?;
-
-        Ok(Some(metadata))
+        match serde_json::from_str::<CacheMetadata>(&content) {
+            Ok(metadata) => Ok(Some(metadata)),
+            Err(e) => {
+                tracing::warn!(
+                    "Corrupted cache metadata detected at {}: {}. Removing cache directory.",
+                    meta_path.display(),
+                    e
+                );
+                let repo_dir = self.get_repo_cache_dir(locator);
+                if let Err(remove_err) = fs::remove_dir_all(&repo_dir) {
+                    tracing::warn!("Failed to clear corrupted cache: {}", remove_err);
+                    // Fallback: at least drop the unreadable metadata
+                    let _ = fs::remove_file(&meta_path);
+                }
+                Ok(None)
+            }
+        }
     }
 
     /// Save cache metadata
@@ -163,6 +277,140 @@ impl FileSystemCache {
         }
     }
 
+    /// Load the tree on disk regardless of its age, e.g. after GitHub confirmed it is unchanged
+    pub fn get_stale_tree_cache(&self, locator: &RepoLocator) -> Result<Option<Vec<RepoNode>>> {
+        let tree_path = self.get_tree_cache_path(locator);
+        if !tree_path.exists() {
+            return Ok(None);
+        }
+        self.try_load_tree_cache(&tree_path).map(Some)
+    }
+
+    /// Evict repository caches until the cache uses at most `max_bytes` on disk.
+    ///
+    /// Directories without readable `meta.json` go first, then repositories in order of
+    /// oldest `fetched_at`.
+    pub fn prune(&self, max_bytes: u64) -> Result<PruneReport> {
+        let mut report = PruneReport::default();
+        if !self.cache_root.exists() {
+            return Ok(report);
+        }
+
+        let mut total = 0;
+        // (fetched_at, name, path, bytes); `None` sorts first so orphans are evicted first
+        let mut candidates = Vec::new();
+        let entries = fs::read_dir(&self.cache_root)
+            .with_context(|| "Failed to read cache root directory")?;
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            let bytes = dir_size(&path)?;
+            total += bytes;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+
+            let metadata = fs::read_to_string(path.join("meta.json"))
+                .ok()
+                .and_then(|content| serde_json::from_str::<CacheMetadata>(&content).ok());
+            let (fetched_at, name) = match metadata {
+                Some(meta) => (
+                    Some(meta.fetched_at),
+                    format!("{}/{}", meta.owner, meta.repo),
+                ),
+                None => (None, entry.file_name().to_string_lossy().into_owned()),
+            };
+            candidates.push((fetched_at, name, path, bytes));
+        }
+        candidates.sort_by_key(|candidate| candidate.0);
+
+        for (_, name, path, bytes) in candidates {
+            if total <= max_bytes {
+                break;
+            }
+            fs::remove_dir_all(&path)
+                .with_context(|| format!("Failed to remove cache directory {}", path.display()))?;
+            total -= bytes;
+            report.bytes_freed += bytes;
+            report.evicted.push(PrunedRepo { name, bytes });
+        }
+
+        report.bytes_remaining = total;
+        Ok(report)
+    }
+
+    /// Re-parse every cached `tree.json` and recompute the Git blob SHA of every cached blob
+    /// whose SHA the repository's tree records. Nothing is changed on disk.
+    pub fn verify(&self) -> Result<CacheVerifyReport> {
+        let mut report = CacheVerifyReport::default();
+        if !self.cache_root.exists() {
+            return Ok(report);
+        }
+
+        let entries = fs::read_dir(&self.cache_root)
+            .with_context(|| "Failed to read cache root directory")?;
+        for entry in entries.flatten() {
+            if !entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                continue;
+            }
+            let repo_dir = entry.path();
+            let repo = fs::read_to_string(repo_dir.join("meta.json"))
+                .ok()
+                .and_then(|content| serde_json::from_str::<CacheMetadata>(&content).ok())
+                .map(|meta| format!("{}/{}", meta.owner, meta.repo))
+                .unwrap_or_else(|| entry.file_name().to_string_lossy().into_owned());
+
+            // Blobs are stored under their Git SHA when the tree knew it
+            let mut known_shas = std::collections::HashSet::new();
+            let tree_path = repo_dir.join("tree").join("tree.json");
+            if tree_path.exists() {
+                match self.try_load_tree_cache(&tree_path) {
+                    Ok(nodes) => {
+                        report.trees_ok += 1;
+                        known_shas.extend(nodes.into_iter().filter_map(|node| node.blob_sha));
+                    }
+                    Err(e) => report.problems.push(CacheProblem {
+                        repo: repo.clone(),
+                        path: tree_path,
+                        kind: CacheProblemKind::CorruptTree,
+                        detail: format!("{e:#}"),
+                    }),
+                }
+            }
+
+            let Ok(blobs) = fs::read_dir(repo_dir.join("blobs")) else {
+                continue;
+            };
+            let mut blobs: Vec<PathBuf> = blobs.flatten().map(|blob| blob.path()).collect();
+            blobs.sort();
+            for blob_path in blobs {
+                let Some(sha) = blob_path.file_stem().and_then(|stem| stem.to_str()) else {
+                    continue;
+                };
+                if !known_shas.contains(sha) {
+                    report.blobs_unchecked += 1;
+                    continue;
+                }
+                match crate::copier::git_blob_sha_of_file(&blob_path) {
+                    Ok(actual) if actual.eq_ignore_ascii_case(sha) => report.blobs_ok += 1,
+                    Ok(actual) => report.problems.push(CacheProblem {
+                        repo: repo.clone(),
+                        path: blob_path.clone(),
+                        kind: CacheProblemKind::BlobMismatch,
+                        detail: format!("content hashes to {actual}"),
+                    }),
+                    Err(e) => report.problems.push(CacheProblem {
+                        repo: repo.clone(),
+                        path: blob_path.clone(),
+                        kind: CacheProblemKind::UnreadableBlob,
+                        detail: format!("{e:#}"),
+                    }),
+                }
+            }
+        }
+        Ok(report)
+    }
+
     /// Try to load tree cache with detailed error handling
     fn try_load_tree_cache(&self, tree_path: &std::path::Path) -> Result<Vec<RepoNode>> {
         let content = fs::read_to_string(tree_path)
@@ -276,54 +524,35 @@ impl PersistentCache for FileSystemCache {
         Ok(())
     }
 
-    async fn get_blob_cache(&self, content_sha: &str) -> Result<Option<String>> {
-        // For blob cache, we need to search across all repo caches
-        // This is a simplified implementation - in practice, we'd need better indexing
-        let cache_dirs = fs::read_dir(&self.cache_root)
-            .with_context(|| "Failed to read cache root directory")?;
-
-        for entry in cache_dirs {
-            let entry = entry?;
-            let blobs_dir = entry.path().join("blobs");
-            let blob_path = blobs_dir.join(format!("{content_sha}.mdc"));
-
-            if blob_path.exists() {
-                let content = fs::read_to_string(&blob_path).with_context(|| {
-                    format!("Failed to read blob cache from {}", blob_path.display())
-                })?;
-                return Ok(Some(content));
-            }
+    async fn get_blob_cache(
+        &self,
+        locator: &RepoLocator,
+        content_sha: &str,
+    ) -> Result<Option<Vec<u8>>> {
+        let blob_path = self.get_blob_cache_path(locator, content_sha);
+        if !blob_path.exists() {
+            return Ok(None);
         }
 
-        Ok(None)
+        let content = fs::read(&blob_path)
+            .with_context(|| format!("Failed to read blob cache from {}", blob_path.display()))?;
+        Ok(Some(content))
     }
 
-    async fn store_blob_cache(&self, content_sha: &str, content: &str) -> Result<()> {
-        // For blob storage, we'll store in the first available repo cache
-        // This is simplified - a better implementation would track which repo the blob belongs to
-        let cache_dirs = fs::read_dir(&self.cache_root)
-            .with_context(|| "Failed to read cache root directory")?;
-
-        for entry in cache_dirs {
-            let entry = entry?;
-            let blobs_dir = entry.path().join("blobs");
-
-            if blobs_dir.exists() || blobs_dir.parent().is_some_and(|p| p.exists()) {
-                fs::create_dir_all(&blobs_dir).with_context(|| {
-                    format!("Failed to create blobs directory {}", blobs_dir.display())
-                })?;
-
-                let blob_path = blobs_dir.join(format!("{content_sha}.mdc"));
-                fs::write(&blob_path, content).with_context(|| {
-                    format!("Failed to write blob cache to {}", blob_path.display())
-                })?;
+    async fn store_blob_cache(
+        &self,
+        locator: &RepoLocator,
+        content_sha: &str,
+        content: &[u8],
+    ) -> Result<()> {
+        let blob_path = self.get_blob_cache_path(locator, content_sha);
+        let blobs_dir = blob_path.parent().unwrap();
+        fs::create_dir_all(blobs_dir)
+            .with_context(|| format!("Failed to create blobs directory {}", blobs_dir.display()))?;
 
-                return Ok(());
-            }
-        }
+        fs::write(&blob_path, content)
+            .with_context(|| format!("Failed to write blob cache to {}", blob_path.display()))?;
 
-        // If no existing cache directories, skip blob caching
-        // This will be handled better when we track repo context for blobs
         Ok(())
     }
 
@@ -333,10 +562,17 @@ impl PersistentCache for FileSystemCache {
             None => return Ok(false),
         };
 
-        let now = Utc::now();
-        let expiry_time = metadata.fetched_at + chrono::Duration::hours(CACHE_EXPIRY_HOURS as i64);
+        if self.ttl_hours == 0 {
+            return Ok(false);
+        }
+
+        // A TTL too large to represent never expires
+        let expiry_time = i64::try_from(self.ttl_hours)
+            .ok()
+            .and_then(chrono::Duration::try_hours)
+            .and_then(|ttl| metadata.fetched_at.checked_add_signed(ttl));
 
-        Ok(now < expiry_time)
+        Ok(expiry_time.is_none_or(|expiry| Utc::now() < expiry))
     }
 
     async fn clear_cache(&self, locator: &RepoLocator) -> Result<()> {
@@ -359,15 +595,18 @@ impl PersistentCache for FileSystemCache {
         let cache_dirs = fs::read_dir(&self.cache_root)
             .with_context(|| "Failed to read cache root directory")?;
 
-        for entry in cache_dirs {
-            let entry = entry?;
-            if entry.file_type()?.is_dir() {
-                let meta_path = entry.path().join("meta.json");
-                if let Ok(content) = fs::read_to_string(&meta_path) {
-                    if let Ok(metadata) = serde_json::from_str::<CacheMetadata>(&content) {
-                        repos.push((metadata.owner, metadata.repo, metadata.fetched_at));
-                    }
-                }
+        // Unreadable entries and corrupt metadata are skipped rather than failing the listing
+        for entry in cache_dirs.flatten() {
+            if !entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                continue;
+            }
+            let meta_path = entry.path().join("meta.json");
+            let Ok(content) = fs::read_to_string(&meta_path) else {
+                continue;
+            };
+            match serde_json::from_str::<CacheMetadata>(&content) {
+                Ok(metadata) => repos.push((metadata.owner, metadata.repo, metadata.fetched_at)),
+                Err(e) => tracing::warn!("Skipping corrupted {}: {e}", meta_path.display()),
             }
         }
 
@@ -379,6 +618,23 @@ impl PersistentCache for FileSystemCache {
     }
 }
 
+/// Total size in bytes of a file, or of every file below a directory
+fn dir_size(path: &std::path::Path) -> Result<u64> {
+    let metadata = fs::symlink_metadata(path)
+        .with_context(|| format!("Failed to read metadata for {}", path.display()))?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+
+    let mut total = 0;
+    for entry in fs::read_dir(path)
+        .with_context(|| format!("Failed to read directory {}", path.display()))?
+    {
+        total += dir_size(&entry?.path())?;
+    }
+    Ok(total)
+}
+
 /// Get XDG-compliant cache directory
 pub fn get_cache_directory() -> Result<PathBuf> {
     let cache_dir =
@@ -407,6 +663,7 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let cache = FileSystemCache {
             cache_root: temp_dir.path().to_path_buf(),
+            ttl_hours: DEFAULT_CACHE_TTL_HOURS,
         };
         (cache, temp_dir)
     }
@@ -501,6 +758,58 @@ mod tests {
         assert!(!cache.is_cache_fresh(&locator).unwrap());
     }
 
+    /// Save metadata for the test locator as if it was fetched `hours_ago`
+    fn save_metadata_fetched(cache: &FileSystemCache, hours_ago: i64) {
+        let metadata = CacheMetadata {
+            fetched_at: Utc::now() - chrono::Duration::hours(hours_ago),
+            etag: None,
+            last_modified: None,
+            owner: "test".to_string(),
+            repo: "repo".to_string(),
+            branch: "main".to_string(),
+        };
+        cache
+            .save_metadata(&create_test_locator(), &metadata)
+            .unwrap();
+    }
+
+    #[test]
+    fn cache_ttl_zero_is_always_stale() {
+        let (mut cache, _temp_dir) = create_test_cache();
+        cache.ttl_hours = 0;
+
+        save_metadata_fetched(&cache, 0);
+        assert!(!cache.is_cache_fresh(&create_test_locator()).unwrap());
+    }
+
+    #[test]
+    fn cache_ttl_default_is_24_hours() {
+        let (cache, _temp_dir) = create_test_cache();
+        assert_eq!(cache.ttl_hours, DEFAULT_CACHE_TTL_HOURS);
+
+        save_metadata_fetched(&cache, 23);
+        assert!(cache.is_cache_fresh(&create_test_locator()).unwrap());
+        save_metadata_fetched(&cache, 25);
+        assert!(!cache.is_cache_fresh(&create_test_locator()).unwrap());
+    }
+
+    #[test]
+    fn cache_ttl_custom_values() {
+        let (mut cache, _temp_dir) = create_test_cache();
+        let locator = create_test_locator();
+
+        cache.ttl_hours = 2;
+        save_metadata_fetched(&cache, 1);
+        assert!(cache.is_cache_fresh(&locator).unwrap());
+        save_metadata_fetched(&cache, 3);
+        assert!(!cache.is_cache_fresh(&locator).unwrap());
+
+        // A huge TTL pins the cache instead of overflowing
+        cache.ttl_hours = u64::MAX;
+        save_metadata_fetched(&cache, 24 * 365);
+        assert!(cache.is_cache_fresh(&locator).unwrap());
+    }
+
     #[tokio::test]
     async fn file_locking_concurrent_access() {
         let (cache, _temp_dir) = create_test_cache();
@@ -534,8 +843,7 @@ mod tests {
             name: "test.mdc".to_string(),
             path: "test.mdc".to_string(),
             kind: super::super::NodeKind::RuleFile,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         }];
 
         cache
@@ -561,8 +869,7 @@ mod tests {
             name: "test.mdc".to_string(),
             path: "test.mdc".to_string(),
             kind: super::super::NodeKind::RuleFile,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         }];
 
         cache
@@ -589,8 +896,7 @@ mod tests {
             name: "test.mdc".to_string(),
             path: "test.mdc".to_string(),
             kind: super::super::NodeKind::RuleFile,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         }];
 
         cache
@@ -632,6 +938,112 @@ mod tests {
         assert_eq!(repos[0].1, locator.repo);
     }
 
+    #[tokio::test]
+    async fn verify_flags_corrupt_blobs_and_trees() {
+        let (cache, _temp_dir) = create_test_cache();
+        let locator = create_test_locator();
+        let good = b"# Good rule\n";
+        let good_sha = crate::copier::git_blob_sha(good);
+        let planted_sha = crate::copier::git_blob_sha(b"# Original rule\n");
+        let node = |name: &str, sha: &str| RepoNode {
+            name: name.to_string(),
+            path: name.to_string(),
+            kind: crate::github::NodeKind::RuleFile,
+            blob_sha: Some(sha.to_string()),
+            ..Default::default()
+        };
+        cache
+            .store_tree_cache(
+                &locator,
+                &[node("good.mdc", &good_sha), node("bad.mdc", &planted_sha)],
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        cache
+            .store_blob_cache(&locator, &good_sha, good)
+            .await
+            .unwrap();
+        // Stored under the original's SHA, but the bytes were damaged on disk
+        cache
+            .store_blob_cache(&locator, &planted_sha, b"# Origin")
+            .await
+            .unwrap();
+        // Path-hash keys have no recorded SHA to check against
+        cache
+            .store_blob_cache(&locator, "path-hash-key", b"anything")
+            .await
+            .unwrap();
+
+        let report = cache.verify().unwrap();
+        assert_eq!(report.trees_ok, 1);
+        assert_eq!(report.blobs_ok, 1);
+        assert_eq!(report.blobs_unchecked, 1);
+        assert_eq!(report.problems.len(), 1);
+        let problem = &report.problems[0];
+        assert_eq!(problem.kind, CacheProblemKind::BlobMismatch);
+        assert_eq!(problem.repo, "test/repo");
+        assert_eq!(
+            problem.path,
+            cache.get_blob_cache_path(&locator, &planted_sha)
+        );
+
+        // A damaged tree is reported too, and its blobs can no longer be checked
+        fs::write(cache.get_tree_cache_path(&locator), "[{").unwrap();
+        let report = cache.verify().unwrap();
+        assert_eq!(report.trees_ok, 0);
+        assert_eq!(report.blobs_unchecked, 3);
+        assert_eq!(report.problems[0].kind, CacheProblemKind::CorruptTree);
+    }
+
+    #[tokio::test]
+    async fn corrupted_metadata_is_cleared_instead_of_failing() {
+        let (cache, _temp_dir) = create_test_cache();
+        let locator = create_test_locator();
+        let nodes = vec![RepoNode {
+            name: "test.mdc".to_string(),
+            path: "test.mdc".to_string(),
+            kind: crate::github::NodeKind::RuleFile,
+            ..Default::default()
+        }];
+        cache
+            .store_tree_cache(&locator, &nodes, None, None)
+            .await
+            .unwrap();
+
+        // A second, healthy repository must still be listed
+        let healthy = RepoLocator {
+            owner: "healthy".to_string(),
+            ..create_test_locator()
+        };
+        cache
+            .store_tree_cache(&healthy, &nodes, None, None)
+            .await
+            .unwrap();
+
+        let meta_path = cache.get_metadata_path(&locator);
+        fs::write(&meta_path, "{ not json").unwrap();
+
+        let repos = cache.list_cached_repos().unwrap();
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].0, "healthy");
+
+        assert!(!cache.is_cache_fresh(&locator).unwrap());
+        assert!(!cache.get_repo_cache_dir(&locator).exists());
+        assert!(cache.get_metadata(&locator).unwrap().is_none());
+        assert!(cache
+            .get_tree_cache(&locator, false)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(cache
+            .get_tree_cache(&healthy, false)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
     #[tokio::test]
     async fn corrupted_cache_auto_recovery() {
         let _ = tracing_subscriber::fmt::try_init();
@@ -710,8 +1122,7 @@ mod tests {
             name: "test.mdc".to_string(),
             path: "test.mdc".to_string(),
             kind: crate::github::NodeKind::RuleFile,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         }];
         cache
             .store_tree_cache(&locator, &nodes, None, None)
@@ -719,15 +1130,77 @@ mod tests {
             .unwrap();
 
         // Cache should be empty initially
-        let result = cache.get_blob_cache(content_sha).await.unwrap();
+        let result = cache.get_blob_cache(&locator, content_sha).await.unwrap();
         assert!(result.is_none());
 
         // Store content in cache
-        cache.store_blob_cache(content_sha, content).await.unwrap();
+        cache
+            .store_blob_cache(&locator, content_sha, content.as_bytes())
+            .await
+            .unwrap();
 
         // Should be able to retrieve it
-        let result = cache.get_blob_cache(content_sha).await.unwrap();
-        assert_eq!(result.unwrap(), content);
+        let result = cache.get_blob_cache(&locator, content_sha).await.unwrap();
+        assert_eq!(result.unwrap(), content.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn blob_cache_round_trips_non_utf8_bytes() {
+        let (cache, _temp_dir) = create_test_cache();
+        let locator = create_test_locator();
+        // Latin-1 text and a stray continuation byte: not valid UTF-8
+        let content: &[u8] = b"# Caf\xe9 rules\n\x80\xff\x00end";
+
+        cache
+            .store_blob_cache(&locator, "binary-sha", content)
+            .await
+            .unwrap();
+        let cached = cache.get_blob_cache(&locator, "binary-sha").await.unwrap();
+        assert_eq!(cached.as_deref(), Some(content));
+    }
+
+    #[tokio::test]
+    async fn blob_cache_is_scoped_per_repo() {
+        let (cache, _temp_dir) = create_test_cache();
+        let first = create_test_locator();
+        let second = RepoLocator {
+            owner: "other-owner".to_string(),
+            repo: "other-repo".to_string(),
+            branch: "main".to_string(),
+        };
+        let content_sha = "same-sha";
+
+        // No tree cache is needed before storing blobs
+        cache
+            .store_blob_cache(&first, content_sha, b"first repo")
+            .await
+            .unwrap();
+        assert!(cache
+            .get_blob_cache(&second, content_sha)
+            .await
+            .unwrap()
+            .is_none());
+
+        cache
+            .store_blob_cache(&second, content_sha, b"second repo")
+            .await
+            .unwrap();
+        assert_eq!(
+            cache
+                .get_blob_cache(&first, content_sha)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(&b"first repo"[..])
+        );
+        assert_eq!(
+            cache
+                .get_blob_cache(&second, content_sha)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(&b"second repo"[..])
+        );
     }
 
     #[tokio::test]
@@ -740,8 +1213,7 @@ mod tests {
             name: "test.mdc".to_string(),
             path: "test.mdc".to_string(),
             kind: crate::github::NodeKind::RuleFile,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         }];
 
         let etag = Some("test-etag-123".to_string());
@@ -769,8 +1241,7 @@ mod tests {
             name: "test.mdc".to_string(),
             path: "test.mdc".to_string(),
             kind: crate::github::NodeKind::RuleFile,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         }];
 
         let valid_path = temp_dir.path().join("valid.json");
@@ -798,4 +1269,66 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("empty"));
     }
+
+    /// Seed a repository cache fetched `hours_ago` with a payload of `bytes` bytes
+    fn seed_repo_cache(cache: &FileSystemCache, repo: &str, hours_ago: i64, bytes: usize) {
+        let locator = RepoLocator {
+            owner: "test".to_string(),
+            repo: repo.to_string(),
+            branch: "main".to_string(),
+        };
+        let metadata = CacheMetadata {
+            fetched_at: Utc::now() - chrono::Duration::hours(hours_ago),
+            etag: None,
+            last_modified: None,
+            owner: "test".to_string(),
+            repo: repo.to_string(),
+            branch: "main".to_string(),
+        };
+        cache.save_metadata(&locator, &metadata).unwrap();
+        fs::write(
+            cache.get_repo_cache_dir(&locator).join("payload"),
+            vec![0u8; bytes],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn prune_evicts_orphans_then_oldest_until_under_limit() {
+        let (cache, temp_dir) = create_test_cache();
+        seed_repo_cache(&cache, "old", 48, 4000);
+        seed_repo_cache(&cache, "new", 1, 4000);
+        let orphan = temp_dir.path().join("orphan");
+        fs::create_dir_all(&orphan).unwrap();
+        fs::write(orphan.join("payload"), vec![0u8; 1000]).unwrap();
+
+        let total = dir_size(temp_dir.path()).unwrap();
+        let report = cache.prune(total - 2000).unwrap();
+
+        let names: Vec<_> = report.evicted.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["orphan", "test/old"]);
+        assert_eq!(report.bytes_freed + report.bytes_remaining, total);
+        assert_eq!(report.bytes_remaining, dir_size(temp_dir.path()).unwrap());
+        assert!(!orphan.exists());
+        assert_eq!(cache.list_cached_repos().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn prune_within_limit_evicts_nothing() {
+        let (cache, temp_dir) = create_test_cache();
+        seed_repo_cache(&cache, "repo", 1, 1000);
+
+        let total = dir_size(temp_dir.path()).unwrap();
+        let report = cache.prune(total).unwrap();
+        assert!(report.evicted.is_empty());
+        assert_eq!(report.bytes_remaining, total);
+    }
+
+    #[test]
+    fn prune_missing_cache_root_is_empty() {
+        let (mut cache, temp_dir) = create_test_cache();
+        cache.cache_root = temp_dir.path().join("missing");
+
+        assert_eq!(cache.prune(0).unwrap(), PruneReport::default());
+    }
 }
@@ -0,0 +1,169 @@
+//! Construction of the HTTP clients used to talk to GitHub.
+//!
+//! Every Octocrab and reqwest client is built here so the resolved token, the request
+//! timeout (`--timeout`) and the base URL override (`OCTO_BASE`, used by tests and GitHub
+//! Enterprise) are applied the same way everywhere. It also records whether `--offline`
+//! was given, which callers check before making any request.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use octocrab::Octocrab;
+
+/// Request timeout used when `--timeout` is not given
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+static TIMEOUT: OnceLock<Duration> = OnceLock::new();
+static TOKEN: OnceLock<Option<String>> = OnceLock::new();
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Set the token and timeout used by [`authenticated`] clients built afterwards. Only the
+/// first call takes effect.
+pub fn configure(token: Option<String>, timeout: Duration) {
+    let _ = TOKEN.set(token);
+    let _ = TIMEOUT.set(timeout);
+}
+
+/// The token set by [`configure`], if any
+pub fn token() -> Option<&'static str> {
+    TOKEN.get().and_then(Option::as_deref)
+}
+
+/// Forbid (or allow again) network access for the rest of the run
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether `--offline` is in effect, so everything must be served from the cache
+pub fn offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// The configured request timeout
+pub fn timeout() -> Duration {
+    TIMEOUT
+        .get()
+        .copied()
+        .unwrap_or(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
+}
+
+/// Base URL override from `OCTO_BASE`, if set
+pub fn base_url() -> Option<String> {
+    std::env::var("OCTO_BASE").ok()
+}
+
+/// Build an Octocrab client with the configured token, timeout and base URL
+pub fn authenticated() -> Result<Octocrab> {
+    octocrab(token())
+}
+
+/// Build an Octocrab client for `token` with the configured timeout and base URL
+pub fn octocrab(token: Option<&str>) -> Result<Octocrab> {
+    build(token, base_url().as_deref(), timeout())
+}
+
+/// Build an Octocrab client, authenticated when `token` is given, against `base_url`
+/// (api.github.com when `None`), giving up on connections and reads after `timeout`
+pub fn build(token: Option<&str>, base_url: Option<&str>, timeout: Duration) -> Result<Octocrab> {
+    let mut builder = Octocrab::builder()
+        .set_connect_timeout(Some(timeout))
+        .set_read_timeout(Some(timeout))
+        .set_write_timeout(Some(timeout));
+    if let Some(base_url) = base_url {
+        builder = builder
+            .base_uri(base_url)
+            .with_context(|| format!("Invalid GitHub base URL: {base_url}"))?;
+    }
+    if let Some(token) = token {
+        builder = builder.personal_token(token.to_string());
+    }
+    builder.build().context("Failed to build GitHub client")
+}
+
+/// Plain HTTP client for `download_url`s, using the configured timeout.
+///
+/// Only connecting and each read are bounded, so large downloads that keep making
+/// progress are not cut off.
+pub fn http() -> Result<reqwest::Client> {
+    http_with_timeout(timeout())
+}
+
+fn http_with_timeout(timeout: Duration) -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .connect_timeout(timeout)
+        .read_timeout(timeout)
+        .build()
+        .context("Failed to build HTTP client")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn slow_server() -> mockito::ServerGuard {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/repos/owner/repo")
+            .with_status(200)
+            .with_body_from_request(|_| {
+                std::thread::sleep(Duration::from_secs(1));
+                b"{}".to_vec()
+            })
+            .create_async()
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn built_client_sends_token() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/user")
+            .match_header("authorization", "Bearer secret-token")
+            .with_status(200)
+            .with_body(r#"{"login": "octocat"}"#)
+            .create_async()
+            .await;
+
+        let client = build(
+            Some("secret-token"),
+            Some(&server.url()),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        let user: serde_json::Value = client.get("/user", None::<&()>).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(user["login"], "octocat");
+    }
+
+    #[tokio::test]
+    async fn octocrab_requests_time_out() {
+        let server = slow_server().await;
+        let client = build(None, Some(&server.url()), Duration::from_millis(200)).unwrap();
+
+        let started = std::time::Instant::now();
+        let result = client
+            ._get(format!("{}/repos/owner/repo", server.url()))
+            .await;
+
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn http_requests_time_out() {
+        let server = slow_server().await;
+        let client = http_with_timeout(Duration::from_millis(200)).unwrap();
+
+        let error = client
+            .get(format!("{}/repos/owner/repo", server.url()))
+            .send()
+            .await
+            .unwrap_err();
+
+        assert!(error.is_timeout());
+    }
+}
@@ -0,0 +1,174 @@
+//! Commit history lookups for `--since`.
+//!
+//! The tree API carries no dates, so the last commit touching each file is looked up
+//! through the commits API, one request per file.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+
+use super::RepoLocator;
+
+/// Parse a `--since` value: an RFC 3339 timestamp, a `YYYY-MM-DD` date (midnight UTC),
+/// or a relative age such as `30m`, `12h`, `7d` or `2w`.
+pub fn parse_since(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_since_at(value, Utc::now())
+}
+
+fn parse_since_at(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+
+    let invalid = || {
+        format!(
+            "Invalid time {value:?}; use a date like 2024-05-01, a timestamp, or an age like 7d"
+        )
+    };
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let age = match unit {
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    now.checked_sub_signed(age).ok_or_else(invalid)
+}
+
+/// Time of the last commit on the locator's branch that touched `path`, or `None` when
+/// GitHub reports no such commit
+pub async fn last_modified(locator: &RepoLocator, path: &str) -> Result<Option<DateTime<Utc>>> {
+    let octocrab = super::client::authenticated()?;
+    let endpoint = format!("/repos/{}/{}/commits", locator.owner, locator.repo);
+    let commits: serde_json::Value = octocrab
+        .get(
+            &endpoint,
+            Some(&[
+                ("path", path),
+                ("sha", locator.branch.as_str()),
+                ("per_page", "1"),
+            ]),
+        )
+        .await
+        .with_context(|| format!("Failed to look up the history of {path}"))?;
+
+    let Some(date) = commits[0]["commit"]["committer"]["date"].as_str() else {
+        return Ok(None);
+    };
+    let date = DateTime::parse_from_rfc3339(date)
+        .with_context(|| format!("Invalid commit date {date:?} for {path}"))?;
+    Ok(Some(date.with_timezone(&Utc)))
+}
+
+/// Entries of `paths` changed after `since`, in their original order, plus the ones whose
+/// last change could not be determined. Those are kept too, so callers can warn about them.
+pub async fn changed_since(
+    locator: &RepoLocator,
+    paths: &[String],
+    since: DateTime<Utc>,
+) -> (Vec<String>, Vec<String>) {
+    let mut changed = Vec::new();
+    let mut unknown = Vec::new();
+    for path in paths {
+        match last_modified(locator, path).await {
+            Ok(Some(modified)) if modified <= since => {}
+            Ok(Some(_)) => changed.push(path.clone()),
+            Ok(None) | Err(_) => {
+                changed.push(path.clone());
+                unknown.push(path.clone());
+            }
+        }
+    }
+    (changed, unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn absolute_times_parse_as_utc() {
+        let now = at("2024-06-15T12:00:00Z");
+        assert_eq!(
+            parse_since_at("2024-05-01", now),
+            Ok(at("2024-05-01T00:00:00Z"))
+        );
+        assert_eq!(
+            parse_since_at("2024-05-01T08:30:00Z", now),
+            Ok(at("2024-05-01T08:30:00Z"))
+        );
+        assert_eq!(
+            parse_since_at("2024-05-01T10:30:00+02:00", now),
+            Ok(at("2024-05-01T08:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn relative_ages_count_back_from_now() {
+        let now = at("2024-06-15T12:00:00Z");
+        assert_eq!(parse_since_at("7d", now), Ok(at("2024-06-08T12:00:00Z")));
+        assert_eq!(parse_since_at("2w", now), Ok(at("2024-06-01T12:00:00Z")));
+        assert_eq!(parse_since_at("12h", now), Ok(at("2024-06-15T00:00:00Z")));
+        assert_eq!(parse_since_at(" 30m ", now), Ok(at("2024-06-15T11:30:00Z")));
+    }
+
+    #[test]
+    fn malformed_times_are_rejected() {
+        let now = at("2024-06-15T12:00:00Z");
+        for value in ["", "7", "d", "7y", "-7d", "2024-13-01", "yesterday"] {
+            assert!(parse_since_at(value, now).is_err(), "{value:?}");
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn changed_since_keeps_recent_and_undated_entries() {
+        let mut server = mockito::Server::new_async().await;
+        let commit =
+            |date: &str| serde_json::json!([{"commit": {"committer": {"date": date}}}]).to_string();
+        for (path, body) in [
+            ("old.mdc", commit("2024-01-01T00:00:00Z")),
+            ("new.mdc", commit("2024-06-01T00:00:00Z")),
+            ("gone.mdc", "[]".to_string()),
+        ] {
+            server
+                .mock("GET", "/repos/o/r/commits")
+                .match_query(mockito::Matcher::AllOf(vec![
+                    mockito::Matcher::UrlEncoded("path".into(), path.into()),
+                    mockito::Matcher::UrlEncoded("sha".into(), "main".into()),
+                ]))
+                .with_status(200)
+                .with_body(body)
+                .create_async()
+                .await;
+        }
+        std::env::set_var("OCTO_BASE", server.url());
+
+        let locator = RepoLocator {
+            owner: "o".into(),
+            repo: "r".into(),
+            branch: "main".into(),
+        };
+        let paths = ["old.mdc", "new.mdc", "gone.mdc"].map(String::from);
+        let (changed, unknown) = changed_since(&locator, &paths, at("2024-03-01T00:00:00Z")).await;
+        std::env::remove_var("OCTO_BASE");
+
+        assert_eq!(changed, ["new.mdc", "gone.mdc"]);
+        assert_eq!(unknown, ["gone.mdc"]);
+    }
+}
@@ -3,11 +3,13 @@
 //! This module handles parsing of manifest files in different formats (.txt, .yaml, .json)
 //! and provides validation of rule file paths within a repository tree.
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::future::Future;
 use thiserror::Error;
 
-use super::{RepoLocator, RepoTree};
+use super::{NodeKind, RepoLocator, RepoTree};
 
 /// Error types for manifest parsing and validation
 #[derive(Error, Debug)]
@@ -60,6 +62,15 @@ pub struct ManifestSchema {
     pub name: String,
     pub description: Option<String>,
     pub rules: Vec<String>,
+    /// Paths or globs removed from `rules` after expansion
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    /// Other `quick-add/` manifests whose rules are merged into this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub includes: Vec<String>,
+    /// Subdirectory of `--out` that this manifest's rules are copied into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_dir: Option<String>,
 }
 
 /// A parsed and validated manifest
@@ -75,6 +86,8 @@ pub struct Manifest {
     pub errors: Vec<String>,
     /// Validation warnings encountered
     pub warnings: Vec<String>,
+    /// Subdirectory of the output directory to copy into, if the manifest sets one
+    pub target_dir: Option<String>,
 }
 
 /// Parse a .txt manifest (one rule path per line)
@@ -99,20 +112,32 @@ pub fn parse_json_manifest(content: &str) -> Result<ManifestSchema, ManifestErro
     serde_json::from_str(content).map_err(|e| ManifestError::ParseError(e.to_string()))
 }
 
-/// Find manifest files in quick-add directory and resolve priority
+/// Directory holding quick-add manifests unless `--manifest-dir` or the `manifest_dir`
+/// config key names another
+pub const DEFAULT_MANIFEST_DIR: &str = "quick-add";
+
+/// Find manifest files in the tree's manifest directory (see
+/// [`RepoTree::with_manifest_dir`]) and resolve priority. Hidden manifests are skipped
+/// unless `show_hidden`.
 pub async fn find_manifests_in_quickadd(
     repo_tree: &mut RepoTree,
     locator: &RepoLocator,
     force_refresh: bool,
+    show_hidden: bool,
 ) -> anyhow::Result<HashMap<String, (ManifestFormat, String)>> {
     let mut manifests: HashMap<String, (ManifestFormat, String)> = HashMap::new();
 
-    // Get children of quick-add directory
+    let manifest_dir = repo_tree.manifest_dir().to_string();
     let quickadd_children = repo_tree
-        .children(locator, "quick-add", force_refresh)
+        .children(locator, &manifest_dir, force_refresh)
         .await?;
 
     for child in quickadd_children {
+        // Only the manifest's own name counts, so a manifest directory such as
+        // `.github/bundles` is not hidden along with it
+        if !show_hidden && child.name.starts_with('.') {
+            continue;
+        }
         if let Some(format) = get_manifest_format(&child.name) {
             let basename = get_basename(&child.name);
 
@@ -147,6 +172,25 @@ pub async fn validate_manifest_entries(
             continue;
         }
 
+        // Expand wildcards against the repository tree
+        if is_glob(entry) {
+            let matches = expand_glob(entry, repo_tree, locator, force_refresh).await?;
+            match matches {
+                Some(matches) if matches.is_empty() => {
+                    warnings.push(format!("No rule files match pattern: {entry}"));
+                }
+                Some(matches) => {
+                    for path in matches {
+                        if !valid_entries.contains(&path) {
+                            valid_entries.push(path);
+                        }
+                    }
+                }
+                None => warnings.push(format!("Invalid glob pattern ignored: {entry}")),
+            }
+            continue;
+        }
+
         // Check if it's a .mdc file
         if !entry.ends_with(".mdc") {
             warnings.push(format!("Non-.mdc file ignored: {entry}"));
@@ -157,13 +201,167 @@ pub async fn validate_manifest_entries(
         if file_exists_in_repo(entry, repo_tree, locator, force_refresh).await? {
             valid_entries.push(entry.to_string());
         } else {
-            errors.push(format!("File not found in repository: {entry}"));
+            let siblings = repo_tree
+                .children(locator, parent_dir(entry), force_refresh)
+                .await?;
+            let candidates = siblings
+                .iter()
+                .filter(|node| node.kind == NodeKind::RuleFile)
+                .map(|node| node.path.as_str());
+            match suggest_closest(entry, candidates) {
+                Some(suggestion) => errors.push(format!(
+                    "File not found in repository: {entry} (did you mean {suggestion}?)"
+                )),
+                None => errors.push(format!("File not found in repository: {entry}")),
+            }
         }
     }
 
     Ok((valid_entries, errors, warnings))
 }
 
+/// Pick the candidate whose file name is closest to `entry`'s by edit distance, as long as
+/// the two are similar enough for the suggestion to be useful.
+pub fn suggest_closest<'a, I>(entry: &str, candidates: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let name = file_name(entry);
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != entry)
+        .map(|candidate| (edit_distance(name, file_name(candidate)), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings, counted in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+fn parent_dir(path: &str) -> &str {
+    path.rfind('/').map_or("", |pos| &path[..pos])
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+/// Whether a manifest entry contains glob metacharacters
+fn is_glob(entry: &str) -> bool {
+    entry.contains(['*', '?', '['])
+}
+
+/// Compile a manifest glob into an anchored regex. `*` and `?` match within a single
+/// path segment, `**` spans directories and `[...]` is a character class.
+fn glob_to_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.next_if_eq(&'/').is_some() {
+                    // `**/` also matches zero directories
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                if chars.next_if_eq(&'!').is_some() {
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    match c {
+                        ']' => {
+                            regex.push(']');
+                            break;
+                        }
+                        '\\' | '[' => {
+                            regex.push('\\');
+                            regex.push(c);
+                        }
+                        _ => regex.push(c),
+                    }
+                }
+            }
+            _ => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+
+    regex.push('$');
+    Regex::new(&regex)
+}
+
+/// Expand a glob entry into the sorted `.mdc` paths it matches in the repository tree.
+/// Returns `None` when the pattern is not a valid glob.
+async fn expand_glob(
+    pattern: &str,
+    repo_tree: &mut RepoTree,
+    locator: &RepoLocator,
+    force_refresh: bool,
+) -> anyhow::Result<Option<Vec<String>>> {
+    let Ok(regex) = glob_to_regex(pattern) else {
+        return Ok(None);
+    };
+
+    // Make sure the tree is loaded before walking the in-memory cache
+    repo_tree.children(locator, "", force_refresh).await?;
+
+    let mut matches: Vec<String> = repo_tree
+        .cached_nodes()
+        .filter(|node| node.kind == NodeKind::RuleFile && regex.is_match(&node.path))
+        .map(|node| node.path.clone())
+        .collect();
+    matches.sort();
+
+    Ok(Some(matches))
+}
+
+/// Remove every entry that equals an exclude path or matches an exclude glob
+fn apply_excludes(entries: Vec<String>, exclude: &[String]) -> Vec<String> {
+    if exclude.is_empty() {
+        return entries;
+    }
+
+    let patterns: Vec<Regex> = exclude
+        .iter()
+        .map(|pattern| pattern.trim())
+        .filter(|pattern| is_glob(pattern))
+        .filter_map(|pattern| glob_to_regex(pattern).ok())
+        .collect();
+
+    entries
+        .into_iter()
+        .filter(|entry| {
+            let entry = entry.trim();
+            !exclude.iter().any(|path| path.trim() == entry)
+                && !patterns.iter().any(|pattern| pattern.is_match(entry))
+        })
+        .collect()
+}
+
 /// Check if a file exists in the repository tree
 async fn file_exists_in_repo(
     file_path: &str,
@@ -192,45 +390,329 @@ async fn file_exists_in_repo(
     Ok(false)
 }
 
-/// Parse manifest content based on format
-pub async fn parse_manifest_content(
+/// Rules and composition fields read from a single manifest file
+struct ManifestParts {
+    name: String,
+    description: Option<String>,
+    rules: Vec<String>,
+    exclude: Vec<String>,
+    includes: Vec<String>,
+    target_dir: Option<String>,
+}
+
+/// Split manifest content into its parts without validating anything
+fn parse_manifest_parts(
     content: &str,
-    format: ManifestFormat,
+    format: &ManifestFormat,
     filename: &str,
-    repo_tree: &mut RepoTree,
-    locator: &RepoLocator,
-    force_refresh: bool,
-) -> Result<Manifest, ManifestError> {
-    let (entries, name, description) = match format {
+) -> Result<ManifestParts, ManifestError> {
+    let schema = match format {
         ManifestFormat::Txt => {
-            let entries = parse_txt_manifest(content)?;
-            let name = get_basename(filename);
-            (entries, name, None)
+            return Ok(ManifestParts {
+                name: get_basename(filename),
+                description: None,
+                rules: parse_txt_manifest(content)?,
+                exclude: Vec::new(),
+                includes: Vec::new(),
+                target_dir: None,
+            });
         }
-        ManifestFormat::Yaml => {
-            let schema = parse_yaml_manifest(content)?;
-            (schema.rules, schema.name, schema.description)
+        ManifestFormat::Yaml => parse_yaml_manifest(content)?,
+        ManifestFormat::Json => parse_json_manifest(content)?,
+    };
+
+    Ok(ManifestParts {
+        name: schema.name,
+        description: schema.description,
+        rules: schema.rules,
+        exclude: schema.exclude,
+        includes: schema.includes,
+        target_dir: schema.target_dir,
+    })
+}
+
+/// Rules, excludes and errors gathered while walking manifest includes
+#[derive(Default)]
+struct IncludeState {
+    rules: Vec<String>,
+    exclude: Vec<String>,
+    errors: Vec<String>,
+    /// Manifest paths already merged, so shared includes are fetched once
+    loaded: HashSet<String>,
+}
+
+/// Resolve manifest ID to format and path
+pub fn resolve_manifest_id(
+    manifest_id: &str,
+    available_manifests: &HashMap<String, (ManifestFormat, String)>,
+) -> Option<(ManifestFormat, String)> {
+    // First, try exact ID match (basename without extension)
+    if let Some((format, path)) = available_manifests.get(manifest_id) {
+        return Some((format.clone(), path.clone()));
+    }
+
+    // If ID contains extension, try to find exact filename match
+    if manifest_id.contains('.') {
+        for (format, path) in available_manifests.values() {
+            if path.ends_with(manifest_id) {
+                return Some((format.clone(), path.clone()));
+            }
         }
-        ManifestFormat::Json => {
-            let schema = parse_json_manifest(content)?;
-            (schema.rules, schema.name, schema.description)
+    }
+
+    None
+}
+
+/// Depth-first merge of `includes` into `state`. `chain` holds the manifests currently
+/// being expanded; an include that points back into it is reported as a cycle.
+async fn collect_includes<F, Fut>(
+    includes: &[String],
+    available: &HashMap<String, (ManifestFormat, String)>,
+    download: &F,
+    chain: &mut Vec<String>,
+    state: &mut IncludeState,
+) where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = anyhow::Result<String>>,
+{
+    for include in includes {
+        let include = include.trim();
+        let Some((format, path)) = resolve_manifest_id(include, available) else {
+            state
+                .errors
+                .push(format!("Included manifest not found: {include}"));
+            continue;
+        };
+
+        if chain.contains(&path) {
+            state.errors.push(format!(
+                "Cyclic manifest include: {} -> {path}",
+                chain.join(" -> ")
+            ));
+            continue;
         }
+        if !state.loaded.insert(path.clone()) {
+            continue;
+        }
+
+        let parts = match download(path.clone()).await {
+            Ok(content) => parse_manifest_parts(&content, &format, &path),
+            Err(e) => Err(ManifestError::FileNotFound(e.to_string())),
+        };
+        let parts = match parts {
+            Ok(parts) => parts,
+            Err(e) => {
+                state
+                    .errors
+                    .push(format!("Failed to load included manifest {include}: {e}"));
+                continue;
+            }
+        };
+
+        state.rules.extend(parts.rules);
+        state.exclude.extend(parts.exclude);
+
+        chain.push(path);
+        Box::pin(collect_includes(
+            &parts.includes,
+            available,
+            download,
+            chain,
+            state,
+        ))
+        .await;
+        chain.pop();
+    }
+}
+
+/// Parse manifest content based on format.
+///
+/// Manifests listed in `includes` are fetched from the manifest directory with `download`, which
+/// receives the manifest path, and their rules are merged in order after this manifest's own.
+pub async fn parse_manifest_content<F, Fut>(
+    content: &str,
+    format: ManifestFormat,
+    filename: &str,
+    repo_tree: &mut RepoTree,
+    locator: &RepoLocator,
+    force_refresh: bool,
+    download: &F,
+) -> Result<Manifest, ManifestError>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = anyhow::Result<String>>,
+{
+    let parts = parse_manifest_parts(content, &format, filename)?;
+
+    let mut state = IncludeState {
+        rules: parts.rules,
+        exclude: parts.exclude,
+        ..IncludeState::default()
     };
 
+    if !parts.includes.is_empty() {
+        // An include names its manifest explicitly, so hidden ones resolve too
+        let available = find_manifests_in_quickadd(repo_tree, locator, force_refresh, true)
+            .await
+            .map_err(|e| ManifestError::ValidationError(e.to_string()))?;
+        let root = resolve_manifest_id(filename, &available)
+            .map_or_else(|| filename.to_string(), |(_, path)| path);
+
+        state.loaded.insert(root.clone());
+        let mut chain = vec![root];
+        collect_includes(
+            &parts.includes,
+            &available,
+            download,
+            &mut chain,
+            &mut state,
+        )
+        .await;
+    }
+
+    // Merge duplicates from included manifests, keeping the first occurrence
+    let mut seen = HashSet::new();
+    let entries: Vec<String> = state
+        .rules
+        .into_iter()
+        .filter(|rule| seen.insert(rule.trim().to_string()))
+        .collect();
+
+    // Drop excluded entries up front so they are never reported as missing, then again
+    // after validation to filter paths produced by glob expansion
+    let entries = apply_excludes(entries, &state.exclude);
     let (valid_entries, errors, warnings) =
         validate_manifest_entries(&entries, repo_tree, locator, force_refresh)
             .await
             .map_err(|e| ManifestError::ValidationError(e.to_string()))?;
+    let valid_entries = apply_excludes(valid_entries, &state.exclude);
+
+    // Tag entry problems with the manifest they were reported for
+    let mut all_errors = state.errors;
+    all_errors.extend(errors.into_iter().map(|e| format!("{filename}: {e}")));
+    let warnings = warnings
+        .into_iter()
+        .map(|w| format!("{filename}: {w}"))
+        .collect();
 
     Ok(Manifest {
-        name,
-        description,
+        name: parts.name,
+        description: parts.description,
         entries: valid_entries,
-        errors,
+        errors: all_errors,
         warnings,
+        target_dir: parts.target_dir,
     })
 }
 
+/// Normalize manifest entries into a canonical form.
+///
+/// Entries are trimmed, blank lines and comments are dropped, duplicates are removed
+/// and the result is sorted so the same set of rules always produces the same output.
+pub fn canonicalize_entries(entries: &[String]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty() && !entry.starts_with('#'))
+        .map(|entry| entry.to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Render a resolved manifest as a `.txt` manifest.
+///
+/// In canonical mode the header comments are omitted and entries are normalized
+/// with [`canonicalize_entries`] so the file is stable and diff-friendly.
+pub fn render_materialized_manifest(manifest: &Manifest, canonical: bool) -> String {
+    let mut output = String::new();
+
+    let entries = if canonical {
+        canonicalize_entries(&manifest.entries)
+    } else {
+        output.push_str(&format!("# {}\n", manifest.name));
+        if let Some(description) = &manifest.description {
+            output.push_str(&format!("# {description}\n"));
+        }
+        manifest.entries.clone()
+    };
+
+    for entry in entries {
+        output.push_str(&entry);
+        output.push('\n');
+    }
+
+    output
+}
+
+/// The URL `manifest_id` names when it is an `http(s)` link rather than a manifest in
+/// the repository. Any other `scheme://` is rejected rather than looked up as an ID.
+pub fn manifest_url(manifest_id: &str) -> anyhow::Result<Option<reqwest::Url>> {
+    let Some((scheme, _)) = manifest_id.split_once("://") else {
+        return Ok(None);
+    };
+    if scheme.is_empty()
+        || !scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+    {
+        return Ok(None);
+    }
+    if !matches!(scheme.to_ascii_lowercase().as_str(), "http" | "https") {
+        anyhow::bail!(
+            "Unsupported manifest URL scheme {scheme:?}; only http and https are allowed"
+        );
+    }
+    let url = reqwest::Url::parse(manifest_id)
+        .map_err(|e| anyhow::anyhow!("Invalid manifest URL {manifest_id}: {e}"))?;
+    Ok(Some(url))
+}
+
+/// Download a manifest that lives outside the repository, such as a raw file or gist.
+/// The format comes from the URL's extension, or else the response's `Content-Type`.
+pub async fn fetch_remote_manifest(url: &reqwest::Url) -> anyhow::Result<(ManifestFormat, String)> {
+    if super::client::offline() {
+        anyhow::bail!("Manifest {url} cannot be downloaded offline");
+    }
+    let response = super::client::http()?
+        .get(url.clone())
+        .send()
+        .await?
+        .error_for_status()
+        .map_err(|e| anyhow::anyhow!("Failed to download manifest {url}: {e}"))?;
+
+    let from_extension = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|file| file.contains('.'))
+        .and_then(get_manifest_format);
+    let from_content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(format_from_content_type);
+    let format = from_extension.or(from_content_type).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Cannot tell the format of manifest {url}; link to a .txt, .yaml or .json file"
+        )
+    })?;
+
+    Ok((format, response.text().await?))
+}
+
+/// Manifest format for a `Content-Type` such as `application/json; charset=utf-8`
+fn format_from_content_type(content_type: &str) -> Option<ManifestFormat> {
+    let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "application/json" | "text/json" => Some(ManifestFormat::Json),
+        "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => {
+            Some(ManifestFormat::Yaml)
+        }
+        "text/plain" => Some(ManifestFormat::Txt),
+        _ => None,
+    }
+}
+
 /// Helper functions
 fn get_manifest_format(filename: &str) -> Option<ManifestFormat> {
     if let Some(ext) = filename.split('.').next_back() {
@@ -495,6 +977,53 @@ invalid yaml: [unclosed
         assert!(yaml_priority < json_priority);
     }
 
+    fn manifest_with_entries(entries: &[&str]) -> Manifest {
+        Manifest {
+            name: "frontend".to_string(),
+            description: Some("Frontend rules".to_string()),
+            entries: entries.iter().map(|e| e.to_string()).collect(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            target_dir: None,
+        }
+    }
+
+    #[test]
+    fn test_canonical_manifest_output_is_order_independent() {
+        let first = manifest_with_entries(&[
+            "frontend/vue.mdc",
+            "backend/rust.mdc",
+            "frontend/react.mdc",
+            "backend/rust.mdc",
+        ]);
+        let second = manifest_with_entries(&[
+            "# pinned rules",
+            "frontend/react.mdc",
+            "  frontend/vue.mdc  ",
+            "backend/rust.mdc",
+        ]);
+
+        let first_output = render_materialized_manifest(&first, true);
+        let second_output = render_materialized_manifest(&second, true);
+
+        assert_eq!(first_output, second_output);
+        assert_eq!(
+            first_output,
+            "backend/rust.mdc\nfrontend/react.mdc\nfrontend/vue.mdc\n"
+        );
+    }
+
+    #[test]
+    fn test_materialized_manifest_preserves_order_with_header() {
+        let manifest = manifest_with_entries(&["frontend/vue.mdc", "backend/rust.mdc"]);
+        let output = render_materialized_manifest(&manifest, false);
+
+        assert_eq!(
+            output,
+            "# frontend\n# Frontend rules\nfrontend/vue.mdc\nbackend/rust.mdc\n"
+        );
+    }
+
     #[test]
     fn test_manifest_error_display() {
         let error1 = ManifestError::InvalidFormat("test".to_string());
@@ -619,6 +1148,9 @@ invalid yaml: [unclosed
             name: "Test Schema".to_string(),
             description: Some("A test schema".to_string()),
             rules: vec!["rule1.mdc".to_string(), "rule2.mdc".to_string()],
+            exclude: Vec::new(),
+            includes: Vec::new(),
+            target_dir: None,
         };
 
         // Test JSON serialization round-trip
@@ -638,6 +1170,7 @@ invalid yaml: [unclosed
             entries: vec!["entry1.mdc".to_string()],
             errors: vec!["Error 1".to_string()],
             warnings: vec!["Warning 1".to_string()],
+            target_dir: None,
         };
 
         let cloned = manifest.clone();
@@ -750,9 +1283,461 @@ rules: []
         assert!(result.rules.is_empty());
     }
 
-    // Note: find_manifests_in_quickadd tests require GitHub API access
-    // These are covered by integration tests with real repositories
-
     // Note: Full integration test for file validation requires GitHub API access
     // This functionality is tested via CLI integration tests instead
+
+    fn test_locator() -> RepoLocator {
+        RepoLocator {
+            owner: "test".to_string(),
+            repo: "repo".to_string(),
+            branch: "main".to_string(),
+        }
+    }
+
+    fn node(path: &str, kind: NodeKind) -> crate::github::RepoNode {
+        crate::github::RepoNode {
+            name: path.rsplit('/').next().unwrap().to_string(),
+            path: path.to_string(),
+            kind,
+            ..Default::default()
+        }
+    }
+
+    /// Tree with rules at the root, in `sub/` and in `sub/deep/`
+    fn seeded_tree() -> RepoTree {
+        RepoTree::from_nodes(vec![
+            node("a.mdc", NodeKind::RuleFile),
+            node("b.mdc", NodeKind::RuleFile),
+            node("notes.txt", NodeKind::Manifest),
+            node("sub", NodeKind::Dir),
+            node("sub/c.mdc", NodeKind::RuleFile),
+            node("sub/deep", NodeKind::Dir),
+            node("sub/deep/d.mdc", NodeKind::RuleFile),
+            node("quick-add", NodeKind::Dir),
+            node("quick-add/base.yaml", NodeKind::Manifest),
+            node("quick-add/extra.txt", NodeKind::Manifest),
+            node("quick-add/loop-a.yaml", NodeKind::Manifest),
+            node("quick-add/loop-b.json", NodeKind::Manifest),
+        ])
+    }
+
+    /// Serve included manifests from memory instead of GitHub
+    fn fake_download(path: String) -> std::future::Ready<anyhow::Result<String>> {
+        let content = match path.as_str() {
+            "quick-add/base.yaml" => "name: Base\nrules: [a.mdc, sub/c.mdc]\nexclude: [sub/c.mdc]",
+            "quick-add/extra.txt" => "b.mdc\na.mdc",
+            "quick-add/loop-a.yaml" => "name: Loop A\nrules: [a.mdc]\nincludes: [loop-b]",
+            "quick-add/loop-b.json" => {
+                r#"{"name": "Loop B", "rules": ["b.mdc"], "includes": ["loop-a"]}"#
+            }
+            _ => return std::future::ready(Err(anyhow::anyhow!("no such manifest: {path}"))),
+        };
+        std::future::ready(Ok(content.to_string()))
+    }
+
+    async fn validate(entries: &[&str]) -> (Vec<String>, Vec<String>, Vec<String>) {
+        let entries: Vec<String> = entries.iter().map(|e| e.to_string()).collect();
+        validate_manifest_entries(&entries, &mut seeded_tree(), &test_locator(), false)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_glob_expands_root_rules() {
+        let (valid, errors, warnings) = validate(&["*.mdc"]).await;
+        assert_eq!(valid, vec!["a.mdc", "b.mdc"]);
+        assert!(errors.is_empty());
+        assert!(warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_glob_double_star_spans_directories() {
+        let (valid, _, _) = validate(&["sub/**/*.mdc"]).await;
+        assert_eq!(valid, vec!["sub/c.mdc", "sub/deep/d.mdc"]);
+    }
+
+    #[tokio::test]
+    async fn test_glob_without_matches_warns() {
+        let (valid, errors, warnings) = validate(&["a.mdc", "frontend/*.mdc"]).await;
+        assert_eq!(valid, vec!["a.mdc"]);
+        assert!(errors.is_empty());
+        assert_eq!(
+            warnings,
+            vec!["No rule files match pattern: frontend/*.mdc"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_glob_does_not_duplicate_plain_entries() {
+        let (valid, _, _) = validate(&["b.mdc", "*.mdc"]).await;
+        assert_eq!(valid, vec!["b.mdc", "a.mdc"]);
+    }
+
+    #[test]
+    fn test_glob_to_regex_segments_and_classes() {
+        let star = glob_to_regex("*.mdc").unwrap();
+        assert!(star.is_match("a.mdc"));
+        assert!(!star.is_match("sub/a.mdc"));
+
+        let class = glob_to_regex("[ab].mdc").unwrap();
+        assert!(class.is_match("a.mdc"));
+        assert!(!class.is_match("c.mdc"));
+
+        let negated = glob_to_regex("[!ab].mdc").unwrap();
+        assert!(negated.is_match("c.mdc"));
+
+        assert!(glob_to_regex("[ab.mdc").is_err());
+    }
+
+    async fn parse_seeded(content: &str, format: ManifestFormat) -> Manifest {
+        parse_named(content, format, "manifest").await
+    }
+
+    async fn parse_named(content: &str, format: ManifestFormat, filename: &str) -> Manifest {
+        parse_manifest_content(
+            content,
+            format,
+            filename,
+            &mut seeded_tree(),
+            &test_locator(),
+            false,
+            &fake_download,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn test_manifest_target_dir_is_optional() {
+        let schema = parse_yaml_manifest("name: Flat\nrules: [a.mdc]").unwrap();
+        assert_eq!(schema.target_dir, None);
+
+        let schema =
+            parse_yaml_manifest("name: Nested\ntarget_dir: rules/frontend/\nrules: [a.mdc]")
+                .unwrap();
+        assert_eq!(schema.target_dir.as_deref(), Some("rules/frontend/"));
+
+        let schema =
+            parse_json_manifest(r#"{"name": "Nested", "rules": [], "target_dir": "backend"}"#)
+                .unwrap();
+        assert_eq!(schema.target_dir.as_deref(), Some("backend"));
+    }
+
+    #[tokio::test]
+    async fn test_parsed_manifest_carries_target_dir() {
+        let nested = parse_seeded(
+            "name: Nested\ntarget_dir: frontend\nrules: [a.mdc]\nincludes: [base]",
+            ManifestFormat::Yaml,
+        )
+        .await;
+        assert_eq!(nested.target_dir.as_deref(), Some("frontend"));
+
+        let flat = parse_seeded("a.mdc", ManifestFormat::Txt).await;
+        assert_eq!(flat.target_dir, None);
+    }
+
+    #[test]
+    fn test_manifest_exclude_is_optional() {
+        let schema = parse_yaml_manifest("name: Plain\nrules: [a.mdc]").unwrap();
+        assert!(schema.exclude.is_empty());
+
+        let schema =
+            parse_json_manifest(r#"{"name": "Pruned", "rules": [], "exclude": ["a.mdc"]}"#)
+                .unwrap();
+        assert_eq!(schema.exclude, vec!["a.mdc"]);
+    }
+
+    #[tokio::test]
+    async fn test_yaml_manifest_exclude_removes_rule() {
+        let content = r#"
+name: "Pruned"
+rules:
+  - a.mdc
+  - b.mdc
+  - sub/c.mdc
+exclude:
+  - b.mdc
+  - missing.mdc
+"#;
+        let manifest = parse_seeded(content, ManifestFormat::Yaml).await;
+        assert_eq!(manifest.entries, vec!["a.mdc", "sub/c.mdc"]);
+        assert!(manifest.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_json_manifest_exclude_filters_glob_expansion() {
+        let content = r#"
+{
+  "name": "Pruned",
+  "rules": ["**/*.mdc", "gone.mdc"],
+  "exclude": ["sub/deep/*", "gone.mdc"]
+}
+"#;
+        let manifest = parse_seeded(content, ManifestFormat::Json).await;
+        assert_eq!(manifest.entries, vec!["a.mdc", "b.mdc", "sub/c.mdc"]);
+        assert!(manifest.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_manifest_includes_merge_rules_in_order() {
+        let content = r#"
+name: "Composed"
+rules:
+  - sub/deep/d.mdc
+includes:
+  - base
+  - extra.txt
+"#;
+        let manifest = parse_seeded(content, ManifestFormat::Yaml).await;
+        // base contributes a.mdc (its exclude drops sub/c.mdc), extra adds b.mdc once
+        assert_eq!(manifest.entries, vec!["sub/deep/d.mdc", "a.mdc", "b.mdc"]);
+        assert!(manifest.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_manifest_missing_include_is_an_error() {
+        let content = r#"{"name": "Broken", "rules": ["a.mdc"], "includes": ["nope"]}"#;
+        let manifest = parse_seeded(content, ManifestFormat::Json).await;
+        assert_eq!(manifest.entries, vec!["a.mdc"]);
+        assert_eq!(manifest.errors, vec!["Included manifest not found: nope"]);
+    }
+
+    #[tokio::test]
+    async fn test_manifest_cyclic_include_is_detected() {
+        let content = "name: Loop A\nrules: [a.mdc]\nincludes: [loop-b]";
+        let manifest = parse_named(content, ManifestFormat::Yaml, "loop-a").await;
+        assert_eq!(manifest.entries, vec!["a.mdc", "b.mdc"]);
+        assert_eq!(
+            manifest.errors,
+            vec![
+                "Cyclic manifest include: quick-add/loop-a.yaml -> quick-add/loop-b.json -> quick-add/loop-a.yaml"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_suggest_closest_near_miss_filenames() {
+        let candidates = [
+            "frontend/react.mdc",
+            "frontend/vue.mdc",
+            "frontend/svelte.mdc",
+        ];
+
+        assert_eq!(
+            suggest_closest("frontend/reakt.mdc", candidates),
+            Some("frontend/react.mdc")
+        );
+        assert_eq!(
+            suggest_closest("frontend/veu.mdc", candidates),
+            Some("frontend/vue.mdc")
+        );
+        assert_eq!(
+            suggest_closest("frontend/Svelte.mdc", candidates),
+            Some("frontend/svelte.mdc")
+        );
+        assert_eq!(suggest_closest("frontend/angular.mdc", candidates), None);
+        assert_eq!(suggest_closest("frontend/react.mdc", []), None);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("react", "react"), 0);
+        assert_eq!(edit_distance("react", "reakt"), 1);
+        assert_eq!(edit_distance("vue", "veu"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_find_manifests_hides_dotfiles_without_all() {
+        let mut tree = RepoTree::from_nodes(vec![
+            node("quick-add", NodeKind::Dir),
+            node("quick-add/base.yaml", NodeKind::Manifest),
+            node("quick-add/.draft.txt", NodeKind::Manifest),
+            node("quick-add/.wip.yaml", NodeKind::Manifest),
+        ]);
+
+        let visible = find_manifests_in_quickadd(&mut tree, &test_locator(), false, false)
+            .await
+            .unwrap();
+        assert!(!visible.contains_key(".draft"));
+        assert!(!visible.contains_key(".wip"));
+        assert!(visible.contains_key("base"));
+
+        let all = find_manifests_in_quickadd(&mut tree, &test_locator(), false, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            all.get(".draft"),
+            Some(&(ManifestFormat::Txt, "quick-add/.draft.txt".to_string()))
+        );
+        assert!(all.contains_key(".wip"));
+        assert_eq!(visible.len(), 1);
+        assert_eq!(all.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_find_manifests_in_alternate_directory() {
+        let nodes = vec![
+            node("quick-add", NodeKind::Dir),
+            node("quick-add/old.txt", NodeKind::Manifest),
+            node("bundles", NodeKind::Dir),
+            node("bundles/web.yaml", NodeKind::Manifest),
+            node("bundles/web.txt", NodeKind::Manifest),
+            node("bundles/api.json", NodeKind::Manifest),
+        ];
+
+        let mut tree = RepoTree::from_nodes(nodes.clone());
+        let default = find_manifests_in_quickadd(&mut tree, &test_locator(), false, false)
+            .await
+            .unwrap();
+        assert_eq!(default.keys().collect::<Vec<_>>(), ["old"]);
+
+        let mut tree = RepoTree::from_nodes(nodes).with_manifest_dir("bundles");
+        let bundles = find_manifests_in_quickadd(&mut tree, &test_locator(), false, false)
+            .await
+            .unwrap();
+        assert_eq!(bundles.len(), 2);
+        assert_eq!(
+            resolve_manifest_id("web", &bundles),
+            Some((ManifestFormat::Txt, "bundles/web.txt".to_string()))
+        );
+        assert_eq!(
+            resolve_manifest_id("api.json", &bundles),
+            Some((ManifestFormat::Json, "bundles/api.json".to_string()))
+        );
+        assert_eq!(resolve_manifest_id("old", &bundles), None);
+    }
+
+    #[tokio::test]
+    async fn test_find_manifests_in_a_hidden_manifest_directory() {
+        let mut tree = RepoTree::from_nodes(vec![
+            node(".github", NodeKind::Dir),
+            node(".github/bundles", NodeKind::Dir),
+            node(".github/bundles/web.txt", NodeKind::Manifest),
+            node(".github/bundles/.draft.txt", NodeKind::Manifest),
+        ])
+        .with_manifest_dir(".github/bundles");
+
+        let found = find_manifests_in_quickadd(&mut tree, &test_locator(), false, false)
+            .await
+            .unwrap();
+
+        assert_eq!(found.keys().collect::<Vec<_>>(), ["web"]);
+    }
+
+    #[test]
+    fn test_manifest_url_accepts_only_http() {
+        assert!(manifest_url("frontend").unwrap().is_none());
+        assert!(manifest_url("quick-add/frontend.yaml").unwrap().is_none());
+        assert_eq!(
+            manifest_url("https://example.com/web.yaml")
+                .unwrap()
+                .unwrap()
+                .as_str(),
+            "https://example.com/web.yaml"
+        );
+        assert!(manifest_url("HTTP://example.com/web.txt")
+            .unwrap()
+            .is_some());
+        for rejected in [
+            "file:///etc/passwd",
+            "ftp://example.com/web.txt",
+            "git+ssh://host/x",
+        ] {
+            assert!(manifest_url(rejected).is_err(), "{rejected}");
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_remote_manifest_builds_a_copy_plan() {
+        let mut server = mockito::Server::new_async().await;
+        let yaml = server
+            .mock("GET", "/gist/raw/web")
+            .with_header("content-type", "application/yaml; charset=utf-8")
+            .with_body("name: Remote\ntarget_dir: web\nrules: [a.mdc, sub/c.mdc]\n")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/manifests/api.json")
+            .with_header("content-type", "text/plain")
+            .with_body(r#"{"name": "API", "rules": ["b.mdc"]}"#)
+            .create_async()
+            .await;
+
+        // No extension, so the format comes from Content-Type
+        let url = manifest_url(&format!("{}/gist/raw/web", server.url()))
+            .unwrap()
+            .unwrap();
+        let (format, content) = fetch_remote_manifest(&url).await.unwrap();
+        yaml.assert_async().await;
+        assert_eq!(format, ManifestFormat::Yaml);
+
+        let mut tree = seeded_tree();
+        let manifest = parse_manifest_content(
+            &content,
+            format,
+            url.as_str(),
+            &mut tree,
+            &test_locator(),
+            false,
+            &fake_download,
+        )
+        .await
+        .unwrap();
+        assert!(manifest.errors.is_empty(), "{:?}", manifest.errors);
+
+        let output = tempfile::tempdir().unwrap();
+        let config = crate::copier::CopyConfig {
+            output_dir: output.path().to_path_buf(),
+            ..Default::default()
+        };
+        let plan = crate::copier::create_copy_plan_in(
+            &manifest.entries,
+            &config,
+            manifest.target_dir.as_deref(),
+        )
+        .unwrap();
+        let destinations: Vec<_> = plan.iter().map(|p| p.destination_path.clone()).collect();
+        assert_eq!(
+            destinations,
+            [
+                output.path().join("web").join("a.mdc"),
+                output.path().join("web").join("c.mdc")
+            ]
+        );
+
+        // The extension wins over a generic Content-Type
+        let url = manifest_url(&format!("{}/manifests/api.json", server.url()))
+            .unwrap()
+            .unwrap();
+        let (format, _) = fetch_remote_manifest(&url).await.unwrap();
+        assert_eq!(format, ManifestFormat::Json);
+    }
+
+    #[tokio::test]
+    async fn test_missing_entry_suggests_sibling() {
+        let (_, errors, _) = validate(&["sub/e.mdc", "sub/zzzzzz.mdc"]).await;
+        assert_eq!(
+            errors,
+            vec![
+                "File not found in repository: sub/e.mdc (did you mean sub/c.mdc?)",
+                "File not found in repository: sub/zzzzzz.mdc",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_manifest_errors_name_the_manifest() {
+        let content = r#"{"name": "Typos", "rules": ["b.mdx", "bb.mdc"]}"#;
+        let manifest = parse_named(content, ManifestFormat::Json, "typos").await;
+        assert_eq!(
+            manifest.errors,
+            vec!["typos: File not found in repository: bb.mdc (did you mean b.mdc?)"]
+        );
+        assert_eq!(
+            manifest.warnings,
+            vec!["typos: Non-.mdc file ignored: b.mdx"]
+        );
+    }
 }
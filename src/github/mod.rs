@@ -1,10 +1,17 @@
 pub mod cache;
+pub mod client;
+pub mod history;
 pub mod manifests;
 pub mod repo_locator;
 pub mod tree;
 
 pub use cache::{FileSystemCache, PersistentCache};
-pub use manifests::{find_manifests_in_quickadd, parse_manifest_content, ManifestFormat};
+pub use manifests::{
+    find_manifests_in_quickadd, parse_manifest_content, render_materialized_manifest,
+    resolve_manifest_id, ManifestFormat,
+};
 #[allow(unused_imports)]
-pub use repo_locator::{resolve_repo, RepoDiscoveryError, RepoLocator};
-pub use tree::{NodeKind, RepoNode, RepoTree};
+pub use repo_locator::{
+    local_owner, resolve_repo, split_owner_flag, RepoDiscoveryError, RepoLocator,
+};
+pub use tree::{full_trees, should_show, NodeKind, RepoNode, RepoTree};
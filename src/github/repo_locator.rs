@@ -1,6 +1,7 @@
 use std::{fs, io, path::PathBuf, process::Command};
 
 use anyhow::Context;
+use chrono::{DateTime, Utc};
 use inquire::Text;
 use is_terminal::IsTerminal;
 use regex::Regex;
@@ -11,6 +12,9 @@ use tracing::{debug, instrument};
 /// See: https://docs.github.com/en/repositories/creating-and-managing-repositories/about-repositories#repository-name-limitations
 const REPO_NAME_REGEX: &str = r"^[A-Za-z0-9._-]+$";
 
+/// Most alternative repository names suggested when a repository is missing
+const MAX_REPO_SUGGESTIONS: usize = 5;
+
 /// Same rules apply to owner/user logins.
 const LOGIN_REGEX: &str = REPO_NAME_REGEX;
 
@@ -22,6 +26,36 @@ pub struct RepoLocator {
     pub branch: String,
 }
 
+impl RepoLocator {
+    /// github.com page for `path` on this branch: `/tree/` for a directory (the repository
+    /// root when `path` is empty), `/blob/` for a file
+    pub fn web_url(&self, path: &str, is_dir: bool) -> String {
+        let mut url = reqwest::Url::parse("https://github.com").expect("valid base URL");
+        url.path_segments_mut()
+            .expect("https URLs have a path")
+            .extend([
+                self.owner.as_str(),
+                self.repo.as_str(),
+                if is_dir { "tree" } else { "blob" },
+            ])
+            .extend(self.branch.split('/'))
+            .extend(path.split('/').filter(|segment| !segment.is_empty()));
+        url.to_string()
+    }
+
+    /// raw.githubusercontent.com download URL of the file at `path` on this branch
+    pub fn raw_url(&self, path: &str) -> String {
+        let mut url =
+            reqwest::Url::parse("https://raw.githubusercontent.com").expect("valid base URL");
+        url.path_segments_mut()
+            .expect("https URLs have a path")
+            .extend([self.owner.as_str(), self.repo.as_str()])
+            .extend(self.branch.split('/'))
+            .extend(path.split('/').filter(|segment| !segment.is_empty()));
+        url.to_string()
+    }
+}
+
 /// All possible errors that can occur while resolving a [`RepoLocator`].
 #[derive(Debug, Error)]
 pub enum RepoDiscoveryError {
@@ -34,33 +68,63 @@ pub enum RepoDiscoveryError {
     OwnerPromptCancelled,
 
     /// The requested repository does not exist or the authenticated user does not have access to it.
-    #[error("Repository '{owner}/{repo}' not found or is private")]
-    RepoNotFound { owner: String, repo: String },
+    #[error("{}", repo_not_found_message(.owner, .repo, .suggestions))]
+    RepoNotFound {
+        owner: String,
+        repo: String,
+        /// Similarly named repositories of the owner, as a hint
+        suggestions: Vec<String>,
+    },
+
+    /// The requested branch, tag or commit does not exist in the repository.
+    #[error("{}", ref_not_found_message(.owner, .repo, .r#ref, .branches))]
+    RefNotFound {
+        owner: String,
+        repo: String,
+        r#ref: String,
+        /// Some of the branches that do exist, as a hint
+        branches: Vec<String>,
+    },
+
+    /// GitHub refused the request because the API rate limit is used up.
+    #[error("{}", rate_limited_message(.reset_at))]
+    RateLimited { reset_at: Option<DateTime<Utc>> },
 
     /// Any other network-related error surfaced by the GitHub API.
     #[error("Network error: {0}")]
     NetworkError(#[from] anyhow::Error),
 }
 
+fn repo_not_found_message(owner: &str, repo: &str, suggestions: &[String]) -> String {
+    let mut message = format!("Repository '{owner}/{repo}' not found or is private");
+    if !suggestions.is_empty() {
+        message.push_str(&format!("; did you mean: {}?", suggestions.join(", ")));
+    }
+    message
+}
+
+fn ref_not_found_message(owner: &str, repo: &str, r#ref: &str, branches: &[String]) -> String {
+    let mut message =
+        format!("Branch, tag or commit '{ref}' not found in '{owner}/{repo}'", ref = r#ref);
+    if !branches.is_empty() {
+        message.push_str(&format!("; available branches: {}", branches.join(", ")));
+    }
+    message
+}
+
+fn rate_limited_message(reset_at: &Option<DateTime<Utc>>) -> String {
+    let wait = match reset_at {
+        Some(reset_at) => format!("wait until {} UTC", reset_at.format("%H:%M")),
+        None => "wait for the limit to reset".to_string(),
+    };
+    format!(
+        "GitHub API rate limit exceeded; run `cursor-rules auth login` or set GITHUB_TOKEN for a higher limit, or {wait}"
+    )
+}
+
 /// Construct an `Octocrab` instance, injecting `OCTO_BASE` when running in tests.
 fn build_octocrab(token: Option<&str>) -> Result<octocrab::Octocrab, RepoDiscoveryError> {
-    use octocrab::Octocrab;
-    let mut builder = Octocrab::builder();
-    if let Ok(base) = std::env::var("OCTO_BASE") {
-        builder = builder
-            .base_uri(&base)
-            .map_err(|e| RepoDiscoveryError::NetworkError(e.into()))?;
-    }
-    if let Some(tok) = token {
-        builder
-            .personal_token(tok.to_string())
-            .build()
-            .map_err(|e| RepoDiscoveryError::NetworkError(e.into()))
-    } else {
-        builder
-            .build()
-            .map_err(|e| RepoDiscoveryError::NetworkError(e.into()))
-    }
+    super::client::octocrab(token).map_err(RepoDiscoveryError::NetworkError)
 }
 
 /// Resolve the GitHub repository coordinates (owner/repo@branch) by applying CLI overrides,
@@ -68,7 +132,7 @@ fn build_octocrab(token: Option<&str>) -> Result<octocrab::Octocrab, RepoDiscove
 ///
 /// * `owner_flag` – value from `--owner` CLI flag.
 /// * `repo_flag` – value from `--repo` CLI flag (default = `cursor-rules`).
-/// * `branch_flag` – value from `--branch` CLI flag (default = `main`).
+/// * `branch_flag` – branch, tag or commit SHA from the `--branch` CLI flag (default = `main`).
 /// * `token` – optional GitHub Personal Access Token.
 #[instrument(level = "debug", skip(token))]
 pub async fn resolve_repo(
@@ -77,6 +141,15 @@ pub async fn resolve_repo(
     branch_flag: Option<String>,
     token: Option<String>,
 ) -> Result<RepoLocator, RepoDiscoveryError> {
+    // A pasted URL or `owner/repo` shorthand in --owner also names the repo
+    let (owner_flag, repo_flag) = match owner_flag {
+        Some(value) => {
+            let (owner, repo) = split_owner_flag(&value);
+            (Some(owner), repo_flag.or(repo))
+        }
+        None => (None, repo_flag),
+    };
+
     // 1. Owner resolution (multi-step)
     let owner = if let Some(owner) = owner_flag {
         debug!(%owner, "Using --owner override");
@@ -119,11 +192,18 @@ pub async fn resolve_repo(
     let repo = repo_flag.unwrap_or_else(|| "cursor-rules".to_string());
     validate_repo_name(&repo).context("Invalid repository name")?; // convert to anyhow then into NetworkError later maybe
 
+    // 3. Check visibility/existence via GitHub API, unless the network is off limits.
+    // An explicit ref is checked too; the `main` default is left to the tree fetch.
+    if super::client::offline() {
+        debug!("Offline: skipping repository existence check");
+    } else {
+        verify_repo_exists(&owner, &repo, token.as_deref()).await?;
+        if let Some(r#ref) = &branch_flag {
+            verify_ref_exists(&owner, &repo, r#ref, token.as_deref()).await?;
+        }
+    }
     let branch = branch_flag.unwrap_or_else(|| "main".to_string());
 
-    // 3. Check visibility/existence via GitHub API
-    verify_repo_exists(&owner, &repo, token.as_deref()).await?;
-
     Ok(RepoLocator {
         owner,
         repo,
@@ -151,6 +231,14 @@ fn resolve_owner_interactively() -> Result<String, RepoDiscoveryError> {
     }
 }
 
+/// Owner detectable without asking GitHub or the user: a valid login in git's
+/// `user.username`, or the user the gh CLI is logged in as
+pub fn local_owner() -> Option<String> {
+    git_config_username()
+        .filter(|owner| is_valid_login(owner))
+        .or_else(gh_hosts_user)
+}
+
 fn git_config_username() -> Option<String> {
     get_git_config_value("user.username")
 }
@@ -220,6 +308,9 @@ async fn search_owner_by_fullname(
     fullname: &str,
     token: Option<&str>,
 ) -> Result<Option<String>, RepoDiscoveryError> {
+    if super::client::offline() {
+        return Ok(None);
+    }
     let raw = fullname.trim().replace(' ', "+");
     let query = format!("fullname:{raw}");
 
@@ -241,6 +332,35 @@ async fn search_owner_by_fullname(
     Ok(None)
 }
 
+/// Split an `--owner` value into owner and optional repo.
+///
+/// Accepts a bare login (`owner`), shorthand (`owner/repo`), HTTPS URLs
+/// (`https://github.com/owner/repo`) and SSH remotes (`git@github.com:owner/repo.git`).
+/// A trailing `.git` is stripped and anything after the repo segment is ignored.
+pub fn split_owner_flag(value: &str) -> (String, Option<String>) {
+    let value = value.trim();
+
+    let path = if let Some(rest) = value.split_once("://").map(|(_, rest)| rest) {
+        // https://github.com/owner/repo or ssh://git@github.com/owner/repo
+        rest.split_once('/').map_or("", |(_, path)| path)
+    } else if let Some((_, path)) = value.strip_prefix("git@").and_then(|r| r.split_once(':')) {
+        path
+    } else if let Some(path) = value.strip_prefix("github.com/") {
+        path
+    } else {
+        value
+    };
+
+    let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+    let owner = segments.next().unwrap_or_default().to_string();
+    let repo = segments
+        .next()
+        .map(|repo| repo.strip_suffix(".git").unwrap_or(repo).to_string())
+        .filter(|repo| !repo.is_empty());
+
+    (owner, repo)
+}
+
 fn validate_repo_name(name: &str) -> anyhow::Result<()> {
     let re = Regex::new(REPO_NAME_REGEX).expect("valid regex");
     if re.is_match(name) {
@@ -258,27 +378,142 @@ async fn verify_repo_exists(
     let octocrab = build_octocrab(token)?;
 
     let path = format!("/repos/{owner}/{repo}");
-    let res: Result<serde_json::Value, octocrab::Error> = octocrab.get(&path, None::<&()>).await;
-
-    match res {
-        Ok(_) => {
+    match get_status(&octocrab, &path).await? {
+        (status, _) if status.is_success() => {
             debug!("Repository accessible");
             Ok(())
         }
-        Err(e) => {
-            if let octocrab::Error::GitHub { source, .. } = &e {
-                if source.status_code == http::StatusCode::NOT_FOUND {
-                    return Err(RepoDiscoveryError::RepoNotFound {
-                        owner: owner.to_string(),
-                        repo: repo.to_string(),
-                    });
-                }
-            }
-            Err(RepoDiscoveryError::NetworkError(e.into()))
+        (http::StatusCode::NOT_FOUND, _) => Err(RepoDiscoveryError::RepoNotFound {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            // Listing repos costs a request, so only spend it from the higher
+            // authenticated budget
+            suggestions: if token.is_some() {
+                similar_repo_names(&octocrab, owner).await
+            } else {
+                Vec::new()
+            },
+        }),
+        (status, body) => Err(unexpected_status(status, &path, &body)),
+    }
+}
+
+/// Check that `r#ref` names a branch, tag or commit of `owner/repo`. The commits endpoint
+/// resolves all three, so one request covers them.
+async fn verify_ref_exists(
+    owner: &str,
+    repo: &str,
+    r#ref: &str,
+    token: Option<&str>,
+) -> Result<(), RepoDiscoveryError> {
+    let octocrab = build_octocrab(token)?;
+
+    let path = format!("/repos/{owner}/{repo}/commits/{ref}", ref = r#ref);
+    match get_status(&octocrab, &path).await? {
+        (status, _) if status.is_success() => {
+            debug!(r#ref, "Ref exists");
+            Ok(())
         }
+        // GitHub answers 422 rather than 404 for some malformed refs
+        (http::StatusCode::NOT_FOUND | http::StatusCode::UNPROCESSABLE_ENTITY, _) => {
+            Err(RepoDiscoveryError::RefNotFound {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                r#ref: r#ref.to_string(),
+                branches: branch_names(&octocrab, owner, repo).await,
+            })
+        }
+        (status, body) => Err(unexpected_status(status, &path, &body)),
     }
 }
 
+/// GET `path`, returning the status and body. A 403 or 429 with no quota left becomes
+/// [`RepoDiscoveryError::RateLimited`], carrying the reset time GitHub reported.
+async fn get_status(
+    octocrab: &octocrab::Octocrab,
+    path: &str,
+) -> Result<(http::StatusCode, String), RepoDiscoveryError> {
+    let response = octocrab
+        ._get(path)
+        .await
+        .map_err(|e| RepoDiscoveryError::NetworkError(e.into()))?;
+    let status = response.status();
+    if let Some(reset_at) = rate_limit_reset(status, response.headers()) {
+        return Err(RepoDiscoveryError::RateLimited { reset_at });
+    }
+    let body = octocrab
+        .body_to_string(response)
+        .await
+        .map_err(|e| RepoDiscoveryError::NetworkError(e.into()))?;
+    Ok((status, body))
+}
+
+/// `Some(reset time)` when the response says the rate limit is used up: a 403 or 429
+/// with `X-RateLimit-Remaining: 0`. The reset time comes from `X-RateLimit-Reset`.
+fn rate_limit_reset(
+    status: http::StatusCode,
+    headers: &http::HeaderMap,
+) -> Option<Option<DateTime<Utc>>> {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let exhausted = (status == http::StatusCode::FORBIDDEN
+        || status == http::StatusCode::TOO_MANY_REQUESTS)
+        && header("x-ratelimit-remaining").is_some_and(|remaining| remaining.trim() == "0");
+    exhausted.then(|| {
+        header("x-ratelimit-reset")
+            .and_then(|reset| reset.trim().parse::<i64>().ok())
+            .and_then(|reset| DateTime::from_timestamp(reset, 0))
+    })
+}
+
+fn unexpected_status(status: http::StatusCode, path: &str, body: &str) -> RepoDiscoveryError {
+    RepoDiscoveryError::NetworkError(anyhow::anyhow!(
+        "GitHub returned {status} for {path}: {body}"
+    ))
+}
+
+/// Up to [`MAX_REPO_SUGGESTIONS`] of the owner's repos whose names mention "cursor" or
+/// "rules", e.g. `.cursor-rules` or `cursor_rules`. Empty when they cannot be listed.
+async fn similar_repo_names(octocrab: &octocrab::Octocrab, owner: &str) -> Vec<String> {
+    let path = format!("/users/{owner}/repos");
+    let repos: serde_json::Value = match octocrab.get(&path, Some(&[("per_page", "100")])).await {
+        Ok(repos) => repos,
+        Err(e) => {
+            debug!(error=%e, "Could not list repositories");
+            return Vec::new();
+        }
+    };
+    repos
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|repo| repo.get("name")?.as_str())
+        .filter(|name| {
+            let name = name.to_lowercase();
+            name.contains("cursor") || name.contains("rules")
+        })
+        .take(MAX_REPO_SUGGESTIONS)
+        .map(str::to_string)
+        .collect()
+}
+
+/// First page of branch names, for hints. Empty when they cannot be listed.
+async fn branch_names(octocrab: &octocrab::Octocrab, owner: &str, repo: &str) -> Vec<String> {
+    let path = format!("/repos/{owner}/{repo}/branches");
+    let branches: serde_json::Value = match octocrab.get(&path, Some(&[("per_page", "10")])).await {
+        Ok(branches) => branches,
+        Err(e) => {
+            debug!(error=%e, "Could not list branches");
+            return Vec::new();
+        }
+    };
+    branches
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|branch| branch.get("name")?.as_str().map(str::to_string))
+        .collect()
+}
+
 fn is_valid_login(name: &str) -> bool {
     Regex::new(LOGIN_REGEX).unwrap().is_match(name)
 }
@@ -322,6 +557,140 @@ mod tests {
         assert_eq!(owner, Some("alice".to_string()));
     }
 
+    #[test]
+    fn split_owner_flag_plain_owner() {
+        assert_eq!(split_owner_flag("alice"), ("alice".to_string(), None));
+        assert_eq!(split_owner_flag(" alice "), ("alice".to_string(), None));
+    }
+
+    #[test]
+    fn split_owner_flag_shorthand() {
+        assert_eq!(
+            split_owner_flag("alice/my-rules"),
+            ("alice".to_string(), Some("my-rules".to_string()))
+        );
+        assert_eq!(
+            split_owner_flag("github.com/alice/my-rules"),
+            ("alice".to_string(), Some("my-rules".to_string()))
+        );
+    }
+
+    #[test]
+    fn web_url_links_files_and_directories() {
+        let locator = RepoLocator {
+            owner: "alice".into(),
+            repo: "my-rules".into(),
+            branch: "main".into(),
+        };
+        assert_eq!(
+            locator.web_url("frontend/react.mdc", false),
+            "https://github.com/alice/my-rules/blob/main/frontend/react.mdc"
+        );
+        assert_eq!(
+            locator.web_url("frontend", true),
+            "https://github.com/alice/my-rules/tree/main/frontend"
+        );
+        assert_eq!(
+            locator.web_url("", true),
+            "https://github.com/alice/my-rules/tree/main"
+        );
+
+        // Branches keep their slashes; other unsafe characters are escaped
+        let feature = RepoLocator {
+            branch: "feature/new-rules".into(),
+            ..locator
+        };
+        assert_eq!(
+            feature.web_url("web/my rule.mdc", false),
+            "https://github.com/alice/my-rules/blob/feature/new-rules/web/my%20rule.mdc"
+        );
+    }
+
+    #[test]
+    fn raw_urls_for_resolved_entries() {
+        let locator = RepoLocator {
+            owner: "alice".into(),
+            repo: "my-rules".into(),
+            branch: "v1.2".into(),
+        };
+        let entries = ["react.mdc", "frontend/vue.mdc", "/backend/api rules.mdc"];
+        let urls: Vec<String> = entries.iter().map(|entry| locator.raw_url(entry)).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://raw.githubusercontent.com/alice/my-rules/v1.2/react.mdc",
+                "https://raw.githubusercontent.com/alice/my-rules/v1.2/frontend/vue.mdc",
+                "https://raw.githubusercontent.com/alice/my-rules/v1.2/backend/api%20rules.mdc",
+            ]
+        );
+    }
+
+    #[test]
+    fn split_owner_flag_https_url() {
+        let expected = ("alice".to_string(), Some("my-rules".to_string()));
+        assert_eq!(
+            split_owner_flag("https://github.com/alice/my-rules"),
+            expected
+        );
+        assert_eq!(
+            split_owner_flag("https://github.com/alice/my-rules.git"),
+            expected
+        );
+        assert_eq!(
+            split_owner_flag("https://github.com/alice/my-rules/"),
+            expected
+        );
+        assert_eq!(
+            split_owner_flag("https://github.com/alice/my-rules/tree/main"),
+            expected
+        );
+        assert_eq!(
+            split_owner_flag("https://github.com/alice"),
+            ("alice".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn split_owner_flag_ssh_remote() {
+        let expected = ("alice".to_string(), Some("my-rules".to_string()));
+        assert_eq!(
+            split_owner_flag("git@github.com:alice/my-rules.git"),
+            expected
+        );
+        assert_eq!(split_owner_flag("git@github.com:alice/my-rules"), expected);
+        assert_eq!(
+            split_owner_flag("ssh://git@github.com/alice/my-rules.git"),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn explicit_repo_overrides_owner_url() {
+        let mut server = mockito::Server::new_async().await;
+        let _m = server
+            .mock("GET", "/repos/alice/explicit")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body("{}")
+            .create_async()
+            .await;
+        std::env::set_var("OCTO_BASE", server.url());
+
+        let res = resolve_repo(
+            Some("https://github.com/alice/from-url".into()),
+            Some("explicit".into()),
+            None,
+            None,
+        )
+        .await;
+        std::env::remove_var("OCTO_BASE");
+
+        let locator = res.unwrap();
+        assert_eq!(locator.owner, "alice");
+        assert_eq!(locator.repo, "explicit");
+    }
+
     #[test]
     #[serial_test::serial]
     fn validate_repo_name_good() {
@@ -471,14 +840,232 @@ mod tests {
             .unwrap_err();
         std::env::remove_var("OCTO_BASE");
         match err {
-            super::RepoDiscoveryError::RepoNotFound { owner, repo } => {
+            super::RepoDiscoveryError::RepoNotFound {
+                owner,
+                repo,
+                suggestions,
+            } => {
                 assert_eq!(owner, "foo");
                 assert_eq!(repo, "bar");
+                // Anonymous lookups don't spend a request on suggestions
+                assert!(suggestions.is_empty());
             }
             _ => panic!("unexpected error variant"),
         }
     }
 
+    /// Mock server where `alice/rules` exists with the given refs
+    async fn ref_server(refs: &[&str]) -> mockito::ServerGuard {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/repos/alice/rules")
+            .with_status(200)
+            .with_body("{}")
+            .create_async()
+            .await;
+        for r#ref in refs {
+            server
+                .mock(
+                    "GET",
+                    format!("/repos/alice/rules/commits/{ref}", ref = r#ref).as_str(),
+                )
+                .with_status(200)
+                .with_body(r#"{"sha": "0123abc"}"#)
+                .create_async()
+                .await;
+        }
+        server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex("^/repos/alice/rules/commits/".into()),
+            )
+            // Refs not listed above
+            .with_status(422)
+            .with_body(r#"{"message": "No commit found for SHA"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/alice/rules/branches")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(r#"[{"name": "main"}, {"name": "dev"}]"#)
+            .create_async()
+            .await;
+        server
+    }
+
+    async fn resolve_ref(r#ref: &str) -> Result<RepoLocator, RepoDiscoveryError> {
+        resolve_repo(
+            Some("alice".into()),
+            Some("rules".into()),
+            Some(r#ref.into()),
+            None,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn explicit_branch_tag_and_commit_are_accepted() {
+        let server = ref_server(&["dev", "v1.2.0", "0123abc"]).await;
+        std::env::set_var("OCTO_BASE", server.url());
+
+        let branch = resolve_ref("dev").await;
+        let tag = resolve_ref("v1.2.0").await;
+        let commit = resolve_ref("0123abc").await;
+        std::env::remove_var("OCTO_BASE");
+
+        assert_eq!(branch.unwrap().branch, "dev");
+        assert_eq!(tag.unwrap().branch, "v1.2.0");
+        assert_eq!(commit.unwrap().branch, "0123abc");
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn missing_ref_lists_available_branches() {
+        let server = ref_server(&["main", "dev"]).await;
+        std::env::set_var("OCTO_BASE", server.url());
+        let err = resolve_ref("mian").await.unwrap_err();
+        std::env::remove_var("OCTO_BASE");
+
+        match &err {
+            RepoDiscoveryError::RefNotFound {
+                r#ref, branches, ..
+            } => {
+                assert_eq!(r#ref, "mian");
+                assert_eq!(branches, &["main", "dev"]);
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+        assert_eq!(
+            err.to_string(),
+            "Branch, tag or commit 'mian' not found in 'alice/rules'; available branches: main, dev"
+        );
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn default_branch_is_not_checked() {
+        // No ref exists on the server, yet the implicit `main` still resolves
+        let server = ref_server(&[]).await;
+        std::env::set_var("OCTO_BASE", server.url());
+        let res = resolve_repo(Some("alice".into()), Some("rules".into()), None, None).await;
+        std::env::remove_var("OCTO_BASE");
+
+        assert_eq!(res.unwrap().branch, "main");
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn missing_repo_suggests_similar_names_when_authenticated() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/repos/alice/cursor-rules")
+            .with_status(404)
+            .with_body(r#"{"message": "Not Found"}"#)
+            .create_async()
+            .await;
+        let listing = server
+            .mock("GET", "/users/alice/repos")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(
+                serde_json::json!([
+                    {"name": ".cursor-rules"},
+                    {"name": "dotfiles"},
+                    {"name": "cursor_rules"},
+                    {"name": "Lint-Rules"},
+                    {"name": "my-cursor"},
+                    {"name": "cursor-prompts"},
+                    {"name": "rules-archive"},
+                ])
+                .to_string(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        std::env::set_var("OCTO_BASE", server.url());
+        let err = resolve_repo(Some("alice".into()), None, None, Some("token".into()))
+            .await
+            .unwrap_err();
+        std::env::remove_var("OCTO_BASE");
+
+        match &err {
+            RepoDiscoveryError::RepoNotFound { suggestions, .. } => assert_eq!(
+                suggestions,
+                &[
+                    ".cursor-rules",
+                    "cursor_rules",
+                    "Lint-Rules",
+                    "my-cursor",
+                    "cursor-prompts"
+                ]
+            ),
+            other => panic!("unexpected error: {other}"),
+        }
+        assert!(err
+            .to_string()
+            .ends_with("not found or is private; did you mean: .cursor-rules, cursor_rules, Lint-Rules, my-cursor, cursor-prompts?"));
+        listing.assert_async().await;
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn verify_repo_exists_reports_rate_limit_with_reset_time() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/repos/foo/bar")
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", "1700000000")
+            .with_body(r#"{"message": "API rate limit exceeded"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/foo/private")
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "4999")
+            .with_body(r#"{"message": "Resource not accessible"}"#)
+            .create_async()
+            .await;
+        std::env::set_var("OCTO_BASE", server.url());
+        let limited = verify_repo_exists("foo", "bar", None).await.unwrap_err();
+        let forbidden = verify_repo_exists("foo", "private", None)
+            .await
+            .unwrap_err();
+        std::env::remove_var("OCTO_BASE");
+
+        let expected = DateTime::from_timestamp(1_700_000_000, 0);
+        match &limited {
+            RepoDiscoveryError::RateLimited { reset_at } => assert_eq!(*reset_at, expected),
+            other => panic!("unexpected error: {other}"),
+        }
+        let message = limited.to_string();
+        assert!(message.contains("auth login"), "{message}");
+        assert!(message.contains("wait until 22:13 UTC"), "{message}");
+
+        // A 403 with quota left is a permissions problem, not a rate limit
+        assert!(
+            matches!(forbidden, RepoDiscoveryError::NetworkError(_)),
+            "{forbidden}"
+        );
+    }
+
+    #[test]
+    fn rate_limit_without_reset_header_still_detected() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", http::HeaderValue::from_static("0"));
+        assert_eq!(
+            rate_limit_reset(http::StatusCode::TOO_MANY_REQUESTS, &headers),
+            Some(None)
+        );
+        assert_eq!(rate_limit_reset(http::StatusCode::OK, &headers), None);
+        assert_eq!(
+            RepoDiscoveryError::RateLimited { reset_at: None }.to_string(),
+            "GitHub API rate limit exceeded; run `cursor-rules auth login` or set GITHUB_TOKEN for a higher limit, or wait for the limit to reset"
+        );
+    }
+
     #[tokio::test]
     #[serial_test::serial]
     async fn owner_not_found_non_interactive() {
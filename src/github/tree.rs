@@ -1,27 +1,38 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
 
 use super::cache::{FileSystemCache, PersistentCache};
 use super::RepoLocator;
 use octocrab::Octocrab;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum NodeKind {
+    #[default]
     Dir,
     RuleFile,
     Manifest,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct RepoNode {
     pub name: String,
     pub path: String,
     pub kind: NodeKind,
-    #[allow(dead_code)]
-    pub children: Option<Vec<RepoNode>>, // Not used yet
+    /// Nested entries, populated only when a full tree is assembled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<RepoNode>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub manifest_count: Option<usize>,
+    /// Git blob SHA of a file, as reported by the tree API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob_sha: Option<String>,
+    /// File size in bytes, as reported by the tree API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
 }
 
 impl RepoNode {
@@ -30,12 +41,93 @@ impl RepoNode {
     }
 }
 
+/// Whether `node` is listed: dotfiles, and everything inside a dot-directory, only with
+/// `--all`. Every listing (the browser, `list`, `stats`, `search` and manifest discovery)
+/// filters through this so hidden entries appear or disappear together.
+pub fn should_show(node: &RepoNode, all: bool) -> bool {
+    all || !node.path.split('/').any(|segment| segment.starts_with('.'))
+}
+
+/// Tree response body with its `ETag` and `Last-Modified` headers
+type TreeResponse = (serde_json::Value, Option<String>, Option<String>);
+
+/// Longest we are willing to sleep for a rate limit reset before retrying
+const MAX_RATE_LIMIT_WAIT: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// GitHub throttled a request; `wait` is how long it asked us to back off, when known
+#[derive(Debug, thiserror::Error)]
+#[error("GitHub API rate limit exceeded ({status})")]
+struct RateLimited {
+    status: http::StatusCode,
+    wait: Option<std::time::Duration>,
+}
+
+/// Work out how long to wait from `Retry-After` (seconds) or `X-RateLimit-Reset`
+/// (unix timestamp), capped at [`MAX_RATE_LIMIT_WAIT`]. Returns `None` when neither
+/// header is present so callers can fall back to exponential backoff.
+pub(crate) fn rate_limit_delay(
+    headers: &http::HeaderMap,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<std::time::Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<i64>().ok())
+    };
+
+    let seconds = if let Some(retry_after) = header("retry-after") {
+        retry_after
+    } else if headers
+        .get("x-ratelimit-remaining")
+        .is_some_and(|remaining| remaining.as_bytes() != b"0")
+    {
+        // Quota left, so this is not a primary rate limit
+        return None;
+    } else {
+        header("x-ratelimit-reset")? - now.timestamp()
+    };
+
+    // Always pause at least a second so a reset that just passed doesn't spin
+    let wait = std::time::Duration::from_secs(seconds.max(1) as u64);
+    Some(wait.min(MAX_RATE_LIMIT_WAIT))
+}
+
+/// Whether `path` is at most `max_depth` levels deep, counting top-level entries as
+/// level 1. Everything is within an unlimited (`None`) depth.
+pub(crate) fn within_depth(path: &str, max_depth: Option<usize>) -> bool {
+    max_depth.is_none_or(|max_depth| path.matches('/').count() < max_depth)
+}
+
+/// Classify an entry by type and extension
+fn node_kind(path: &str, is_dir: bool) -> NodeKind {
+    if is_dir {
+        NodeKind::Dir
+    } else if path.ends_with(".mdc") {
+        NodeKind::RuleFile
+    } else if path.ends_with(".txt")
+        || path.ends_with(".yaml")
+        || path.ends_with(".yml")
+        || path.ends_with(".json")
+    {
+        NodeKind::Manifest
+    } else {
+        NodeKind::RuleFile
+    }
+}
+
 /// Repository tree with in-memory cache and persistent backing.
 /// Provides fast access to GitHub repository structure with offline capability.
 #[derive(Default)]
 pub struct RepoTree {
     cache: HashMap<String, Vec<RepoNode>>, // key = dir path ("" for root)
     persistent_cache: Option<FileSystemCache>,
+    /// Fetch one directory at a time on demand instead of the whole recursive tree
+    lazy: bool,
+    /// Deepest path level kept in memory (1 = top-level entries only); `None` is unlimited
+    max_depth: Option<usize>,
+    /// Directory quick-add manifests are discovered in; `None` is `quick-add`
+    manifest_dir: Option<String>,
 }
 
 impl RepoTree {
@@ -43,15 +135,56 @@ impl RepoTree {
         Self::default()
     }
 
-    /// Create new RepoTree with persistent cache enabled
-    pub fn with_persistent_cache() -> Result<Self> {
-        let persistent_cache = FileSystemCache::new()?;
+    /// Create new RepoTree with persistent cache enabled, expiring trees after `ttl_hours`
+    pub fn with_persistent_cache(ttl_hours: u64) -> Result<Self> {
+        let persistent_cache = FileSystemCache::with_ttl(ttl_hours)?;
         Ok(Self {
             cache: HashMap::new(),
             persistent_cache: Some(persistent_cache),
+            lazy: false,
+            max_depth: None,
+            manifest_dir: None,
         })
     }
 
+    /// Load directories one at a time as they are visited rather than fetching the whole
+    /// recursive tree up front. Used by the browser; `list` and manifest validation need
+    /// the full tree and keep the recursive path. A fresh tree already in the persistent
+    /// cache is still used in full, so nothing is fetched at all.
+    pub fn lazy(mut self) -> Self {
+        self.lazy = true;
+        self
+    }
+
+    /// Keep only entries at most `max_depth` levels deep (`--depth`); deeper ones are left
+    /// out of the in-memory cache entirely. The persistent cache still stores complete
+    /// trees, so a later run without a limit sees everything.
+    pub fn with_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Look for quick-add manifests in `dir` (`--manifest-dir`) instead of `quick-add/`.
+    /// Leading and trailing slashes are ignored.
+    pub fn with_manifest_dir(mut self, dir: &str) -> Self {
+        let dir = dir.trim_matches('/');
+        self.manifest_dir = (!dir.is_empty()).then(|| dir.to_string());
+        self
+    }
+
+    /// Directory quick-add manifests are discovered in, without a trailing slash
+    pub fn manifest_dir(&self) -> &str {
+        self.manifest_dir
+            .as_deref()
+            .unwrap_or(super::manifests::DEFAULT_MANIFEST_DIR)
+    }
+
+    /// Forget every directory loaded in memory, e.g. before browsing another repository.
+    /// The persistent cache and lazy mode are kept.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
     /// Ensure the git tree is loaded into memory (one API call) then return children for `dir_path`.
     /// Now supports persistent caching and --refresh flag.
     pub async fn children(
@@ -60,41 +193,137 @@ impl RepoTree {
         dir_path: &str,
         force_refresh: bool,
     ) -> Result<&[RepoNode]> {
-        if self.cache.is_empty() {
+        if super::client::offline() {
+            if self.cache.is_empty() {
+                self.load_offline_tree(locator)?;
+            }
+        } else if self.lazy {
+            // A complete tree already on disk serves every directory without the network
+            if !force_refresh && self.cache.is_empty() {
+                self.load_persistent_tree(locator, false).await;
+            }
+            if force_refresh || !self.cache.contains_key(dir_path) {
+                self.populate_dir(locator, dir_path).await?;
+            }
+        } else if self.cache.is_empty() {
             self.populate_cache(locator, force_refresh).await?;
         }
 
         Ok(self.cache.get(dir_path).map(Vec::as_slice).unwrap_or(&[]))
     }
 
-    async fn populate_cache(&mut self, locator: &RepoLocator, force_refresh: bool) -> Result<()> {
-        // Try to load from persistent cache first
-        if let Some(ref persistent_cache) = self.persistent_cache {
-            if let Ok(Some(cached_nodes)) = persistent_cache
-                .get_tree_cache(locator, force_refresh)
-                .await
-            {
-                // Populate in-memory cache from persistent cache
-                self.cache.clear();
-                for node in cached_nodes {
-                    let dir_key = if let Some(pos) = node.path.rfind('/') {
-                        node.path[..pos].to_string()
-                    } else {
-                        String::new()
-                    };
-                    self.cache.entry(dir_key).or_default().push(node);
+    /// Children of `dir_path` from the in-memory cache, without touching the network.
+    pub fn cached_children(&self, dir_path: &str) -> Option<&[RepoNode]> {
+        self.cache.get(dir_path).map(Vec::as_slice)
+    }
+
+    /// Every node loaded into the in-memory cache, in no particular order
+    pub fn cached_nodes(&self) -> impl Iterator<Item = &RepoNode> {
+        self.cache.values().flatten()
+    }
+
+    /// Look up a loaded node by its full path
+    pub fn find_node(&self, path: &str) -> Option<&RepoNode> {
+        let dir_key = path.rfind('/').map_or("", |pos| &path[..pos]);
+        self.cache
+            .get(dir_key)?
+            .iter()
+            .find(|node| node.path == path)
+    }
+
+    /// Record how many rules the manifest at `path` lists, if it is loaded
+    pub fn set_manifest_count(&mut self, path: &str, count: usize) {
+        let dir_key = path.rfind('/').map_or("", |pos| &path[..pos]);
+        if let Some(node) = self
+            .cache
+            .get_mut(dir_key)
+            .and_then(|nodes| nodes.iter_mut().find(|node| node.path == path))
+        {
+            node.manifest_count = Some(count);
+        }
+    }
+
+    /// Build a tree already loaded with `nodes`, so tests never reach the network
+    #[cfg(test)]
+    pub(crate) fn from_nodes(nodes: Vec<RepoNode>) -> Self {
+        let mut tree = Self::new();
+        tree.insert_nodes(nodes);
+        tree
+    }
+
+    /// Load the repository and assemble it into a nested tree where directories carry their
+    /// contents in `children`. Dotfiles and dot-directories are dropped unless `show_hidden`.
+    pub async fn full_tree(
+        &mut self,
+        locator: &RepoLocator,
+        force_refresh: bool,
+        show_hidden: bool,
+    ) -> Result<Vec<RepoNode>> {
+        // Make sure the flat cache is populated before walking it
+        self.children(locator, "", force_refresh).await?;
+        Ok(self.build_subtree("", show_hidden))
+    }
+
+    fn build_subtree(&self, dir_path: &str, show_hidden: bool) -> Vec<RepoNode> {
+        let Some(nodes) = self.cache.get(dir_path) else {
+            return Vec::new();
+        };
+
+        nodes
+            .iter()
+            .filter(|node| should_show(node, show_hidden))
+            .map(|node| {
+                let mut node = node.clone();
+                if node.is_dir() {
+                    node.children = Some(self.build_subtree(&node.path, show_hidden));
                 }
-                self.cache.entry(String::new()).or_default();
-                return Ok(());
+                node
+            })
+            .collect()
+    }
+
+    /// Fill the in-memory cache from a fresh tree in the persistent cache. Returns `false`
+    /// when there is no persistent cache or it holds nothing usable for `locator`.
+    async fn load_persistent_tree(&mut self, locator: &RepoLocator, force_refresh: bool) -> bool {
+        let Some(persistent_cache) = &self.persistent_cache else {
+            return false;
+        };
+        match persistent_cache
+            .get_tree_cache(locator, force_refresh)
+            .await
+        {
+            Ok(Some(cached_nodes)) => {
+                self.insert_nodes(cached_nodes);
+                true
             }
+            _ => false,
+        }
+    }
+
+    /// Fill the in-memory cache from whatever tree is on disk, however old, for `--offline`
+    fn load_offline_tree(&mut self, locator: &RepoLocator) -> Result<()> {
+        let nodes = self
+            .persistent_cache
+            .as_ref()
+            .and_then(|cache| cache.get_stale_tree_cache(locator).ok().flatten())
+            .with_context(|| {
+                format!(
+                    "no cached data for {}/{}; run online once",
+                    locator.owner, locator.repo
+                )
+            })?;
+        self.insert_nodes(nodes);
+        Ok(())
+    }
+
+    async fn populate_cache(&mut self, locator: &RepoLocator, force_refresh: bool) -> Result<()> {
+        // Try to load from persistent cache first
+        if self.load_persistent_tree(locator, force_refresh).await {
+            return Ok(());
         }
 
         // Fallback to GitHub API with conditional requests and rate limit handling
-        let octo = if let Ok(base) = std::env::var("OCTO_BASE") {
-            Octocrab::builder().base_uri(&base)?.build()?
-        } else {
-            Octocrab::builder().build()?
-        };
+        let octo = super::client::authenticated()?;
 
         // Get any existing ETag for conditional requests
         let existing_etag = if let Some(ref persistent_cache) = self.persistent_cache {
@@ -114,10 +343,25 @@ impl RepoTree {
         );
 
         // Make request with rate limit handling
-        let (response, response_etag, response_last_modified) = self
-            .make_api_request_with_rate_limit(&octo, &endpoint, existing_etag)
+        let fetched = self
+            .make_api_request_with_rate_limit(&octo, &endpoint, existing_etag.as_deref())
             .await?;
 
+        let (response, response_etag, response_last_modified) = match fetched {
+            Some(fetched) => fetched,
+            None => {
+                // 304 Not Modified: the tree on disk is still current
+                if let Some(nodes) = self.reuse_unmodified_tree(locator).await {
+                    self.insert_nodes(nodes);
+                    return Ok(());
+                }
+                // Tree file went missing; fetch it again without the validator
+                self.make_api_request_with_rate_limit(&octo, &endpoint, None)
+                    .await?
+                    .context("GitHub returned 304 for an unconditional request")?
+            }
+        };
+
         let empty: Vec<serde_json::Value> = Vec::new();
         let tree = response["tree"].as_array().unwrap_or(&empty);
 
@@ -127,63 +371,130 @@ impl RepoTree {
             let path = item["path"].as_str().unwrap_or("").to_string();
             let item_type = item["type"].as_str().unwrap_or("");
 
-            let kind = if item_type == "tree" {
-                NodeKind::Dir
-            } else if path.ends_with(".mdc") {
-                NodeKind::RuleFile
-            } else if path.ends_with(".txt")
-                || path.ends_with(".yaml")
-                || path.ends_with(".yml")
-                || path.ends_with(".json")
-            {
-                NodeKind::Manifest
-            } else {
-                NodeKind::RuleFile
-            };
-
+            let kind = node_kind(&path, item_type == "tree");
             let name = path.split('/').next_back().unwrap_or("").to_string();
-
-            let node = RepoNode {
+            let blob_sha = (item_type == "blob")
+                .then(|| item["sha"].as_str().map(str::to_string))
+                .flatten();
+            let size = (item_type == "blob")
+                .then(|| item["size"].as_u64())
+                .flatten();
+
+            all_nodes.push(RepoNode {
                 name,
-                path: path.clone(),
+                path,
                 kind,
                 children: None,
                 manifest_count: None,
-            };
+                blob_sha,
+                size,
+            });
+        }
+
+        // Store in persistent cache with HTTP headers
+        if let Some(ref persistent_cache) = self.persistent_cache {
+            let _ = persistent_cache
+                .store_tree_cache(locator, &all_nodes, response_etag, response_last_modified)
+                .await;
+        }
+
+        self.insert_nodes(all_nodes);
 
-            // Store for cache and add to in-memory cache
-            all_nodes.push(node.clone());
+        Ok(())
+    }
+
+    /// Fetch the entries of a single directory through the contents API and cache them
+    /// under `dir_path`, leaving every other cached directory untouched. Partial listings
+    /// are never written to the persistent cache, which holds complete trees only.
+    async fn populate_dir(&mut self, locator: &RepoLocator, dir_path: &str) -> Result<()> {
+        let octo = super::client::authenticated()?;
+        let endpoint = if dir_path.is_empty() {
+            format!(
+                "/repos/{}/{}/contents?ref={}",
+                locator.owner, locator.repo, locator.branch
+            )
+        } else {
+            format!(
+                "/repos/{}/{}/contents/{}?ref={}",
+                locator.owner, locator.repo, dir_path, locator.branch
+            )
+        };
+
+        let (response, _, _) = self
+            .make_api_request_with_rate_limit(&octo, &endpoint, None)
+            .await?
+            .context("GitHub returned 304 for an unconditional request")?;
+
+        let entries = response
+            .as_array()
+            .with_context(|| format!("{dir_path:?} is not a directory"))?;
+
+        let nodes = entries
+            .iter()
+            .map(|entry| {
+                let path = entry["path"].as_str().unwrap_or("").to_string();
+                let is_dir = entry["type"].as_str() == Some("dir");
+                let name = entry["name"].as_str().unwrap_or("").to_string();
+                let blob_sha = (!is_dir)
+                    .then(|| entry["sha"].as_str().map(str::to_string))
+                    .flatten();
+                let size = (!is_dir).then(|| entry["size"].as_u64()).flatten();
+                RepoNode {
+                    name,
+                    kind: node_kind(&path, is_dir),
+                    path,
+                    children: None,
+                    manifest_count: None,
+                    blob_sha,
+                    size,
+                }
+            })
+            .filter(|node: &RepoNode| within_depth(&node.path, self.max_depth))
+            .collect();
 
+        self.cache.insert(dir_path.to_string(), nodes);
+        Ok(())
+    }
+
+    /// Replace the in-memory cache with `nodes`, grouped by parent directory.
+    fn insert_nodes(&mut self, nodes: Vec<RepoNode>) {
+        self.cache.clear();
+        for node in nodes {
+            if !within_depth(&node.path, self.max_depth) {
+                continue;
+            }
             // Determine parent directory key
-            let dir_key = if let Some(pos) = path.rfind('/') {
-                path[..pos].to_string()
+            let dir_key = if let Some(pos) = node.path.rfind('/') {
+                node.path[..pos].to_string()
             } else {
                 String::new()
             };
-
             self.cache.entry(dir_key).or_default().push(node);
         }
 
         // Ensure root entry exists even if empty
         self.cache.entry(String::new()).or_default();
+    }
 
-        // Store in persistent cache with HTTP headers
-        if let Some(ref persistent_cache) = self.persistent_cache {
-            let _ = persistent_cache
-                .store_tree_cache(locator, &all_nodes, response_etag, response_last_modified)
-                .await;
-        }
-
-        Ok(())
+    /// Load the tree on disk after a 304 response and mark it fresh again, keeping its validators.
+    async fn reuse_unmodified_tree(&self, locator: &RepoLocator) -> Option<Vec<RepoNode>> {
+        let persistent_cache = self.persistent_cache.as_ref()?;
+        let nodes = persistent_cache.get_stale_tree_cache(locator).ok()??;
+        let metadata = persistent_cache.get_metadata(locator).ok()??;
+        let _ = persistent_cache
+            .store_tree_cache(locator, &nodes, metadata.etag, metadata.last_modified)
+            .await;
+        Some(nodes)
     }
 
-    /// Make API request with rate limit handling and exponential backoff
+    /// Make API request with rate limit handling and exponential backoff.
+    /// Returns `None` when GitHub answers 304 Not Modified for `existing_etag`.
     async fn make_api_request_with_rate_limit(
         &self,
         octo: &Octocrab,
         endpoint: &str,
-        existing_etag: Option<String>,
-    ) -> Result<(serde_json::Value, Option<String>, Option<String>)> {
+        existing_etag: Option<&str>,
+    ) -> Result<Option<TreeResponse>> {
         let mut attempts = 0;
         let max_attempts = 3;
         let mut delay = std::time::Duration::from_secs(1);
@@ -191,49 +502,11 @@ impl RepoTree {
         loop {
             attempts += 1;
 
-            // Make conditional request if we have an ETag
-            let result = if let Some(ref etag) = existing_etag {
-                match self.make_conditional_request(octo, endpoint, etag).await {
-                    Ok(Some((resp, new_etag, last_mod))) => {
-                        // Got fresh data (200 OK)
-                        return Ok((resp, new_etag, last_mod));
-                    }
-                    Ok(None) => {
-                        // Got 304 Not Modified - use existing cache
-                        if let Some(ref persistent_cache) = self.persistent_cache {
-                            if let Ok(Some(_cached_nodes)) = persistent_cache
-                                .get_tree_cache(
-                                    &RepoLocator {
-                                        owner: "dummy".to_string(),
-                                        repo: "dummy".to_string(),
-                                        branch: "main".to_string(),
-                                    },
-                                    true,
-                                ) // Force load from disk
-                                .await
-                            {
-                                // Return empty response since we're using cache
-                                return Ok((serde_json::json!({"tree": []}), None, None));
-                            }
-                        }
-                        // Fallback to regular request
-                        octo.get(endpoint, None::<&()>)
-                            .await
-                            .map_err(|e| anyhow::anyhow!("{}", e))
-                    }
-                    Err(e) => Err(e),
-                }
-            } else {
-                // No ETag available, make regular request
-                octo.get(endpoint, None::<&()>)
-                    .await
-                    .map_err(|e| anyhow::anyhow!("{}", e))
-            };
-
-            match result {
-                Ok(response) => {
-                    return Ok((response, None, None));
-                }
+            match self
+                .make_conditional_request(octo, endpoint, existing_etag)
+                .await
+            {
+                Ok(response) => return Ok(response),
                 Err(e) => {
                     // Check if it's a rate limit error
                     if self.is_rate_limit_error(&e) {
@@ -247,16 +520,27 @@ impl RepoTree {
                             ));
                         }
 
+                        // Prefer the wait GitHub asked for over guessing
+                        let wait = match e.downcast_ref::<RateLimited>().and_then(|r| r.wait) {
+                            Some(wait) => wait,
+                            None => {
+                                let wait = delay;
+                                delay =
+                                    std::cmp::min(delay * 2, std::time::Duration::from_secs(60));
+                                wait
+                            }
+                        };
+
+                        let resume_at = chrono::Local::now()
+                            + chrono::Duration::from_std(wait).unwrap_or_default();
                         tracing::warn!(
-                            "GitHub API rate limit hit. Retrying in {:?} (attempt {}/{})",
-                            delay,
+                            "GitHub API rate limited, resuming at {} (attempt {}/{})",
+                            resume_at.format("%H:%M"),
                             attempts,
                             max_attempts
                         );
 
-                        // Exponential backoff with jitter
-                        tokio::time::sleep(delay).await;
-                        delay = std::cmp::min(delay * 2, std::time::Duration::from_secs(60));
+                        tokio::time::sleep(wait).await;
                     } else {
                         // Not a rate limit error, propagate immediately
                         return Err(e);
@@ -268,34 +552,129 @@ impl RepoTree {
 
     /// Check if an error is a GitHub API rate limit error
     fn is_rate_limit_error(&self, error: &anyhow::Error) -> bool {
-        let error_str = error.to_string().to_lowercase();
-        error_str.contains("rate limit")
-            || error_str.contains("403")
-            || error_str.contains("api rate limit exceeded")
-            || error_str.contains("x-ratelimit")
+        error.downcast_ref::<RateLimited>().is_some()
     }
 
-    /// Make a conditional HTTP request using ETag
+    /// Make a GET request, sending `If-None-Match` when an ETag is known.
+    /// Returns `None` on 304 Not Modified, otherwise the body with its `ETag` and
+    /// `Last-Modified` response headers.
     async fn make_conditional_request(
         &self,
         octo: &Octocrab,
         endpoint: &str,
-        _etag: &str,
-    ) -> Result<Option<(serde_json::Value, Option<String>, Option<String>)>> {
-        // For now, we'll implement conditional requests using regular requests
-        // TODO: Implement proper conditional requests with custom headers
-        let response: serde_json::Value = octo.get(endpoint, None::<&()>).await?;
+        etag: Option<&str>,
+    ) -> Result<Option<TreeResponse>> {
+        let mut headers = http::HeaderMap::new();
+        if let Some(etag) = etag {
+            headers.insert(
+                http::header::IF_NONE_MATCH,
+                http::HeaderValue::from_str(etag).context("Invalid cached ETag")?,
+            );
+        }
+
+        let response = octo._get_with_headers(endpoint, Some(headers)).await?;
+        let status = response.status();
+        if status == http::StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+
+        let header = |name: http::header::HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let response_etag = header(http::header::ETAG);
+        let last_modified = header(http::header::LAST_MODIFIED);
+
+        if status == http::StatusCode::FORBIDDEN || status == http::StatusCode::TOO_MANY_REQUESTS {
+            let wait = rate_limit_delay(response.headers(), chrono::Utc::now());
+            // A 403 without rate-limit headers is a permissions problem, not throttling
+            if status == http::StatusCode::TOO_MANY_REQUESTS || wait.is_some() {
+                return Err(RateLimited { status, wait }.into());
+            }
+        }
+
+        let body = octo.body_to_string(response).await?;
+        if !status.is_success() {
+            anyhow::bail!("GitHub returned {status} for {endpoint}: {body}");
+        }
 
-        // Return response with empty headers for now
-        // This provides the framework for ETag integration without complex HTTP handling
-        Ok(Some((response, None, None)))
+        let json = serde_json::from_str(&body).context("Failed to parse GitHub tree response")?;
+        Ok(Some((json, response_etag, last_modified)))
     }
 }
 
+/// Load the nested trees of several repositories, at most `parallel` at a time, each into
+/// its own tree from `new_tree`, so every repository keeps its own cache lock. The result is sorted by `owner/repo` (then branch), so it
+/// does not depend on which fetch finished first.
+pub async fn full_trees(
+    locators: Vec<RepoLocator>,
+    parallel: usize,
+    new_tree: impl Fn() -> Result<RepoTree>,
+    force_refresh: bool,
+    show_hidden: bool,
+) -> Result<Vec<(RepoLocator, Vec<RepoNode>)>> {
+    let semaphore = Arc::new(Semaphore::new(parallel.max(1)));
+
+    let mut tasks = Vec::new();
+    for locator in locators {
+        let semaphore = semaphore.clone();
+        let mut tree = new_tree()?;
+        tasks.push(tokio::spawn(async move {
+            let _permit = semaphore.acquire().await?;
+            let nodes = tree
+                .full_tree(&locator, force_refresh, show_hidden)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to load {}/{}@{}",
+                        locator.owner, locator.repo, locator.branch
+                    )
+                })?;
+            anyhow::Ok((locator, nodes))
+        }));
+    }
+
+    let mut trees = Vec::new();
+    for task in tasks {
+        trees.push(task.await??);
+    }
+    trees.sort_by(|(a, _), (b, _)| {
+        (&a.owner, &a.repo, &a.branch).cmp(&(&b.owner, &b.repo, &b.branch))
+    });
+    Ok(trees)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn should_show_hides_dotfiles_and_dot_directories_without_all() {
+        let node = |path: &str| RepoNode {
+            name: path.rsplit('/').next().unwrap().into(),
+            path: path.into(),
+            kind: NodeKind::RuleFile,
+            ..Default::default()
+        };
+
+        for visible in ["react.mdc", "web/react.mdc", "quick-add/web.txt"] {
+            assert!(should_show(&node(visible), false), "{visible}");
+            assert!(should_show(&node(visible), true), "{visible}");
+        }
+        for hidden in [
+            ".github",
+            ".hidden.mdc",
+            "web/.draft.mdc",
+            ".internal/web/react.mdc",
+        ] {
+            assert!(!should_show(&node(hidden), false), "{hidden}");
+            assert!(should_show(&node(hidden), true), "{hidden}");
+        }
+    }
+
     #[tokio::test]
     async fn children_returns_cached_slice() {
         let locator = RepoLocator {
@@ -313,8 +692,7 @@ mod tests {
                 name: "dir".into(),
                 path: "dir".into(),
                 kind: NodeKind::Dir,
-                children: None,
-                manifest_count: None,
+                ..Default::default()
             }],
         );
 
@@ -323,6 +701,16 @@ mod tests {
         assert_eq!(slice[0].name, "dir");
     }
 
+    #[test]
+    fn cached_children_reads_memory_only() {
+        let mut tree = RepoTree::new();
+        assert!(tree.cached_children("").is_none());
+
+        tree.cache.insert("dir".into(), Vec::new());
+        assert_eq!(tree.cached_children("dir").map(<[RepoNode]>::len), Some(0));
+        assert!(tree.cached_children("other").is_none());
+    }
+
     #[tokio::test]
     async fn children_returns_empty_for_nonexistent_dir() {
         let locator = RepoLocator {
@@ -414,22 +802,19 @@ mod tests {
                 name: "src".into(),
                 path: "src".into(),
                 kind: NodeKind::Dir,
-                children: None,
-                manifest_count: None,
+                ..Default::default()
             },
             RepoNode {
                 name: "Button.mdc".into(),
                 path: "src/Button.mdc".into(),
                 kind: NodeKind::RuleFile,
-                children: None,
-                manifest_count: None,
+                ..Default::default()
             },
             RepoNode {
                 name: "manifest.txt".into(),
                 path: "src/manifest.txt".into(),
                 kind: NodeKind::Manifest,
-                children: None,
-                manifest_count: None,
+                ..Default::default()
             },
         ];
 
@@ -464,8 +849,7 @@ mod tests {
             name: "test".into(),
             path: "test".into(),
             kind: NodeKind::Dir,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         };
         assert!(dir_node.is_dir());
 
@@ -473,8 +857,7 @@ mod tests {
             name: "test.mdc".into(),
             path: "test.mdc".into(),
             kind: NodeKind::RuleFile,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         };
         assert!(!file_node.is_dir());
 
@@ -482,8 +865,7 @@ mod tests {
             name: "manifest.txt".into(),
             path: "manifest.txt".into(),
             kind: NodeKind::Manifest,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         };
         assert!(!manifest_node.is_dir());
     }
@@ -593,8 +975,7 @@ mod tests {
                 name: "empty_dir".into(),
                 path: "empty_dir".into(),
                 kind: NodeKind::Dir,
-                children: None,
-                manifest_count: None,
+                ..Default::default()
             }],
         );
 
@@ -614,8 +995,7 @@ mod tests {
             name: "file.mdc".into(),
             path: deep_path.into(),
             kind: NodeKind::RuleFile,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         };
 
         let dir_key = if let Some(pos) = deep_path.rfind('/') {
@@ -653,15 +1033,13 @@ mod tests {
                     name: "src".into(),
                     path: "src".into(),
                     kind: NodeKind::Dir,
-                    children: None,
-                    manifest_count: None,
+                    ..Default::default()
                 },
                 RepoNode {
                     name: "README.mdc".into(),
                     path: "README.mdc".into(),
                     kind: NodeKind::RuleFile,
-                    children: None,
-                    manifest_count: None,
+                    ..Default::default()
                 },
             ],
         );
@@ -734,7 +1112,7 @@ mod tests {
     #[test]
     fn test_with_persistent_cache_creation() {
         // Test successful creation
-        let result = RepoTree::with_persistent_cache();
+        let result = RepoTree::with_persistent_cache(crate::github::cache::DEFAULT_CACHE_TTL_HOURS);
         assert!(
             result.is_ok(),
             "Should create RepoTree with persistent cache"
@@ -755,25 +1133,22 @@ mod tests {
     fn test_is_rate_limit_error_detection() {
         let tree = RepoTree::new();
 
-        // Test various rate limit error patterns
-        let rate_limit_errors = vec![
-            anyhow::anyhow!("GitHub API rate limit exceeded"),
-            anyhow::anyhow!("HTTP 403 Forbidden"),
-            anyhow::anyhow!("api rate limit exceeded for user"),
-            anyhow::anyhow!("Rate limit exceeded. Please wait."),
-            anyhow::anyhow!("X-RateLimit-Remaining: 0"),
-            anyhow::anyhow!("RATE LIMIT"), // Test case insensitive
-        ];
-
-        for error in rate_limit_errors {
+        // Only a typed rate limit counts, whatever the status
+        for status in [
+            http::StatusCode::FORBIDDEN,
+            http::StatusCode::TOO_MANY_REQUESTS,
+        ] {
+            let error = anyhow::Error::from(RateLimited { status, wait: None });
             assert!(
                 tree.is_rate_limit_error(&error),
                 "Should detect rate limit error: {error}"
             );
         }
 
-        // Test non-rate-limit errors
+        // Error text alone, such as a permission 403, is not a rate limit
         let non_rate_limit_errors = vec![
+            anyhow::anyhow!("GitHub returned 403 Forbidden for /repos/o/r/git/trees/main"),
+            anyhow::anyhow!("GitHub API rate limit exceeded"),
             anyhow::anyhow!("Network connection failed"),
             anyhow::anyhow!("Repository not found"),
             anyhow::anyhow!("Invalid authentication token"),
@@ -825,8 +1200,7 @@ mod tests {
                 name: "cached_file.mdc".into(),
                 path: "cached_file.mdc".into(),
                 kind: NodeKind::RuleFile,
-                children: None,
-                manifest_count: None,
+                ..Default::default()
             }],
         );
 
@@ -853,6 +1227,7 @@ mod tests {
             kind: NodeKind::RuleFile,
             children: Some(vec![]),
             manifest_count: Some(5),
+            ..Default::default()
         };
 
         assert_eq!(node.name, "test.mdc");
@@ -866,8 +1241,7 @@ mod tests {
             name: "src".to_string(),
             path: "src".to_string(),
             kind: NodeKind::Dir,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         };
 
         assert!(dir_node.is_dir());
@@ -892,8 +1266,8 @@ mod tests {
             name: "test.mdc".to_string(),
             path: "src/test.mdc".to_string(),
             kind: NodeKind::RuleFile,
-            children: None,
             manifest_count: Some(3),
+            ..Default::default()
         };
 
         let serialized = serde_json::to_string(&node).unwrap();
@@ -978,8 +1352,7 @@ mod tests {
             name: "file.mdc".into(),
             path: "subdir/file.mdc".into(),
             kind: NodeKind::RuleFile,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         };
 
         tree.cache
@@ -995,4 +1368,598 @@ mod tests {
         let empty_result = tree.children(&locator, "nonexistent", false).await.unwrap();
         assert_eq!(empty_result.len(), 0);
     }
+
+    fn contents_entry(path: &str, kind: &str) -> serde_json::Value {
+        serde_json::json!({
+            "name": path.rsplit('/').next().unwrap(),
+            "path": path,
+            "type": kind,
+            "sha": format!("sha-{path}"),
+        })
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn lazy_children_fetch_one_directory_at_a_time() {
+        let mut server = mockito::Server::new_async().await;
+        let root = server
+            .mock("GET", "/repos/o/r/contents")
+            .match_query(mockito::Matcher::UrlEncoded("ref".into(), "main".into()))
+            .with_status(200)
+            .with_body(
+                serde_json::json!([
+                    contents_entry("frontend", "dir"),
+                    contents_entry("backend", "dir"),
+                    contents_entry("stack.txt", "file"),
+                ])
+                .to_string(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let frontend = server
+            .mock("GET", "/repos/o/r/contents/frontend")
+            .match_query(mockito::Matcher::UrlEncoded("ref".into(), "main".into()))
+            .with_status(200)
+            .with_body(
+                serde_json::json!([contents_entry("frontend/react.mdc", "file")]).to_string(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let backend = server
+            .mock("GET", "/repos/o/r/contents/backend")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body("[]")
+            .expect(0)
+            .create_async()
+            .await;
+        std::env::set_var("OCTO_BASE", server.url());
+
+        let locator = RepoLocator {
+            owner: "o".into(),
+            repo: "r".into(),
+            branch: "main".into(),
+        };
+        let mut tree = RepoTree::new().lazy();
+        let root_nodes = tree
+            .children(&locator, "", false)
+            .await
+            .map(<[RepoNode]>::to_vec);
+        let frontend_nodes = tree
+            .children(&locator, "frontend", false)
+            .await
+            .map(<[RepoNode]>::to_vec);
+        // Revisiting both directories is served from memory
+        let revisited = tree.children(&locator, "", false).await.is_ok()
+            && tree.children(&locator, "frontend", false).await.is_ok();
+        std::env::remove_var("OCTO_BASE");
+
+        let root_nodes = root_nodes.unwrap();
+        assert_eq!(root_nodes.len(), 3);
+        assert_eq!(root_nodes[0].kind, NodeKind::Dir);
+        assert_eq!(root_nodes[2].kind, NodeKind::Manifest);
+        assert_eq!(root_nodes[2].blob_sha.as_deref(), Some("sha-stack.txt"));
+        assert_eq!(root_nodes[0].blob_sha, None);
+
+        let frontend_nodes = frontend_nodes.unwrap();
+        assert_eq!(frontend_nodes.len(), 1);
+        assert_eq!(frontend_nodes[0].name, "react.mdc");
+        assert_eq!(frontend_nodes[0].kind, NodeKind::RuleFile);
+        assert!(revisited);
+
+        // The sibling directory was never visited, so it was never fetched
+        assert!(tree.cached_children("backend").is_none());
+        root.assert_async().await;
+        frontend.assert_async().await;
+        backend.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn lazy_children_use_seeded_directories_without_fetching() {
+        let locator = RepoLocator {
+            owner: "o".into(),
+            repo: "r".into(),
+            branch: "main".into(),
+        };
+        let mut tree = RepoTree::new().lazy();
+        tree.cache.insert(
+            "frontend".into(),
+            vec![RepoNode {
+                name: "react.mdc".into(),
+                path: "frontend/react.mdc".into(),
+                kind: NodeKind::RuleFile,
+                ..Default::default()
+            }],
+        );
+
+        // Only "frontend" is cached; asking for it must not touch the (empty) root
+        let slice = tree.children(&locator, "frontend", false).await.unwrap();
+        assert_eq!(slice.len(), 1);
+        assert!(tree.cached_children("").is_none());
+    }
+
+    fn seed_nested_tree(tree: &mut RepoTree) {
+        let node = |path: &str, kind: NodeKind| RepoNode {
+            name: path.rsplit('/').next().unwrap().to_string(),
+            path: path.to_string(),
+            kind,
+            ..Default::default()
+        };
+
+        tree.cache.insert(
+            String::new(),
+            vec![
+                node("frontend", NodeKind::Dir),
+                node(".github", NodeKind::Dir),
+                node("README.mdc", NodeKind::RuleFile),
+            ],
+        );
+        tree.cache.insert(
+            "frontend".to_string(),
+            vec![
+                node("frontend/react", NodeKind::Dir),
+                node("frontend/.draft.mdc", NodeKind::RuleFile),
+            ],
+        );
+        tree.cache.insert(
+            "frontend/react".to_string(),
+            vec![node("frontend/react/hooks.mdc", NodeKind::RuleFile)],
+        );
+        tree.cache.insert(
+            ".github".to_string(),
+            vec![node(".github/ci.mdc", NodeKind::RuleFile)],
+        );
+    }
+
+    #[tokio::test]
+    async fn test_full_tree_nests_directories_and_hides_dotfiles() {
+        let locator = RepoLocator {
+            owner: "o".into(),
+            repo: "r".into(),
+            branch: "main".into(),
+        };
+        let mut tree = RepoTree::new();
+        seed_nested_tree(&mut tree);
+
+        let nodes = tree.full_tree(&locator, false, false).await.unwrap();
+
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].name, "frontend");
+        assert!(nodes[1].children.is_none());
+
+        let frontend_children = nodes[0].children.as_ref().unwrap();
+        assert_eq!(frontend_children.len(), 1);
+        assert_eq!(frontend_children[0].name, "react");
+
+        let react_children = frontend_children[0].children.as_ref().unwrap();
+        assert_eq!(react_children[0].path, "frontend/react/hooks.mdc");
+    }
+
+    #[tokio::test]
+    async fn test_full_tree_includes_dotfiles_when_requested() {
+        let locator = RepoLocator {
+            owner: "o".into(),
+            repo: "r".into(),
+            branch: "main".into(),
+        };
+        let mut tree = RepoTree::new();
+        seed_nested_tree(&mut tree);
+
+        let nodes = tree.full_tree(&locator, false, true).await.unwrap();
+
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[1].children.as_ref().unwrap()[0].name, "ci.mdc");
+        assert_eq!(nodes[0].children.as_ref().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_full_tree_empty_repo_serializes_to_empty_array() {
+        let locator = RepoLocator {
+            owner: "o".into(),
+            repo: "r".into(),
+            branch: "main".into(),
+        };
+        let mut tree = RepoTree::new();
+        tree.cache.insert(String::new(), Vec::new());
+
+        let nodes = tree.full_tree(&locator, false, false).await.unwrap();
+
+        assert!(nodes.is_empty());
+        assert_eq!(serde_json::to_string(&nodes).unwrap(), "[]");
+    }
+
+    /// Point the persistent cache and GitHub client at temporary locations,
+    /// returning the previous cache home for [`restore_mock_env`]
+    fn set_mock_env(cache_home: &std::path::Path, base: &str) -> Option<String> {
+        let original_cache = std::env::var("XDG_CACHE_HOME").ok();
+        std::env::set_var("XDG_CACHE_HOME", cache_home);
+        std::env::set_var("OCTO_BASE", base);
+        original_cache
+    }
+
+    fn restore_mock_env(original_cache: Option<String>) {
+        std::env::remove_var("OCTO_BASE");
+        match original_cache {
+            Some(value) => std::env::set_var("XDG_CACHE_HOME", value),
+            None => std::env::remove_var("XDG_CACHE_HOME"),
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn offline_without_cached_tree_names_the_repo() {
+        let cache_home = tempfile::tempdir().unwrap();
+        let original = set_mock_env(cache_home.path(), "http://127.0.0.1:9");
+        super::super::client::set_offline(true);
+
+        let mut tree =
+            RepoTree::with_persistent_cache(crate::github::cache::DEFAULT_CACHE_TTL_HOURS).unwrap();
+        let result = tree
+            .children(&etag_locator(), "", false)
+            .await
+            .map(<[_]>::len);
+        super::super::client::set_offline(false);
+        restore_mock_env(original);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "no cached data for etag/rules; run online once"
+        );
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn offline_serves_expired_cached_tree() {
+        let cache_home = tempfile::tempdir().unwrap();
+        let original = set_mock_env(cache_home.path(), "http://127.0.0.1:9");
+        let locator = etag_locator();
+        FileSystemCache::new()
+            .unwrap()
+            .store_tree_cache(
+                &locator,
+                &[RepoNode {
+                    name: "cached.mdc".into(),
+                    path: "cached.mdc".into(),
+                    kind: NodeKind::RuleFile,
+                    ..Default::default()
+                }],
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        super::super::client::set_offline(true);
+
+        // A zero TTL means the tree is always expired; offline it is used anyway, and
+        // even a lazy tree asked to refresh must not reach the network
+        let mut tree = RepoTree::with_persistent_cache(0).unwrap().lazy();
+        let nodes = tree.children(&locator, "", true).await.map(<[_]>::to_vec);
+        super::super::client::set_offline(false);
+        restore_mock_env(original);
+
+        let nodes = nodes.unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].name, "cached.mdc");
+    }
+
+    fn etag_locator() -> RepoLocator {
+        RepoLocator {
+            owner: "etag".into(),
+            repo: "rules".into(),
+            branch: "main".into(),
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn not_modified_response_reuses_cached_tree() {
+        let cache_home = tempfile::tempdir().unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/repos/etag/rules/git/trees/main")
+            .match_query(mockito::Matcher::UrlEncoded("recursive".into(), "1".into()))
+            .match_header("if-none-match", "\"abc\"")
+            .with_status(304)
+            .create_async()
+            .await;
+        let original = set_mock_env(cache_home.path(), &server.url());
+
+        let locator = etag_locator();
+        let cache = FileSystemCache::new().unwrap();
+        let cached = vec![RepoNode {
+            name: "cached.mdc".into(),
+            path: "cached.mdc".into(),
+            kind: NodeKind::RuleFile,
+            ..Default::default()
+        }];
+        cache
+            .store_tree_cache(&locator, &cached, Some("\"abc\"".into()), None)
+            .await
+            .unwrap();
+
+        // --refresh skips the freshness check but still revalidates
+        let mut tree =
+            RepoTree::with_persistent_cache(crate::github::cache::DEFAULT_CACHE_TTL_HOURS).unwrap();
+        let nodes = tree.children(&locator, "", true).await.map(<[_]>::to_vec);
+        let metadata = cache.get_metadata(&locator).unwrap().unwrap();
+        restore_mock_env(original);
+
+        mock.assert_async().await;
+        let nodes = nodes.unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].name, "cached.mdc");
+        assert_eq!(metadata.etag.as_deref(), Some("\"abc\""));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn modified_response_refreshes_cache_and_etag() {
+        let cache_home = tempfile::tempdir().unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/repos/etag/rules/git/trees/main")
+            .match_query(mockito::Matcher::UrlEncoded("recursive".into(), "1".into()))
+            .match_header("if-none-match", "\"old\"")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("etag", "\"new\"")
+            .with_header("last-modified", "Wed, 01 Jan 2025 00:00:00 GMT")
+            .with_body(
+                r#"{"tree": [
+                    {"path": "rules", "type": "tree", "sha": "t1"},
+                    {"path": "rules/fresh.mdc", "type": "blob", "sha": "b1"}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let original = set_mock_env(cache_home.path(), &server.url());
+
+        let locator = etag_locator();
+        let cache = FileSystemCache::new().unwrap();
+        cache
+            .store_tree_cache(&locator, &[], Some("\"old\"".into()), None)
+            .await
+            .unwrap();
+
+        let mut tree =
+            RepoTree::with_persistent_cache(crate::github::cache::DEFAULT_CACHE_TTL_HOURS).unwrap();
+        let nodes = tree.children(&locator, "", true).await.map(<[_]>::to_vec);
+        let metadata = cache.get_metadata(&locator).unwrap().unwrap();
+        restore_mock_env(original);
+
+        mock.assert_async().await;
+        let nodes = nodes.unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].name, "rules");
+        // Only blobs carry their SHA
+        assert_eq!(nodes[0].blob_sha, None);
+        let file = tree.find_node("rules/fresh.mdc").unwrap();
+        assert_eq!(file.blob_sha.as_deref(), Some("b1"));
+        assert!(tree.find_node("rules/missing.mdc").is_none());
+        assert_eq!(metadata.etag.as_deref(), Some("\"new\""));
+        assert_eq!(
+            metadata.last_modified.as_deref(),
+            Some("Wed, 01 Jan 2025 00:00:00 GMT")
+        );
+    }
+
+    fn rate_limit_headers(pairs: &[(&'static str, String)]) -> http::HeaderMap {
+        let mut headers = http::HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, http::HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn depth_limit_counts_path_segments() {
+        let paths = ["a.mdc", "dir", "dir/b.mdc", "dir/sub", "dir/sub/c.mdc"];
+        let kept = |depth| {
+            paths
+                .into_iter()
+                .filter(|path| within_depth(path, depth))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(kept(None), paths);
+        assert_eq!(kept(Some(1)), ["a.mdc", "dir"]);
+        assert_eq!(kept(Some(2)), ["a.mdc", "dir", "dir/b.mdc", "dir/sub"]);
+        assert_eq!(kept(Some(3)), paths);
+        assert_eq!(kept(Some(0)), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn depth_limited_tree_omits_deeper_entries() {
+        let node = |path: &str, kind| RepoNode {
+            name: path.rsplit('/').next().unwrap().into(),
+            path: path.into(),
+            kind,
+            ..Default::default()
+        };
+        let mut tree = RepoTree::new().with_depth(Some(1));
+        tree.insert_nodes(vec![
+            node("top.mdc", NodeKind::RuleFile),
+            node("frontend", NodeKind::Dir),
+            node("frontend/react.mdc", NodeKind::RuleFile),
+        ]);
+
+        assert_eq!(tree.cached_nodes().count(), 2);
+        assert!(tree.find_node("frontend").is_some());
+        assert_eq!(tree.cached_children("frontend"), None);
+    }
+
+    #[test]
+    fn rate_limit_delay_prefers_headers_and_caps() {
+        let now = chrono::Utc::now();
+        let reset_in = |secs: i64| (now.timestamp() + secs).to_string();
+
+        let headers = rate_limit_headers(&[
+            ("x-ratelimit-remaining", "0".into()),
+            ("x-ratelimit-reset", reset_in(90)),
+        ]);
+        assert_eq!(
+            rate_limit_delay(&headers, now),
+            Some(std::time::Duration::from_secs(90))
+        );
+
+        // Retry-After wins over the reset timestamp
+        let headers = rate_limit_headers(&[
+            ("retry-after", "7".into()),
+            ("x-ratelimit-reset", reset_in(90)),
+        ]);
+        assert_eq!(
+            rate_limit_delay(&headers, now),
+            Some(std::time::Duration::from_secs(7))
+        );
+
+        // Far-off resets are capped, past ones still wait a moment
+        let headers = rate_limit_headers(&[("x-ratelimit-reset", reset_in(3600))]);
+        assert_eq!(rate_limit_delay(&headers, now), Some(MAX_RATE_LIMIT_WAIT));
+        let headers = rate_limit_headers(&[("x-ratelimit-reset", reset_in(-30))]);
+        assert_eq!(
+            rate_limit_delay(&headers, now),
+            Some(std::time::Duration::from_secs(1))
+        );
+
+        // No headers, or quota remaining, means fall back to exponential backoff
+        assert_eq!(rate_limit_delay(&http::HeaderMap::new(), now), None);
+        let headers = rate_limit_headers(&[
+            ("x-ratelimit-remaining", "12".into()),
+            ("x-ratelimit-reset", reset_in(90)),
+        ]);
+        assert_eq!(rate_limit_delay(&headers, now), None);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_response_carries_reset_delay() {
+        let mut server = mockito::Server::new_async().await;
+        let reset = chrono::Utc::now().timestamp() + 120;
+        let _m = server
+            .mock("GET", "/repos/o/r/git/trees/main")
+            .match_query(mockito::Matcher::Any)
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", &reset.to_string())
+            .with_body(r#"{"message": "API rate limit exceeded"}"#)
+            .create_async()
+            .await;
+
+        let octo = Octocrab::builder()
+            .base_uri(server.url())
+            .unwrap()
+            .build()
+            .unwrap();
+        let tree = RepoTree::new();
+        let err = tree
+            .make_conditional_request(&octo, "/repos/o/r/git/trees/main?recursive=1", None)
+            .await
+            .unwrap_err();
+
+        assert!(tree.is_rate_limit_error(&err));
+        let limited = err.downcast_ref::<RateLimited>().expect("typed rate limit");
+        let wait = limited.wait.expect("delay from X-RateLimit-Reset");
+        assert!(
+            (118..=120).contains(&wait.as_secs()),
+            "unexpected wait {wait:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn forbidden_without_rate_limit_headers_is_not_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let forbidden = server
+            .mock("GET", "/repos/o/r/git/trees/main")
+            .match_query(mockito::Matcher::Any)
+            .with_status(403)
+            .with_body(r#"{"message": "Resource not accessible by integration"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let octo = Octocrab::builder()
+            .base_uri(server.url())
+            .unwrap()
+            .build()
+            .unwrap();
+        let tree = RepoTree::new();
+        let err = tree
+            .make_api_request_with_rate_limit(&octo, "/repos/o/r/git/trees/main?recursive=1", None)
+            .await
+            .unwrap_err();
+
+        forbidden.assert_async().await;
+        assert!(!tree.is_rate_limit_error(&err));
+        assert!(err.to_string().contains("403 Forbidden"), "{err}");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    #[serial_test::serial]
+    async fn full_trees_fetches_repos_in_parallel_and_sorts_them() {
+        let cache_home = tempfile::tempdir().unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let repos = ["zeta/rules", "alpha/rules", "mid/tools", "alpha/extra"];
+        let mut mocks = Vec::new();
+        for repo in repos {
+            let body = format!(
+                r#"{{"tree": [{{"path": "{}.mdc", "type": "blob", "sha": "s"}}]}}"#,
+                repo.replace('/', "-")
+            );
+            mocks.push(
+                server
+                    .mock("GET", format!("/repos/{repo}/git/trees/main").as_str())
+                    .match_query(mockito::Matcher::Any)
+                    .with_status(200)
+                    .with_header("content-type", "application/json")
+                    .with_body(body)
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+        let original = set_mock_env(cache_home.path(), &server.url());
+
+        let locators = repos
+            .iter()
+            .map(|repo| {
+                let (owner, repo) = repo.split_once('/').unwrap();
+                RepoLocator {
+                    owner: owner.into(),
+                    repo: repo.into(),
+                    branch: "main".into(),
+                }
+            })
+            .collect();
+        let trees = full_trees(
+            locators,
+            2,
+            || RepoTree::with_persistent_cache(crate::github::cache::DEFAULT_CACHE_TTL_HOURS),
+            false,
+            false,
+        )
+        .await;
+        restore_mock_env(original);
+
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+        let listed: Vec<_> = trees
+            .unwrap()
+            .into_iter()
+            .map(|(locator, nodes)| {
+                assert_eq!(nodes.len(), 1);
+                format!("{}/{} {}", locator.owner, locator.repo, nodes[0].path)
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                "alpha/extra alpha-extra.mdc",
+                "alpha/rules alpha-rules.mdc",
+                "mid/tools mid-tools.mdc",
+                "zeta/rules zeta-rules.mdc",
+            ]
+        );
+    }
 }
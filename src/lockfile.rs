@@ -0,0 +1,503 @@
+//! Lockfile support for copied rule files.
+//!
+//! A `rules.lock` file lives in the output directory next to the copied rules and
+//! records where each file came from together with the Git blob SHA of the content
+//! that was written. Every copy updates it, which lets `verify` detect local edits and
+//! `sync` restore exactly the recorded versions.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::copier::git_blob_sha;
+
+/// File name of the lockfile stored in the output directory
+pub const LOCKFILE_NAME: &str = "rules.lock";
+
+/// Contents of a `rules.lock` file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RulesLock {
+    /// Locked rule files, one entry per copied destination
+    #[serde(default, rename = "file")]
+    pub files: Vec<LockedFile>,
+}
+
+/// A single copied rule file recorded in the lockfile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedFile {
+    /// Source path in the repository
+    pub source: String,
+    /// Destination path relative to the output directory
+    pub destination: String,
+    /// Repository owner the file was copied from
+    pub owner: String,
+    /// Repository name the file was copied from
+    pub repo: String,
+    /// Branch the file was copied from
+    pub branch: String,
+    /// Manifest that copied the file; `None` for files copied from the browser or a batch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<String>,
+    /// Git blob SHA of the copied content
+    pub sha: String,
+}
+
+/// Result of checking a locked file against its local copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyStatus {
+    /// Local content matches the recorded SHA
+    Ok,
+    /// Local content differs from the recorded SHA
+    Modified,
+    /// Local file no longer exists
+    Missing,
+    /// Destination is absolute or climbs out of the output directory, so it was not read
+    OutsideOutputDir,
+}
+
+/// Verification outcome for a single locked file
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyEntry {
+    /// Destination path relative to the output directory
+    pub destination: String,
+    /// Source path in the repository
+    pub source: String,
+    /// SHA recorded in the lockfile
+    pub expected_sha: String,
+    /// SHA of the local file, if it exists
+    pub actual_sha: Option<String>,
+    /// Verification status
+    pub status: VerifyStatus,
+}
+
+/// A locked file whose source has changed in the repository since it was copied
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Drift {
+    /// Source path in the repository
+    pub source: String,
+    /// SHA recorded in the lockfile
+    pub locked_sha: String,
+    /// SHA of the source in the repository now; `None` when it was removed
+    pub current_sha: Option<String>,
+}
+
+impl LockedFile {
+    /// Where this file lives under `output_dir`; `None` when the recorded destination would
+    /// point outside it
+    pub fn local_path(&self, output_dir: &Path) -> Option<PathBuf> {
+        let destination = Path::new(&self.destination);
+        destination
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)))
+            .then(|| output_dir.join(destination))
+    }
+}
+
+impl RulesLock {
+    /// Record `file`, replacing any earlier entry for the same destination. Entries are
+    /// kept sorted by destination so the lockfile diffs cleanly.
+    pub fn record(&mut self, file: LockedFile) {
+        match self
+            .files
+            .iter_mut()
+            .find(|existing| existing.destination == file.destination)
+        {
+            Some(existing) => *existing = file,
+            None => self.files.push(file),
+        }
+        self.files.sort_by(|a, b| a.destination.cmp(&b.destination));
+    }
+
+    /// Drop the entry for `destination`
+    pub fn forget(&mut self, destination: &str) {
+        self.files.retain(|file| file.destination != destination);
+    }
+}
+
+/// Get the lockfile path for an output directory
+pub fn lockfile_path(output_dir: &Path) -> PathBuf {
+    output_dir.join(LOCKFILE_NAME)
+}
+
+/// Load the lockfile from an output directory, returning `None` if it doesn't exist
+pub fn load_lockfile(output_dir: &Path) -> Result<Option<RulesLock>> {
+    let path = lockfile_path(output_dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read lockfile {}", path.display()))?;
+
+    let lock: RulesLock = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse lockfile {}", path.display()))?;
+
+    Ok(Some(lock))
+}
+
+/// Write `lock` to the output directory
+pub fn save_lockfile(output_dir: &Path, lock: &RulesLock) -> Result<()> {
+    let path = lockfile_path(output_dir);
+    let content = toml::to_string_pretty(lock).context("Failed to serialize lockfile")?;
+    fs::write(&path, content)
+        .with_context(|| format!("Failed to write lockfile {}", path.display()))
+}
+
+/// Add `files` to the output directory's lockfile, creating it if needed
+pub fn update_lockfile(output_dir: &Path, files: Vec<LockedFile>) -> Result<()> {
+    let mut lock = load_lockfile(output_dir)?.unwrap_or_default();
+    for file in files {
+        lock.record(file);
+    }
+    save_lockfile(output_dir, &lock)
+}
+
+/// Locked files whose source no longer has the recorded SHA. `current_sha` looks up the
+/// SHA a source path has in the repository now, or `None` when it is gone.
+pub fn find_drift<'a>(
+    files: impl IntoIterator<Item = &'a LockedFile>,
+    current_sha: impl Fn(&str) -> Option<String>,
+) -> Vec<Drift> {
+    files
+        .into_iter()
+        .filter_map(|file| {
+            let current = current_sha(&file.source);
+            let unchanged = current
+                .as_deref()
+                .is_some_and(|sha| sha.eq_ignore_ascii_case(&file.sha));
+            (!unchanged).then(|| Drift {
+                source: file.source.clone(),
+                locked_sha: file.sha.clone(),
+                current_sha: current,
+            })
+        })
+        .collect()
+}
+
+/// Locked `.mdc` rules that `manifest` copied from `owner/repo` and whose source is not
+/// among `current_sources`: the previous set minus the current one, as removed by
+/// `--prune`. Rules from other manifests or from the browser are never included.
+pub fn prune_set<'a>(
+    lock: &'a RulesLock,
+    owner: &str,
+    repo: &str,
+    manifest: &str,
+    current_sources: &[String],
+) -> Vec<&'a LockedFile> {
+    let current: std::collections::HashSet<&str> =
+        current_sources.iter().map(String::as_str).collect();
+    lock.files
+        .iter()
+        .filter(|file| {
+            file.owner.eq_ignore_ascii_case(owner)
+                && file.repo.eq_ignore_ascii_case(repo)
+                && file.manifest.as_deref() == Some(manifest)
+                && file.destination.ends_with(".mdc")
+                && !current.contains(file.source.as_str())
+        })
+        .collect()
+}
+
+/// Recompute the Git blob SHA of every locked file and compare it with the recorded value
+pub fn verify_lockfile(lock: &RulesLock, output_dir: &Path) -> Result<Vec<VerifyEntry>> {
+    let mut entries = Vec::with_capacity(lock.files.len());
+
+    for file in &lock.files {
+        let Some(local_path) = file.local_path(output_dir) else {
+            entries.push(VerifyEntry {
+                destination: file.destination.clone(),
+                source: file.source.clone(),
+                expected_sha: file.sha.clone(),
+                actual_sha: None,
+                status: VerifyStatus::OutsideOutputDir,
+            });
+            continue;
+        };
+
+        let actual_sha = if local_path.exists() {
+            let content = fs::read(&local_path)
+                .with_context(|| format!("Failed to read {}", local_path.display()))?;
+            Some(git_blob_sha(&content))
+        } else {
+            None
+        };
+
+        let status = match &actual_sha {
+            None => VerifyStatus::Missing,
+            Some(sha) if *sha == file.sha => VerifyStatus::Ok,
+            Some(_) => VerifyStatus::Modified,
+        };
+
+        entries.push(VerifyEntry {
+            destination: file.destination.clone(),
+            source: file.source.clone(),
+            expected_sha: file.sha.clone(),
+            actual_sha,
+            status,
+        });
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const FRONTEND: &str = "quick-add/frontend.txt";
+
+    fn locked_file(destination: &str, sha: &str) -> LockedFile {
+        LockedFile {
+            source: format!("frontend/{destination}"),
+            destination: destination.to_string(),
+            owner: "owner".to_string(),
+            repo: "cursor-rules".to_string(),
+            branch: "main".to_string(),
+            manifest: Some(FRONTEND.to_string()),
+            sha: sha.to_string(),
+        }
+    }
+
+    #[test]
+    fn lockfile_toml_round_trip() {
+        let lock = RulesLock {
+            files: vec![
+                locked_file("react.mdc", "abc123"),
+                locked_file("vue.mdc", "def456"),
+            ],
+        };
+
+        let serialized = toml::to_string_pretty(&lock).unwrap();
+        assert!(serialized.contains("[[file]]"));
+
+        let deserialized: RulesLock = toml::from_str(&serialized).unwrap();
+        assert_eq!(lock, deserialized);
+    }
+
+    #[test]
+    fn update_lockfile_replaces_entries_by_destination() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path();
+
+        update_lockfile(
+            output_dir,
+            vec![locked_file("vue.mdc", "v1"), locked_file("react.mdc", "r1")],
+        )
+        .unwrap();
+        update_lockfile(output_dir, vec![locked_file("vue.mdc", "v2")]).unwrap();
+
+        let lock = load_lockfile(output_dir).unwrap().unwrap();
+        assert_eq!(
+            lock.files,
+            [locked_file("react.mdc", "r1"), locked_file("vue.mdc", "v2")]
+        );
+        let written = fs::read_to_string(lockfile_path(output_dir)).unwrap();
+        assert!(written.contains("sha = \"v2\""), "{written}");
+    }
+
+    #[test]
+    fn find_drift_reports_changed_and_removed_sources() {
+        let files = [
+            locked_file("react.mdc", "aaa"),
+            locked_file("vue.mdc", "bbb"),
+            locked_file("rust.mdc", "ccc"),
+        ];
+        let drift = find_drift(&files, |source| match source {
+            "frontend/react.mdc" => Some("AAA".to_string()),
+            "frontend/vue.mdc" => Some("changed".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(
+            drift,
+            [
+                Drift {
+                    source: "frontend/vue.mdc".into(),
+                    locked_sha: "bbb".into(),
+                    current_sha: Some("changed".into()),
+                },
+                Drift {
+                    source: "frontend/rust.mdc".into(),
+                    locked_sha: "ccc".into(),
+                    current_sha: None,
+                },
+            ]
+        );
+        assert!(find_drift(&files[..1], |_| Some("aaa".to_string())).is_empty());
+    }
+
+    #[test]
+    fn prune_set_is_previous_minus_current_for_the_same_repo() {
+        let mut other_repo = locked_file("go.mdc", "ggg");
+        other_repo.repo = "team-rules".to_string();
+        let mut notes = locked_file("notes.md", "nnn");
+        notes.source = "frontend/notes.md".to_string();
+        let lock = RulesLock {
+            files: vec![
+                locked_file("react.mdc", "aaa"),
+                locked_file("vue.mdc", "bbb"),
+                locked_file("svelte.mdc", "ccc"),
+                other_repo,
+                notes,
+            ],
+        };
+
+        let current = vec![
+            "frontend/react.mdc".to_string(),
+            "frontend/angular.mdc".to_string(),
+        ];
+        let stale: Vec<&str> = prune_set(&lock, "Owner", "cursor-rules", FRONTEND, &current)
+            .iter()
+            .map(|file| file.destination.as_str())
+            .collect();
+        assert_eq!(stale, ["vue.mdc", "svelte.mdc"]);
+
+        let everything: Vec<String> = lock.files.iter().map(|file| file.source.clone()).collect();
+        assert!(prune_set(&lock, "owner", "cursor-rules", FRONTEND, &everything).is_empty());
+        assert!(prune_set(
+            &RulesLock::default(),
+            "owner",
+            "cursor-rules",
+            FRONTEND,
+            &[]
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn prune_set_leaves_other_manifests_and_browser_copies_alone() {
+        let mut backend = locked_file("rust.mdc", "rrr");
+        backend.source = "backend/rust.mdc".to_string();
+        backend.manifest = Some("quick-add/backend.yaml".to_string());
+        let mut browsed = locked_file("svelte.mdc", "sss");
+        browsed.manifest = None;
+        let lock = RulesLock {
+            files: vec![locked_file("react.mdc", "aaa"), backend, browsed],
+        };
+
+        // Neither manifest lists the other's rules or the one picked in the browser
+        let stale: Vec<&str> = prune_set(&lock, "owner", "cursor-rules", FRONTEND, &[])
+            .iter()
+            .map(|file| file.destination.as_str())
+            .collect();
+        assert_eq!(stale, ["react.mdc"]);
+
+        let stale: Vec<&str> = prune_set(
+            &lock,
+            "owner",
+            "cursor-rules",
+            "quick-add/backend.yaml",
+            &["frontend/react.mdc".to_string()],
+        )
+        .iter()
+        .map(|file| file.destination.as_str())
+        .collect();
+        assert_eq!(stale, ["rust.mdc"]);
+    }
+
+    #[test]
+    fn locked_file_without_manifest_round_trips() {
+        let mut file = locked_file("react.mdc", "aaa");
+        file.manifest = None;
+        let lock = RulesLock { files: vec![file] };
+
+        let serialized = toml::to_string_pretty(&lock).unwrap();
+        assert!(!serialized.contains("manifest"), "{serialized}");
+        assert_eq!(toml::from_str::<RulesLock>(&serialized).unwrap(), lock);
+    }
+
+    #[test]
+    fn local_path_stays_inside_the_output_dir() {
+        let output_dir = Path::new("rules");
+        let mut file = locked_file("web/react.mdc", "aaa");
+        assert_eq!(
+            file.local_path(output_dir),
+            Some(output_dir.join("web/react.mdc"))
+        );
+        for destination in ["../escape.mdc", "/etc/passwd", "web/../../x.mdc"] {
+            file.destination = destination.to_string();
+            assert_eq!(file.local_path(output_dir), None, "{destination}");
+        }
+    }
+
+    #[test]
+    fn load_lockfile_missing_returns_none() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(load_lockfile(temp_dir.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn load_lockfile_malformed_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(lockfile_path(temp_dir.path()), "[[file]\nsource = ").unwrap();
+
+        let result = load_lockfile(temp_dir.path());
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("parse lockfile"));
+    }
+
+    #[test]
+    fn verify_detects_ok_modified_and_missing() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path();
+
+        fs::write(output_dir.join("react.mdc"), "react rules").unwrap();
+        fs::write(output_dir.join("vue.mdc"), "locally edited").unwrap();
+
+        let lock = RulesLock {
+            files: vec![
+                locked_file("react.mdc", &git_blob_sha(b"react rules")),
+                locked_file("vue.mdc", &git_blob_sha(b"vue rules")),
+                locked_file("rust.mdc", &git_blob_sha(b"rust rules")),
+            ],
+        };
+        fs::write(
+            lockfile_path(output_dir),
+            toml::to_string_pretty(&lock).unwrap(),
+        )
+        .unwrap();
+
+        let loaded = load_lockfile(output_dir).unwrap().unwrap();
+        let results = verify_lockfile(&loaded, output_dir).unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].status, VerifyStatus::Ok);
+        assert_eq!(results[1].status, VerifyStatus::Modified);
+        assert_eq!(results[1].actual_sha, Some(git_blob_sha(b"locally edited")));
+        assert_eq!(results[2].status, VerifyStatus::Missing);
+        assert!(results[2].actual_sha.is_none());
+    }
+
+    #[test]
+    fn verify_refuses_destinations_outside_the_output_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().join("rules");
+        fs::create_dir_all(&output_dir).unwrap();
+        let secret = temp_dir.path().join("secret.txt");
+        fs::write(&secret, "secret").unwrap();
+
+        let lock = RulesLock {
+            files: vec![
+                locked_file("../secret.txt", &git_blob_sha(b"secret")),
+                locked_file(&secret.display().to_string(), &git_blob_sha(b"secret")),
+            ],
+        };
+        let results = verify_lockfile(&lock, &output_dir).unwrap();
+
+        for result in &results {
+            assert_eq!(result.status, VerifyStatus::OutsideOutputDir);
+            assert!(result.actual_sha.is_none());
+        }
+    }
+
+    #[test]
+    fn verify_status_serializes_snake_case() {
+        assert_eq!(
+            serde_json::to_string(&VerifyStatus::Modified).unwrap(),
+            "\"modified\""
+        );
+    }
+}
@@ -1,20 +1,40 @@
 use clap::{Parser, Subcommand};
+mod auth;
 mod config;
 mod copier;
+mod diff;
+mod doctor;
+mod favorites;
 mod github;
+mod lockfile;
+mod mdc;
+mod output;
+mod recent;
+mod search;
+mod stats;
+mod telemetry;
 mod ui;
+mod update_check;
 
 use base64::Engine;
 use config::{
-    delete_config_value, load_config, resolve_github_token, update_config_value, Config,
-    KeyringStore, SecretStore,
+    delete_config_value, load_config, parse_jobs, resolve_github_token,
+    resolve_github_token_with_source, resolve_jobs, resolve_with_source, update_config_value,
+    Config, KeyringStore, SecretStore, ValueSource,
+};
+use copier::{
+    create_copy_plan, create_copy_plan_in, execute_copy_plan, render_copy_plan_json,
+    render_copy_plan_table, CopyConfig,
+};
+use github::{
+    find_manifests_in_quickadd, parse_manifest_content, render_materialized_manifest,
+    resolve_manifest_id, ManifestFormat,
 };
-use copier::{create_copy_plan, execute_copy_plan, render_copy_plan_table, CopyConfig};
-use github::{find_manifests_in_quickadd, parse_manifest_content, ManifestFormat};
 use inquire::Confirm;
 use is_terminal::IsTerminal;
+use output::{OutputFormat, Status};
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use ui::prompts::{InteractivePromptService, NonInteractivePromptService, PromptService};
 
 use anyhow::Context;
@@ -31,7 +51,7 @@ struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
-    /// GitHub owner to fetch rules from
+    /// GitHub owner to fetch rules from (also accepts `owner/repo` or a GitHub URL)
     #[arg(long, short)]
     owner: Option<String>,
 
@@ -39,7 +59,7 @@ struct Cli {
     #[arg(long, short)]
     repo: Option<String>,
 
-    /// Branch to fetch from (defaults to 'main')
+    /// Branch, tag or commit SHA to fetch from (defaults to 'main')
     #[arg(long, short)]
     branch: Option<String>,
 
@@ -47,7 +67,12 @@ struct Cli {
     #[arg(long, short)]
     token: Option<String>,
 
-    /// Output directory (defaults to './.cursor/rules')
+    /// Read the GitHub token from a file (used when --token is not given)
+    #[arg(long, value_name = "PATH")]
+    token_file: Option<PathBuf>,
+
+    /// Output directory (defaults to `.cursor/rules` in the nearest directory with a
+    /// `.cursor` folder or the git root, else './.cursor/rules')
     #[arg(long)]
     out: Option<String>,
 
@@ -67,32 +92,280 @@ struct Cli {
     #[arg(long)]
     force: bool,
 
-    /// Output in JSON format
-    #[arg(long)]
+    /// Skip files that already exist in the output directory
+    #[arg(long, conflicts_with = "force")]
+    skip_existing: bool,
+
+    /// Rename copied files with a numbered suffix when the destination already exists
+    #[arg(long, conflicts_with_all = ["force", "skip_existing"])]
+    rename: bool,
+
+    /// Output format for command results
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    format: Option<OutputFormat>,
+
+    /// Deprecated alias for `--format json`
+    #[arg(long, conflicts_with = "format")]
     json: bool,
 
+    /// Hide progress bars and informational messages; only errors and the final summary are shown
+    #[arg(long, short)]
+    quiet: bool,
+
     /// Show hidden files and directories (those starting with dot)
     #[arg(long)]
     all: bool,
+
+    /// Fetch file content via the contents API raw media type instead of download URLs
+    #[arg(long)]
+    raw_content: bool,
+
+    /// Skip checking downloaded files against the blob SHA in the repository tree
+    #[arg(long)]
+    no_verify: bool,
+
+    /// Number of files to download concurrently (defaults to 4)
+    #[arg(long, value_parser = parse_jobs)]
+    jobs: Option<usize>,
+
+    /// Seconds to wait on a GitHub connection or read before giving up
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = github::client::DEFAULT_TIMEOUT_SECS,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    timeout: u64,
+
+    /// Hours a cached repository tree stays fresh; 0 always refetches (defaults to 24)
+    #[arg(long, value_name = "HOURS")]
+    cache_ttl: Option<u64>,
+
+    /// Repository directory holding quick-add manifests (defaults to the `manifest_dir`
+    /// config key, then 'quick-add')
+    #[arg(long, value_name = "DIR")]
+    manifest_dir: Option<String>,
+
+    /// Only copy rules changed after this time: a date (2024-05-01), an RFC 3339
+    /// timestamp, or an age such as 7d, 12h or 2w
+    #[arg(
+        long,
+        value_name = "WHEN",
+        value_parser = github::history::parse_since,
+        conflicts_with = "offline"
+    )]
+    since: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Only show entries up to N levels deep in `list` and the browser (1 = top level only)
+    #[arg(long, value_name = "N", value_parser = parse_depth)]
+    depth: Option<usize>,
+
+    /// Never read or write the on-disk cache; everything is fetched fresh for this run
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Leave files alone when their content already matches the repository version,
+    /// instead of rewriting them
+    #[arg(long)]
+    skip_unchanged: bool,
+
+    /// Before overwriting a file, move it aside to `<name>.bak` in the same directory
+    #[arg(long)]
+    backup: bool,
+
+    /// With quick-add, delete rules an earlier copy from this repository recorded in
+    /// rules.lock that the manifest no longer lists (asks first unless --force)
+    #[arg(long)]
+    prune: bool,
+
+    /// Refuse to copy files larger than this many bytes
+    #[arg(long, value_name = "BYTES", default_value_t = copier::DEFAULT_MAX_FILE_SIZE)]
+    max_file_size: u64,
+
+    /// Check the YAML front matter of .mdc rules before writing them and warn about problems
+    #[arg(long)]
+    validate_mdc: bool,
+
+    /// With --validate-mdc, refuse to copy rules whose front matter has problems
+    #[arg(long, requires = "validate_mdc")]
+    strict: bool,
+
+    /// Make no network calls; serve the repository tree and rules from the cache only
+    #[arg(long, conflicts_with_all = ["refresh", "no_cache"])]
+    offline: bool,
+
+    /// Colour theme for the interactive browser (defaults to the `theme` config key,
+    /// or no-color when NO_COLOR is set)
+    #[arg(long, value_enum)]
+    theme: Option<ui::theme::Theme>,
+
+    /// Check for a newer release now, even if the `update_check` config key is off
+    #[arg(long, conflicts_with = "offline")]
+    version_check: bool,
+
+    /// Number of repositories `list --repos` fetches at once
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 4,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    parallel_repos: u64,
+}
+
+impl Cli {
+    /// Output format from `--format`, or JSON for the deprecated `--json`
+    fn output_format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else {
+            self.format.unwrap_or_default()
+        }
+    }
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Interactive browser (default)
-    Browse,
-    /// Apply a manifest (ID = filename or friendly slug)
-    QuickAdd { id: String },
+    Browse {
+        /// Use a line-based, screen-reader-friendly browser instead of the full-screen UI
+        #[arg(long)]
+        plain: bool,
+    },
+    /// Apply a manifest (ID = filename, friendly slug, or an http(s) URL to a manifest
+    /// outside the repository)
+    QuickAdd {
+        id: String,
+        /// Write the resolved manifest to this path as a .txt manifest
+        #[arg(long)]
+        manifest_out: Option<PathBuf>,
+        /// Sort entries and strip duplicates/comments in the written manifest
+        #[arg(long, requires = "manifest_out")]
+        canonical: bool,
+    },
     /// Print repo tree in JSON/YAML
-    List,
+    List {
+        /// Show each entry's kind, size and blob SHA, one full path per line
+        #[arg(long, short)]
+        long: bool,
+        /// Also list these repositories (comma-separated), each under its own heading in
+        /// owner/repo order; the branch defaults to 'main'
+        #[arg(
+            long,
+            value_name = "OWNER/REPO[@BRANCH]",
+            value_delimiter = ',',
+            value_parser = parse_repo_spec
+        )]
+        repos: Vec<github::RepoLocator>,
+    },
+    /// Summarize the repo's directories, rule files and manifests
+    Stats,
+    /// Find rules whose path contains QUERY, or with --content whose text does
+    Search {
+        query: String,
+        /// Also search the text of every rule (downloaded once, then cached)
+        #[arg(long)]
+        content: bool,
+    },
+    /// Show how local rules differ from the repo version (ID = manifest, or a rule path)
+    Diff { target: String },
+    /// Print raw download URLs for a manifest's rules or a single rule, copying nothing
+    CopyUrl { target: String },
+    /// Write a starter config, asking for each value unless it is given as a flag
+    Init {
+        /// Default GitHub owner
+        #[arg(long)]
+        owner: Option<String>,
+        /// Default rules repository
+        #[arg(long)]
+        repo: Option<String>,
+        /// Default output directory
+        #[arg(long)]
+        out_dir: Option<String>,
+        /// Store the token given with --token, --token-file or GITHUB_TOKEN in the keyring
+        #[arg(long)]
+        store_token: bool,
+        /// Never prompt; only write the values given as flags
+        #[arg(long)]
+        no_input: bool,
+        /// Replace an existing config file
+        #[arg(long)]
+        force: bool,
+    },
     /// Show or modify saved config
     Config {
         #[command(subcommand)]
         action: Option<ConfigAction>,
     },
-    /// Manage offline cache (list|clear)
-    Cache { action: Option<String> },
+    /// Manage offline cache (list|clear|prune|verify)
+    Cache {
+        action: Option<String>,
+        /// Size limit in megabytes for `cache prune`
+        #[arg(long, value_name = "MB", default_value_t = DEFAULT_CACHE_MAX_SIZE_MB)]
+        max_size: u64,
+    },
     /// Generate shell completions
     Completions { shell: String },
+    /// Check copied rules against the checksums recorded in rules.lock
+    Verify,
+    /// Restore the exact rule versions recorded in rules.lock
+    Sync {
+        /// Fail without copying anything if a locked rule has changed in the repository
+        #[arg(long)]
+        frozen: bool,
+    },
+    /// Inspect quick-add manifests
+    Manifest {
+        #[command(subcommand)]
+        action: ManifestAction,
+    },
+    /// Log in to GitHub from the browser or remove the stored token
+    Auth {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+    /// Check the config, keyring, owner, token and GitHub connection
+    Doctor,
+    /// List recently used repositories, or browse entry INDEX of that list
+    Recent { index: Option<usize> },
+    /// Save quick-add manifests under a short name and apply them from any directory
+    Favorite {
+        #[command(subcommand)]
+        action: FavoriteAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum FavoriteAction {
+    /// Save a manifest given as OWNER/REPO[@BRANCH]:MANIFEST
+    Add {
+        spec: String,
+        /// Name to apply it by (defaults to the manifest's file name)
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// List saved favorites
+    List,
+    /// Forget a favorite
+    Remove { name: String },
+    /// Run quick-add with a saved manifest
+    Apply { name: String },
+}
+
+#[derive(Subcommand)]
+enum AuthAction {
+    /// Authorize in the browser with GitHub's device flow and store the token
+    Login,
+    /// Remove the token stored in the keyring
+    Logout,
+    /// Check whether the resolved token works and show the remaining rate limit
+    Status,
+}
+
+#[derive(Subcommand)]
+enum ManifestAction {
+    /// Check a manifest (or `all`) for missing and invalid entries without copying
+    Validate { id: String },
 }
 
 #[derive(Subcommand)]
@@ -103,12 +376,32 @@ enum ConfigAction {
     Delete { key: String },
     /// Show current configuration
     Show,
+    /// Show the effective settings and where each one comes from
+    List,
+    /// Open the config file in $VISUAL or $EDITOR, creating it from a template if needed
+    Edit,
+    /// Print where the config file and the cache live
+    Path,
 }
 
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
 
+    // Look for a newer release while the command runs and mention it once it is done
+    let update_check_enabled = load_config()
+        .ok()
+        .and_then(|config| config.update_check)
+        .unwrap_or(false);
+    let update_check = update_check::start(update_check_enabled, cli.version_check, cli.offline);
+
+    let status = run(cli).await;
+    update_check::report(update_check).await;
+    std::process::exit(status);
+}
+
+/// Run the command selected on the command line, returning the process exit status
+async fn run(mut cli: Cli) -> i32 {
     if cli.verbose {
         // Initialise tracing subscriber in verbose mode
         tracing_subscriber::fmt()
@@ -128,7 +421,11 @@ async fn main() {
     };
 
     let secret_store = KeyringStore;
-    let resolved_token = match resolve_github_token(cli.token.as_deref(), &secret_store) {
+    let resolved_token = match resolve_github_token(
+        cli.token.as_deref(),
+        cli.token_file.as_deref(),
+        &secret_store,
+    ) {
         Ok(token) => token,
         Err(e) => {
             if cli.verbose {
@@ -138,24 +435,160 @@ async fn main() {
         }
     };
 
-    // Apply config defaults where CLI args are not provided
-    let owner = cli.owner.clone().or(config.owner);
-    let repo = cli.repo.clone().or(config.repo);
-    let out_dir = cli.out.clone().or(config.out_dir);
-
-    match github::resolve_repo(
-        owner.clone(),
-        repo.clone(),
-        cli.branch.clone(),
+    // Build the shared client with the token, --timeout and OCTO_BASE, so every request
+    // (including file downloads) is authenticated the same way
+    github::client::configure(
         resolved_token.clone(),
-    )
-    .await
+        std::time::Duration::from_secs(cli.timeout),
+    );
+    github::client::set_offline(cli.offline);
+    if let Ok(client) = github::client::authenticated() {
+        octocrab::initialise(client);
+    }
+
+    // Apply config defaults where CLI args are not provided
+    cli.cache_ttl = cli.cache_ttl.or(config.cache_ttl_hours);
+    cli.manifest_dir = cli.manifest_dir.or(config.manifest_dir.clone());
+    let jobs = resolve_jobs(cli.jobs, &config);
+    let telemetry = telemetry::Telemetry::from_setting(config.telemetry);
+    let palette = config::resolve_theme(cli.theme, &config).palette();
+    let (mut owner, mut repo) =
+        config::resolve_owner_and_repo(cli.owner.as_deref(), cli.repo.clone(), &config);
+    let mut branch = cli.branch.clone().or(config.branch);
+    let out_dir = PathBuf::from(
+        cli.out
+            .clone()
+            .or(config.out_dir)
+            .unwrap_or_else(config::default_out_dir),
+    );
+
+    // Commands that only inspect local files don't need a resolved repository
+    if let Some(Commands::Verify) = cli.command {
+        if let Err(e) = handle_verify_command(&cli, &out_dir) {
+            return exit_status("Verify", &e);
+        }
+        return 0;
+    }
+    if let Some(Commands::Sync { frozen }) = cli.command {
+        if let Err(e) = handle_sync_command(&cli, &out_dir, frozen).await {
+            return exit_status("Sync", &e);
+        }
+        return 0;
+    }
+    if let Some(Commands::Init {
+        ref owner,
+        ref repo,
+        ref out_dir,
+        store_token,
+        no_input,
+        force,
+    }) = cli.command
     {
+        let token = if store_token {
+            match resolved_token.clone() {
+                Some(token) => Some(token),
+                None => {
+                    eprintln!(
+                        "Init error: --store-token needs a token from --token, --token-file or GITHUB_TOKEN"
+                    );
+                    return 1;
+                }
+            }
+        } else {
+            None
+        };
+        let options = config::InitOptions {
+            owner: owner.clone(),
+            repo: repo.clone(),
+            out_dir: out_dir.clone(),
+            token,
+        };
+        if let Err(e) = handle_init_command(options, no_input, force) {
+            return exit_status("Init", &e);
+        }
+        return 0;
+    }
+    if let Some(Commands::Config { ref action }) = cli.command {
+        if let Err(e) = handle_config_command(action.as_ref(), &cli).await {
+            return exit_status("Config", &e);
+        }
+        return 0;
+    }
+    if let Some(Commands::Auth { ref action }) = cli.command {
+        if let Err(e) =
+            handle_auth_command(action, resolved_token.as_deref(), cli.output_format()).await
+        {
+            return exit_status("Auth", &e);
+        }
+        return 0;
+    }
+
+    if let Some(Commands::Doctor) = cli.command {
+        match handle_doctor_command(&cli, owner.as_deref()).await {
+            Ok(true) => return 0,
+            Ok(false) => return 1,
+            Err(e) => {
+                return exit_status("Doctor", &e);
+            }
+        }
+    }
+
+    if let Some(Commands::Recent { index }) = cli.command {
+        match handle_recent_command(index, cli.output_format()) {
+            Ok(Some(entry)) => {
+                owner = Some(entry.owner);
+                repo = Some(entry.repo);
+                branch = Some(entry.branch);
+            }
+            Ok(None) => return 0,
+            Err(e) => {
+                return exit_status("Recent", &e);
+            }
+        }
+    }
+
+    if let Some(Commands::Favorite { ref action }) = cli.command {
+        match handle_favorite_command(action, cli.output_format()) {
+            Ok(Some(favorite)) => {
+                owner = Some(favorite.owner);
+                repo = Some(favorite.repo);
+                // An explicit --branch still wins over the saved one
+                branch = cli.branch.clone().or(favorite.branch);
+                cli.command = Some(Commands::QuickAdd {
+                    id: favorite.manifest,
+                    manifest_out: None,
+                    canonical: false,
+                });
+            }
+            Ok(None) => return 0,
+            Err(e) => {
+                return exit_status("Favorite", &e);
+            }
+        }
+    }
+
+    match github::resolve_repo(owner.clone(), repo.clone(), branch, resolved_token.clone()).await {
         Ok(locator) => {
-            println!(
-                "Resolved repo: {}/{}@{}",
-                locator.owner, locator.repo, locator.branch
-            );
+            recent::record(&locator);
+
+            // Keep stdout clean for machine-readable listings
+            if !cli.output_format().is_structured()
+                && !cli.quiet
+                && !matches!(
+                    cli.command,
+                    Some(
+                        Commands::List { .. }
+                            | Commands::Diff { .. }
+                            | Commands::Search { .. }
+                            | Commands::CopyUrl { .. }
+                    )
+                )
+            {
+                println!(
+                    "Resolved repo: {}/{}@{}",
+                    locator.owner, locator.repo, locator.branch
+                );
+            }
 
             // If no explicit subcommand or `browse`, launch the interactive browser UI.
             use tokio::sync::mpsc;
@@ -163,25 +596,73 @@ async fn main() {
             let (tx, mut rx) = mpsc::unbounded_channel();
 
             match cli.command {
-                None | Some(Commands::Browse) => {
+                None | Some(Commands::Browse { .. }) | Some(Commands::Recent { .. }) => {
+                    let plain = matches!(cli.command, Some(Commands::Browse { plain: true }));
+                    // The plain browser waits for this before reading its next command
+                    let copied = std::sync::Arc::new(tokio::sync::Notify::new());
+
                     // Run UI in background task and handle messages in main thread
                     let mut ui_task = tokio::spawn({
                         let locator = locator.clone();
                         let tx = tx.clone();
-                        let all = cli.all;
-                        async move { ui::run(&locator, tx, all).await }
+                        let copied = copied.clone();
+                        let options = ui::BrowserOptions {
+                            show_hidden: cli.all,
+                            confirm_manifests: !cli.force,
+                            palette,
+                            use_cache: !cli.no_cache,
+                            refresh: cli.refresh,
+                        };
+                        // Fall back to an in-memory tree if the cache directory is unusable
+                        let tree = repo_tree_for(&cli, false)
+                            .unwrap_or_default()
+                            .with_manifest_dir(manifest_dir(&cli))
+                            .with_depth(cli.depth);
+                        async move {
+                            if plain {
+                                ui::plain::run(
+                                    &locator,
+                                    tree,
+                                    tx,
+                                    copied,
+                                    options.show_hidden,
+                                    options.use_cache,
+                                    options.refresh,
+                                )
+                                .await
+                            } else {
+                                ui::run(&locator, tree, tx, options).await
+                            }
+                        }
                     });
 
+                    // Copies follow the repository the browser switched to
+                    let mut locator = locator.clone();
+
                     // Handle messages from UI
                     loop {
                         tokio::select! {
+                            // Drain pending copy requests before noticing the UI has exited
+                            biased;
+
                             // Handle UI messages
                             msg = rx.recv() => {
                                 match msg {
                                     Some(ui::AppMessage::CopyRequest { path }) => {
-                                        if let Err(e) = handle_browser_selection(&locator, &path, &cli, out_dir.as_deref()).await {
+                                        if let Err(e) = handle_browser_selection(&locator, &path, &cli, &out_dir, jobs, &telemetry).await {
+                                            eprintln!("Copy error: {e}");
+                                        }
+                                        copied.notify_one();
+                                    }
+                                    Some(ui::AppMessage::CopyBatch { paths }) => {
+                                        if let Err(e) = handle_batch_selection(&locator, &paths, &cli, &out_dir, jobs).await {
                                             eprintln!("Copy error: {e}");
                                         }
+                                        copied.notify_one();
+                                    }
+                                    Some(ui::AppMessage::RepoChanged { locator: switched }) => {
+                                        recent::record(&switched);
+                                        locator = switched;
                                     }
                                     None => {
                                         // Channel closed, UI task finished
@@ -195,11 +676,11 @@ async fn main() {
                                     Ok(Ok(())) => {},
                                     Ok(Err(e)) => {
                                         eprintln!("UI error: {e}");
-                                        std::process::exit(1);
+                                        return 1;
                                     }
                                     Err(e) => {
                                         eprintln!("UI task error: {e}");
-                                        std::process::exit(1);
+                                        return 1;
                                     }
                                 }
                                 break;
@@ -207,24 +688,69 @@ async fn main() {
                         }
                     }
                 }
-                Some(Commands::QuickAdd { ref id }) => {
-                    if let Err(e) = handle_quick_add(&locator, id, &cli, out_dir.as_deref()).await {
-                        eprintln!("Quick-add error: {e}");
-                        std::process::exit(1);
+                Some(Commands::QuickAdd {
+                    ref id,
+                    ref manifest_out,
+                    canonical,
+                }) => {
+                    if let Err(e) = handle_quick_add(
+                        &locator,
+                        id,
+                        &cli,
+                        &out_dir,
+                        jobs,
+                        &telemetry,
+                        manifest_out.as_deref().map(|path| (path, canonical)),
+                    )
+                    .await
+                    {
+                        return exit_status("Quick-add", &e);
+                    }
+                }
+                Some(Commands::List { long, ref repos }) => {
+                    if let Err(e) = handle_list_command(&locator, repos, long, &cli).await {
+                        return exit_status("List", &e);
+                    }
+                }
+                Some(Commands::Diff { ref target }) => {
+                    if let Err(e) = handle_diff_command(&locator, target, &cli, &out_dir).await {
+                        return exit_status("Diff", &e);
+                    }
+                }
+                Some(Commands::CopyUrl { ref target }) => {
+                    if let Err(e) = handle_copy_url_command(&locator, target, &cli).await {
+                        return exit_status("Copy URL", &e);
+                    }
+                }
+                Some(Commands::Stats) => {
+                    if let Err(e) = handle_stats_command(&locator, &cli).await {
+                        return exit_status("Stats", &e);
                     }
                 }
-                Some(Commands::Config { ref action }) => {
-                    if let Err(e) = handle_config_command(action.as_ref()).await {
-                        eprintln!("Config error: {e}");
-                        std::process::exit(1);
+                Some(Commands::Search { ref query, content }) => {
+                    if let Err(e) = handle_search_command(&locator, query, content, &cli).await {
+                        return exit_status("Search", &e);
                     }
                 }
-                Some(Commands::Cache { ref action }) => {
-                    if let Err(e) = handle_cache_command(action.as_deref()).await {
-                        eprintln!("Cache error: {e}");
-                        std::process::exit(1);
+                Some(Commands::Cache {
+                    ref action,
+                    max_size,
+                }) => {
+                    if let Err(e) =
+                        handle_cache_command(action.as_deref(), max_size, cli.output_format()).await
+                    {
+                        return exit_status("Cache", &e);
                     }
                 }
+                Some(Commands::Manifest {
+                    action: ManifestAction::Validate { ref id },
+                }) => match handle_manifest_validate(&locator, id, &cli).await {
+                    Ok(true) => {}
+                    Ok(false) => return 2,
+                    Err(e) => {
+                        return exit_status("Manifest", &e);
+                    }
+                },
                 // Other subcommands will be implemented in future FRs.
                 _ => {
                     eprintln!("Subcommand not yet implemented");
@@ -233,59 +759,394 @@ async fn main() {
         }
         Err(e) => {
             eprintln!("Error resolving repository: {e}");
-            std::process::exit(1);
+            return 1;
+        }
+    }
+    0
+}
+
+/// Ends a command with this exit status once its own output is printed. Handlers return
+/// it as an error so `main` still gets to report the update check before exiting.
+#[derive(Debug)]
+struct Exit(i32);
+
+impl std::fmt::Display for Exit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "command failed with exit status {}", self.0)
+    }
+}
+
+impl std::error::Error for Exit {}
+
+/// Exit status for a failed `command`: the one an [`Exit`] carries, or 1 after printing
+/// the error
+fn exit_status(command: &str, e: &anyhow::Error) -> i32 {
+    if let Some(Exit(status)) = e.downcast_ref() {
+        return *status;
+    }
+    eprintln!("{command} error: {e}");
+    1
+}
+
+/// List recent repositories, or return entry `index` of the list to browse it
+fn handle_recent_command(
+    index: Option<usize>,
+    format: OutputFormat,
+) -> anyhow::Result<Option<recent::RecentRepo>> {
+    let recent = recent::RecentRepos::load()?;
+    let Some(index) = index else {
+        if !output::print_structured(format, recent.entries())? {
+            println!("{}", recent::render(recent.entries()));
+        }
+        return Ok(None);
+    };
+    match recent.get(index) {
+        Some(entry) => Ok(Some(entry.clone())),
+        None => {
+            anyhow::bail!("No recent repository #{index}; run `cursor-rules recent` to list them")
         }
     }
 }
 
+/// Manage saved favorites, or return the one `favorite apply` should run
+fn handle_favorite_command(
+    action: &FavoriteAction,
+    format: OutputFormat,
+) -> anyhow::Result<Option<favorites::Favorite>> {
+    let mut favorites = favorites::Favorites::load()?;
+    match action {
+        FavoriteAction::Add { spec, name } => {
+            let favorite = favorites::Favorite::parse(spec, name.as_deref())?;
+            let name = favorite.name.clone();
+            let replaced = favorites.add(favorite);
+            favorites.save()?;
+            match replaced {
+                Some(old) => println!("Updated favorite {name:?} (was {old})"),
+                None => println!("Saved favorite {name:?}"),
+            }
+        }
+        FavoriteAction::List => {
+            if !output::print_structured(format, favorites.entries())? {
+                println!("{}", favorites::render(favorites.entries()));
+            }
+        }
+        FavoriteAction::Remove { name } => {
+            if !favorites.remove(name) {
+                anyhow::bail!("No favorite named {name:?}");
+            }
+            favorites.save()?;
+            println!("Removed favorite {name:?}");
+        }
+        FavoriteAction::Apply { name } => {
+            return match favorites.get(name) {
+                Some(favorite) => Ok(Some(favorite.clone())),
+                None => anyhow::bail!(
+                    "No favorite named {name:?}; run `cursor-rules favorite list` to see them"
+                ),
+            };
+        }
+    }
+    Ok(None)
+}
+
+/// Handle auth subcommands
+async fn handle_auth_command(
+    action: &AuthAction,
+    token: Option<&str>,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let secret_store = KeyringStore;
+
+    match action {
+        AuthAction::Login => {
+            if !io::stdin().is_terminal() {
+                anyhow::bail!(
+                    "`auth login` needs an interactive terminal; use `cursor-rules config set token <TOKEN>` instead"
+                );
+            }
+
+            let client_id = auth::client_id().with_context(|| {
+                format!(
+                    "No OAuth client ID configured; set {} or use `cursor-rules config set token <TOKEN>`",
+                    auth::CLIENT_ID_ENV
+                )
+            })?;
+
+            let flow = auth::DeviceFlow::new(auth::GITHUB_LOGIN_BASE, &client_id);
+            let code = flow.request_code(auth::LOGIN_SCOPE).await?;
+
+            println!(
+                "Open {} and enter the code: {}",
+                code.verification_uri, code.user_code
+            );
+            println!("Waiting for authorization...");
+
+            let token = flow.wait_for_token(&code).await?;
+            secret_store
+                .set_token(&token)
+                .map_err(anyhow::Error::from)?;
+            println!("Logged in. GitHub token stored securely in keyring.");
+        }
+        AuthAction::Logout => {
+            secret_store.delete_token().map_err(anyhow::Error::from)?;
+            println!("GitHub token removed from keyring.");
+        }
+        AuthAction::Status => {
+            let status = auth::auth_status(token).await?;
+
+            if output::print_structured(format, &status)? {
+                return Ok(());
+            }
+
+            match (&status.login, token) {
+                (Some(login), _) => println!("Authenticated as {login}"),
+                (None, Some(_)) => println!("Not authenticated: GitHub rejected the token"),
+                (None, None) => println!("Not authenticated: no token configured"),
+            }
+            if status.authenticated {
+                let scopes = if status.scopes.is_empty() {
+                    "none reported".to_string()
+                } else {
+                    status.scopes.join(", ")
+                };
+                println!("Scopes: {scopes}");
+            }
+            match status.rate_limit_remaining {
+                Some(remaining) if status.authenticated => {
+                    println!("Rate limit remaining: {remaining}")
+                }
+                Some(remaining) => println!("Rate limit remaining: {remaining} (anonymous)"),
+                None => println!("Rate limit remaining: unknown"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run every `doctor` check and print the results. Returns `false` when a critical check
+/// failed.
+async fn handle_doctor_command(cli: &Cli, owner: Option<&str>) -> anyhow::Result<bool> {
+    let secret_store = KeyringStore;
+    // The keyring is consulted last, so an error there means no other source had a token;
+    // the keyring check reports the error itself
+    let token = resolve_github_token(
+        cli.token.as_deref(),
+        cli.token_file.as_deref(),
+        &secret_store,
+    )
+    .ok()
+    .flatten();
+
+    let checks = vec![
+        doctor::check_config(&config::config_sources().map_err(anyhow::Error::from)?),
+        doctor::check_keyring(&secret_store),
+        doctor::check_owner(owner, github::local_owner()),
+        doctor::check_token(token.as_deref()).await,
+        doctor::check_github(token.as_deref()).await,
+    ];
+
+    if !output::print_structured(cli.output_format(), &checks)? {
+        println!("{}", doctor::render(&checks));
+    }
+    Ok(!doctor::has_critical_failure(&checks))
+}
+
+/// Write a starter config from `options`, prompting for the rest on a terminal unless
+/// `no_input` is set
+fn handle_init_command(
+    options: config::InitOptions,
+    no_input: bool,
+    force: bool,
+) -> anyhow::Result<()> {
+    use is_terminal::IsTerminal;
+
+    // Refuse before asking anything
+    config::check_init_allowed(force)?;
+
+    let interactive = !no_input && std::io::stdin().is_terminal();
+    let options = if interactive {
+        config::prompt_init_options(options)?
+    } else {
+        options
+    };
+    let path = config::write_initial_config(&options, force, &KeyringStore)?;
+    println!(
+        "{}",
+        output::status(output::Status::Ok, format!("Wrote {}", path.display()))
+    );
+    if options.token.is_some() {
+        println!(
+            "{}",
+            output::status(output::Status::Ok, "Token stored in the OS keyring")
+        );
+    }
+    Ok(())
+}
+
 /// Handle config subcommands
-async fn handle_config_command(action: Option<&ConfigAction>) -> anyhow::Result<()> {
+async fn handle_config_command(action: Option<&ConfigAction>, cli: &Cli) -> anyhow::Result<()> {
     let secret_store = KeyringStore;
 
     match action {
+        Some(ConfigAction::Path) => {
+            let paths = config::storage_paths()?;
+            if output::print_structured(cli.output_format(), &paths)? {
+                return Ok(());
+            }
+            println!("{:<7} {}", "config:", paths.config.display());
+            println!("{:<7} {}", "cache:", paths.cache.display());
+        }
+        Some(ConfigAction::Edit) => {
+            let path = config::edit_config()?;
+            println!("Saved {}", path.display());
+        }
+        Some(ConfigAction::List) => {
+            let config = load_config().map_err(anyhow::Error::from)?;
+            let token = resolve_github_token_with_source(
+                cli.token.as_deref(),
+                cli.token_file.as_deref(),
+                &secret_store,
+            )
+            .map_err(anyhow::Error::from)?;
+
+            let owner = resolve_with_source(cli.owner.clone(), None, config.owner);
+            let repo = resolve_with_source(cli.repo.clone(), None, config.repo)
+                .unwrap_or_else(|| ("cursor-rules".to_string(), ValueSource::Default));
+            let branch = resolve_with_source(cli.branch.clone(), None, config.branch)
+                .unwrap_or_else(|| ("main".to_string(), ValueSource::Default));
+            let out_dir = resolve_with_source(cli.out.clone(), None, config.out_dir)
+                .unwrap_or_else(|| (config::default_out_dir(), ValueSource::Default));
+
+            let sourced = |value: &str, source: &ValueSource| serde_json::json!({ "value": value, "source": source.to_string() });
+            let report = serde_json::json!({
+                "owner": owner.as_ref().map(|(value, source)| sourced(value, source)),
+                "repo": sourced(&repo.0, &repo.1),
+                "branch": sourced(&branch.0, &branch.1),
+                "out_dir": sourced(&out_dir.0, &out_dir.1),
+                // Never include the token itself
+                "token": token.as_ref().map(|(_, source)| serde_json::json!({ "source": source.to_string() })),
+            });
+            if output::print_structured(cli.output_format(), &report)? {
+                return Ok(());
+            }
+
+            println!("Effective configuration:");
+            println!();
+            match owner {
+                Some((owner, source)) => println!("{:<10} {owner} ({source})", "owner:"),
+                None => println!("{:<10} unset (detected from git or gh)", "owner:"),
+            }
+            println!("{:<10} {} ({})", "repo:", repo.0, repo.1);
+            println!("{:<10} {} ({})", "branch:", branch.0, branch.1);
+            println!("{:<10} {} ({})", "out_dir:", out_dir.0, out_dir.1);
+            match token {
+                // Never print the token itself
+                Some((_, source)) => println!(
+                    "{:<10} {}",
+                    "token:",
+                    output::status(Status::Ok, format!("set ({source})"))
+                ),
+                None => println!(
+                    "{:<10} {}",
+                    "token:",
+                    output::status(Status::Error, "not set")
+                ),
+            }
+        }
         None | Some(ConfigAction::Show) => {
             // Show current configuration
             let config = load_config().map_err(anyhow::Error::from)?;
-            let token = resolve_github_token(None, &secret_store).map_err(anyhow::Error::from)?;
+            let token =
+                resolve_github_token(None, None, &secret_store).map_err(anyhow::Error::from)?;
+
+            let mut report = serde_json::to_value(&config)?;
+            report["token_stored"] = serde_json::Value::Bool(token.is_some());
+            report["config_files"] =
+                serde_json::to_value(config::config_sources().map_err(anyhow::Error::from)?)?;
+            if output::print_structured(cli.output_format(), &report)? {
+                return Ok(());
+            }
 
             println!("Current configuration:");
             println!();
             println!(
-                "{:<12} {}",
+                "{:<17} {}",
                 "owner:",
                 config.owner.unwrap_or_else(|| "unset".to_string())
             );
             println!(
-                "{:<12} {}",
+                "{:<17} {}",
                 "repo:",
                 config.repo.unwrap_or_else(|| "unset".to_string())
             );
             println!(
-                "{:<12} {}",
+                "{:<17} {}",
+                "branch:",
+                config.branch.unwrap_or_else(|| "unset".to_string())
+            );
+            println!(
+                "{:<17} {}",
                 "out_dir:",
                 config.out_dir.unwrap_or_else(|| "unset".to_string())
             );
             println!(
-                "{:<12} {}",
+                "{:<17} {}",
                 "telemetry:",
                 config
                     .telemetry
                     .map_or("unset".to_string(), |t| t.to_string())
             );
             println!(
-                "{:<12} {}",
+                "{:<17} {}",
+                "jobs:",
+                config.jobs.map_or("unset".to_string(), |j| j.to_string())
+            );
+            println!(
+                "{:<17} {}",
+                "cache_ttl_hours:",
+                config
+                    .cache_ttl_hours
+                    .map_or("unset".to_string(), |h| h.to_string())
+            );
+            println!(
+                "{:<17} {}",
+                "theme:",
+                config.theme.map_or("unset".to_string(), |t| t.to_string())
+            );
+            println!(
+                "{:<17} {}",
+                "update_check:",
+                config
+                    .update_check
+                    .map_or("unset".to_string(), |u| u.to_string())
+            );
+            println!(
+                "{:<17} {}",
+                "manifest_dir:",
+                config.manifest_dir.unwrap_or_else(|| "unset".to_string())
+            );
+            println!(
+                "{:<17} {}",
                 "token:",
                 if token.is_some() {
-                    "✓ stored in keyring"
+                    output::status(Status::Ok, "stored in keyring")
                 } else {
-                    "✗ not set"
+                    output::status(Status::Error, "not set")
                 }
             );
 
-            // Show config file path
-            let config_path = config::config_file_path().map_err(anyhow::Error::from)?;
+            // Show the config files that were merged, global first
+            let sources = config::config_sources().map_err(anyhow::Error::from)?;
             println!();
-            println!("Config file: {}", config_path.display());
+            for (index, path) in sources.iter().enumerate() {
+                let label = if index == 0 {
+                    "Config file:"
+                } else {
+                    "Project config:"
+                };
+                println!("{label} {}", path.display());
+            }
         }
         Some(ConfigAction::Set { key, value }) => {
             if key == "token" {
@@ -304,10 +1165,26 @@ async fn handle_config_command(action: Option<&ConfigAction>) -> anyhow::Result<
                     println!("GitHub token stored securely in keyring.");
 
                     // Validate token by making a test API call
-                    match validate_github_token(value).await {
-                        Ok(()) => println!("✓ Token validation successful."),
+                    match config::validate_github_token_with_scopes(value).await {
+                        Ok(scopes) => {
+                            println!(
+                                "{}",
+                                output::status(Status::Ok, "Token validation successful.")
+                            );
+                            // Fine-grained tokens report no scopes, so only classic ones are checked
+                            if scopes.is_some_and(|scopes| !config::has_repo_scope(&scopes)) {
+                                eprintln!("{}", output::status_err(Status::Warning, "Warning: Token has neither the `repo` nor the `public_repo` scope."));
+                                eprintln!("Private cursor-rules repositories will not be readable with it.");
+                            }
+                        }
                         Err(e) => {
-                            eprintln!("⚠ Warning: Token validation failed: {e}");
+                            eprintln!(
+                                "{}",
+                                output::status_err(
+                                    Status::Warning,
+                                    format!("Warning: Token validation failed: {e}")
+                                )
+                            );
                             eprintln!("The token has been stored but may not be valid.");
                         }
                     }
@@ -349,8 +1226,15 @@ async fn handle_config_command(action: Option<&ConfigAction>) -> anyhow::Result<
     Ok(())
 }
 
+/// Default size limit for `cache prune`, in megabytes
+const DEFAULT_CACHE_MAX_SIZE_MB: u64 = 100;
+
 /// Handle cache subcommands
-async fn handle_cache_command(action: Option<&str>) -> anyhow::Result<()> {
+async fn handle_cache_command(
+    action: Option<&str>,
+    max_size_mb: u64,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
     use github::{FileSystemCache, PersistentCache};
 
     let cache = FileSystemCache::new()?;
@@ -360,6 +1244,20 @@ async fn handle_cache_command(action: Option<&str>) -> anyhow::Result<()> {
             // List all cached repositories
             let repos = cache.list_cached_repos()?;
 
+            let entries: Vec<_> = repos
+                .iter()
+                .map(|(owner, repo, fetched_at)| {
+                    serde_json::json!({
+                        "owner": owner,
+                        "repo": repo,
+                        "fetched_at": fetched_at,
+                    })
+                })
+                .collect();
+            if output::print_structured(format, &entries)? {
+                return Ok(());
+            }
+
             if repos.is_empty() {
                 println!("No cached repositories found.");
                 return Ok(());
@@ -410,90 +1308,897 @@ async fn handle_cache_command(action: Option<&str>) -> anyhow::Result<()> {
                 println!("Cache not cleared.");
             }
         }
+        Some("prune") => {
+            let report = cache.prune(max_size_mb.saturating_mul(1024 * 1024))?;
+
+            if output::print_structured(format, &report)? {
+                // Structured report printed
+            } else if report.evicted.is_empty() {
+                println!("Cache is within {max_size_mb} MB; nothing to prune.");
+            } else {
+                for entry in &report.evicted {
+                    println!("  Evicted {} ({} bytes)", entry.name, entry.bytes);
+                }
+                println!(
+                    "Pruned {} repositories, freed {} bytes ({} bytes remaining).",
+                    report.evicted.len(),
+                    report.bytes_freed,
+                    report.bytes_remaining
+                );
+            }
+        }
+        Some("verify") => {
+            let report = cache.verify()?;
+
+            if output::print_structured(format, &report)? {
+                // Structured report printed
+            } else {
+                for problem in &report.problems {
+                    eprintln!(
+                        "{}",
+                        output::status_err(
+                            output::Status::Error,
+                            format!(
+                                "{}: {} ({})",
+                                problem.repo,
+                                problem.path.display(),
+                                problem.detail
+                            ),
+                        )
+                    );
+                }
+                let corrupt_trees = report
+                    .problems
+                    .iter()
+                    .filter(|problem| problem.kind == github::cache::CacheProblemKind::CorruptTree)
+                    .count();
+                println!(
+                    "Checked {} trees and {} blobs ({} blobs have no recorded SHA); {} corrupt.",
+                    report.trees_ok + corrupt_trees,
+                    report.blobs_ok + report.problems.len() - corrupt_trees,
+                    report.blobs_unchecked,
+                    report.problems.len()
+                );
+
+                if !report.problems.is_empty()
+                    && is_terminal::IsTerminal::is_terminal(&std::io::stdin())
+                    && inquire::Confirm::new("Remove the corrupt files from the cache?")
+                        .with_default(true)
+                        .prompt()
+                        .unwrap_or(false)
+                {
+                    for problem in &report.problems {
+                        std::fs::remove_file(&problem.path).with_context(|| {
+                            format!("Failed to remove {}", problem.path.display())
+                        })?;
+                    }
+                    println!("Removed {} corrupt files.", report.problems.len());
+                }
+            }
+
+            if !report.problems.is_empty() {
+                return Err(Exit(1).into());
+            }
+        }
         Some(unknown) => {
             eprintln!("Unknown cache action: {unknown}");
-            eprintln!("Available actions: list, clear");
-            std::process::exit(1);
+            eprintln!("Available actions: list, clear, prune, verify");
+            return Err(Exit(1).into());
         }
     }
 
     Ok(())
 }
 
-/// Validate a GitHub token by making a test API call
-async fn validate_github_token(token: &str) -> anyhow::Result<()> {
-    let octocrab = octocrab::Octocrab::builder()
-        .personal_token(token.to_string())
-        .build()?;
+/// Print a unified diff from each local rule to its repository version. `target` is a
+/// quick-add manifest, whose entries are all compared, or the path of a single rule.
+async fn handle_diff_command(
+    locator: &github::RepoLocator,
+    target: &str,
+    cli: &Cli,
+    out_dir: &Path,
+) -> anyhow::Result<()> {
+    let mut repo_tree = repo_tree_for(cli, true)?;
+    let (entries, target_dir) =
+        resolve_target_entries(locator, target, &mut repo_tree, cli).await?;
+
+    // Compare against the files a copy would write
+    let copy_config = CopyConfig {
+        output_dir: out_dir.to_path_buf(),
+        overwrite_mode: copier::OverwriteMode::Force,
+        max_concurrency: 1,
+        raw_content: cli.raw_content,
+        quiet: cli.quiet,
+        verify: !cli.no_verify,
+        use_cache: !cli.no_cache,
+        skip_unchanged: cli.skip_unchanged,
+        backup: cli.backup,
+        max_file_size: cli.max_file_size,
+        validate_mdc: cli.validate_mdc,
+        strict_mdc: cli.strict,
+        manifest: None,
+    };
+    let mut copy_plan = create_copy_plan_in(&entries, &copy_config, target_dir.as_deref())?;
+    copier::attach_blob_shas(&mut copy_plan, &repo_tree);
+
+    let octocrab = std::sync::Arc::new(github::client::authenticated()?);
+    let structured = cli.output_format().is_structured();
+    let mut diffs = Vec::new();
+    for plan in &copy_plan {
+        let remote = copier::fetch_file_content(
+            &octocrab,
+            locator,
+            &plan.source_path,
+            plan.blob_sha.as_deref(),
+            cli.raw_content,
+            !cli.no_cache,
+        )
+        .await?;
+        let remote = String::from_utf8_lossy(&remote);
+        let local = std::fs::read(&plan.destination_path).ok();
+        let local_text = local
+            .as_deref()
+            .map(String::from_utf8_lossy)
+            .unwrap_or_default();
+
+        let destination = plan.destination_path.display().to_string();
+        let (added, removed) = diff::summarize(&local_text, &remote);
+        if !structured {
+            let local_label = if local.is_some() {
+                format!("a/{destination}")
+            } else {
+                "/dev/null".to_string()
+            };
+            let remote_label = format!("b/{}", plan.source_path);
+            print!(
+                "{}",
+                diff::unified(&local_text, &remote, &local_label, &remote_label)
+            );
+        }
+        diffs.push(diff::FileDiff {
+            source: plan.source_path.clone(),
+            destination,
+            exists_locally: local.is_some(),
+            added,
+            removed,
+        });
+    }
 
-    // Make a simple API call to validate the token
-    let _user = octocrab.current().user().await?;
+    if !output::print_structured(cli.output_format(), &diffs)?
+        && diffs
+            .iter()
+            .all(|diff| diff.added == 0 && diff.removed == 0)
+    {
+        println!("No differences");
+    }
     Ok(())
 }
 
-/// Handle the quick-add command
-async fn handle_quick_add(
+/// Print the raw download URL of every rule in manifest `target`, or of the single rule
+/// at path `target`, without writing anything locally
+async fn handle_copy_url_command(
     locator: &github::RepoLocator,
-    manifest_id: &str,
+    target: &str,
     cli: &Cli,
-    out_dir: Option<&str>,
 ) -> anyhow::Result<()> {
-    // Create repo tree with persistent cache and find available manifests in the quick-add directory
-    let mut repo_tree = if cli.refresh {
-        // Use regular tree without persistent cache for force refresh
-        github::RepoTree::new()
+    let mut repo_tree = repo_tree_for(cli, true)?;
+    let (entries, _) = resolve_target_entries(locator, target, &mut repo_tree, cli).await?;
+
+    let urls: Vec<_> = entries
+        .iter()
+        .map(|path| serde_json::json!({ "path": path, "url": locator.raw_url(path) }))
+        .collect();
+    if !output::print_structured(cli.output_format(), &urls)? {
+        for path in &entries {
+            println!("{}", locator.raw_url(path));
+        }
+    }
+    Ok(())
+}
+
+/// The rules `target` names: every entry of the quick-add manifest with that id, along
+/// with the manifest's target directory, or else the single rule file at that path
+async fn resolve_target_entries(
+    locator: &github::RepoLocator,
+    target: &str,
+    repo_tree: &mut github::RepoTree,
+    cli: &Cli,
+) -> anyhow::Result<(Vec<String>, Option<String>)> {
+    let available_manifests =
+        find_manifests_in_quickadd(repo_tree, locator, cli.refresh, cli.all).await?;
+
+    match resolve_manifest_id(target, &available_manifests) {
+        Some((format, path)) => {
+            let content = download_manifest_content(locator, &path, !cli.no_cache).await?;
+            let manifest = parse_manifest_content(
+                &content,
+                format,
+                target,
+                repo_tree,
+                locator,
+                cli.refresh,
+                &|path: String| async move {
+                    download_manifest_content(locator, &path, !cli.no_cache).await
+                },
+            )
+            .await?;
+            if !manifest.errors.is_empty() {
+                anyhow::bail!("{}", manifest.errors.join("\n"));
+            }
+            Ok((manifest.entries, manifest.target_dir))
+        }
+        None => {
+            let path = target.trim_matches('/');
+            repo_tree.children(locator, "", cli.refresh).await?;
+            match repo_tree.find_node(path) {
+                Some(node) if node.is_dir() => {
+                    anyhow::bail!("{path} is a directory; pass a manifest or a rule file")
+                }
+                Some(_) => Ok((vec![path.to_string()], None)),
+                None => anyhow::bail!(
+                    "No manifest or file named {target} in {}/{}",
+                    locator.owner,
+                    locator.repo
+                ),
+            }
+        }
+    }
+}
+
+/// Print counts of the repository's directories, rules and manifests
+async fn handle_stats_command(locator: &github::RepoLocator, cli: &Cli) -> anyhow::Result<()> {
+    let mut repo_tree = repo_tree_for(cli, true)?;
+    repo_tree.children(locator, "", cli.refresh).await?;
+
+    let nodes = repo_tree
+        .cached_nodes()
+        .filter(|node| github::should_show(node, cli.all));
+    let stats = stats::compute(nodes);
+
+    if !output::print_structured(cli.output_format(), &stats)? {
+        println!("{}", stats::render(&stats));
+    }
+    Ok(())
+}
+
+/// Find rules by path, and with `content` by text, printing one match per line
+async fn handle_search_command(
+    locator: &github::RepoLocator,
+    query: &str,
+    content: bool,
+    cli: &Cli,
+) -> anyhow::Result<()> {
+    let mut repo_tree = repo_tree_for(cli, true)?;
+    repo_tree.children(locator, "", cli.refresh).await?;
+
+    let rules = search::rule_files(
+        repo_tree
+            .cached_nodes()
+            .filter(|node| github::should_show(node, cli.all)),
+    );
+    let mut matches = search::path_matches(&rules, query);
+
+    if content {
+        let octocrab = std::sync::Arc::new(github::client::authenticated()?);
+        for rule in &rules {
+            let text = match copier::fetch_file_content(
+                &octocrab,
+                locator,
+                &rule.path,
+                rule.blob_sha.as_deref(),
+                cli.raw_content,
+                !cli.no_cache,
+            )
+            .await
+            {
+                Ok(text) => text,
+                Err(e) => {
+                    eprintln!(
+                        "{}",
+                        output::status_err(
+                            Status::Warning,
+                            format!("Skipping {}: {e:#}", rule.path)
+                        )
+                    );
+                    continue;
+                }
+            };
+            matches.extend(search::content_matches(
+                &rule.path,
+                &String::from_utf8_lossy(&text),
+                query,
+            ));
+        }
+    }
+
+    if !output::print_structured(cli.output_format(), &matches)? {
+        println!("{}", search::render(&matches, query));
+    }
+    Ok(())
+}
+
+/// Handle the list command
+async fn handle_list_command(
+    locator: &github::RepoLocator,
+    repos: &[github::RepoLocator],
+    long: bool,
+    cli: &Cli,
+) -> anyhow::Result<()> {
+    if !repos.is_empty() {
+        return handle_multi_repo_list(locator, repos, long, cli).await;
+    }
+
+    let mut repo_tree = repo_tree_for(cli, true)?.with_depth(cli.depth);
+
+    let nodes = repo_tree.full_tree(locator, cli.refresh, cli.all).await?;
+
+    let rendered = output::render(cli.output_format(), &nodes, || {
+        if long {
+            output::render_tree_long(&nodes)
+        } else {
+            output::render_tree(&nodes)
+        }
+    })?;
+    if !rendered.is_empty() {
+        println!("{rendered}");
+    }
+
+    Ok(())
+}
+
+/// List the resolved repository together with `repos`, fetching up to `--parallel-repos`
+/// trees at once
+async fn handle_multi_repo_list(
+    locator: &github::RepoLocator,
+    repos: &[github::RepoLocator],
+    long: bool,
+    cli: &Cli,
+) -> anyhow::Result<()> {
+    let mut locators = vec![locator.clone()];
+    for repo in repos {
+        if !locators.contains(repo) {
+            locators.push(repo.clone());
+        }
+    }
+
+    let trees = github::full_trees(
+        locators,
+        cli.parallel_repos as usize,
+        || Ok(repo_tree_for(cli, true)?.with_depth(cli.depth)),
+        cli.refresh,
+        cli.all,
+    )
+    .await?;
+
+    let listing: Vec<_> = trees
+        .iter()
+        .map(|(locator, nodes)| {
+            serde_json::json!({
+                "repo": format!("{}/{}@{}", locator.owner, locator.repo, locator.branch),
+                "tree": nodes,
+            })
+        })
+        .collect();
+    let rendered = output::render(cli.output_format(), &listing, || {
+        trees
+            .iter()
+            .map(|(locator, nodes)| {
+                let tree = if long {
+                    output::render_tree_long(nodes)
+                } else {
+                    output::render_tree(nodes)
+                };
+                format!(
+                    "{}/{}@{}\n{tree}",
+                    locator.owner, locator.repo, locator.branch
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    })?;
+    println!("{rendered}");
+
+    Ok(())
+}
+
+/// Handle `manifest validate`, returning whether every checked manifest is free of errors
+async fn handle_manifest_validate(
+    locator: &github::RepoLocator,
+    manifest_id: &str,
+    cli: &Cli,
+) -> anyhow::Result<bool> {
+    let mut repo_tree = repo_tree_for(cli, true)?;
+    let available_manifests =
+        find_manifests_in_quickadd(&mut repo_tree, locator, cli.refresh, cli.all).await?;
+
+    let targets: Vec<(String, ManifestFormat, String)> = if manifest_id == "all" {
+        let mut targets: Vec<_> = available_manifests
+            .iter()
+            .map(|(id, (format, path))| (id.clone(), format.clone(), path.clone()))
+            .collect();
+        targets.sort_by(|a, b| a.0.cmp(&b.0));
+        targets
     } else {
-        // Use persistent cache for normal operation
-        github::RepoTree::with_persistent_cache()?
+        let (format, path) = resolve_manifest_id(manifest_id, &available_manifests)
+            .with_context(|| format!("Manifest '{manifest_id}' not found"))?;
+        vec![(manifest_id.to_string(), format, path)]
     };
-    let available_manifests =
-        find_manifests_in_quickadd(&mut repo_tree, locator, cli.refresh).await?;
 
-    if available_manifests.is_empty() {
-        println!("No manifests found in the quick-add/ directory.");
+    let mut reports = Vec::new();
+    let mut clean = true;
+    for (id, format, path) in targets {
+        let parsed = match download_manifest_content(locator, &path, !cli.no_cache).await {
+            Ok(content) => parse_manifest_content(
+                &content,
+                format,
+                &id,
+                &mut repo_tree,
+                locator,
+                cli.refresh,
+                &|path: String| async move {
+                    download_manifest_content(locator, &path, !cli.no_cache).await
+                },
+            )
+            .await
+            .map_err(anyhow::Error::from),
+            Err(e) => Err(e),
+        };
+
+        let (errors, warnings, entry_count) = match parsed {
+            Ok(manifest) => (manifest.errors, manifest.warnings, manifest.entries.len()),
+            Err(e) => (vec![format!("{id}: {e}")], Vec::new(), 0),
+        };
+        clean &= errors.is_empty();
+        reports.push(serde_json::json!({
+            "manifest": id,
+            "errors": errors,
+            "warnings": warnings,
+            "entry_count": entry_count,
+        }));
+
+        if !cli.output_format().is_structured() {
+            println!(
+                "{id}: {entry_count} rule(s), {} error(s), {} warning(s)",
+                errors.len(),
+                warnings.len()
+            );
+            for error in &errors {
+                println!("  {}", output::status(Status::Error, error));
+            }
+            for warning in &warnings {
+                println!("  {}", output::status(Status::Warning, warning));
+            }
+        }
+    }
+
+    output::print_structured(cli.output_format(), &reports)?;
+
+    Ok(clean)
+}
+
+/// Rules in the output directory's rules.lock that an earlier run of `manifest` from
+/// `locator`'s repository wrote and that `entries` no longer list
+fn stale_rules(
+    locator: &github::RepoLocator,
+    manifest: &str,
+    entries: &[String],
+    output_dir: &Path,
+) -> anyhow::Result<Vec<lockfile::LockedFile>> {
+    let Some(lock) = lockfile::load_lockfile(output_dir)? else {
+        return Ok(Vec::new());
+    };
+    Ok(
+        lockfile::prune_set(&lock, &locator.owner, &locator.repo, manifest, entries)
+            .into_iter()
+            .cloned()
+            .collect(),
+    )
+}
+
+/// Delete the rules [`stale_rules`] finds for the manifest `copy_config` applies, or with
+/// `--dry-run` only list them. Asks first unless `--force`; files edited since they were
+/// copied are kept.
+fn prune_stale_rules(
+    locator: &github::RepoLocator,
+    copy_config: &CopyConfig,
+    entries: &[String],
+    cli: &Cli,
+) -> anyhow::Result<()> {
+    use lockfile::LOCKFILE_NAME;
+
+    let output_dir = copy_config.output_dir.as_path();
+    let Some(manifest) = copy_config.manifest.as_deref() else {
+        return Ok(());
+    };
+    let stale = stale_rules(locator, manifest, entries, output_dir)?;
+    if stale.is_empty() {
+        if !cli.quiet {
+            println!("No rules to prune.");
+        }
         return Ok(());
     }
 
-    // Try to resolve the manifest ID
-    let (manifest_format, manifest_path) =
-        match resolve_manifest_id(manifest_id, &available_manifests) {
-            Some(manifest) => manifest,
-            None => {
-                eprintln!("Manifest '{manifest_id}' not found.");
-                eprintln!("Available manifests:");
-                for (id, (format, _)) in &available_manifests {
-                    eprintln!("  - {} (.{})", id, format_extension(format));
+    println!();
+    if cli.dry_run {
+        println!(
+            "Would prune {} rule(s) no longer in the manifest:",
+            stale.len()
+        );
+    } else {
+        println!("{} rule(s) are no longer in the manifest:", stale.len());
+    }
+    for file in &stale {
+        println!("  {} (from {})", file.destination, file.source);
+    }
+    if cli.dry_run {
+        return Ok(());
+    }
+
+    if !cli.force {
+        let confirmed = is_terminal::IsTerminal::is_terminal(&std::io::stdin())
+            && Confirm::new("Delete them?")
+                .with_default(false)
+                .prompt()
+                .unwrap_or(false);
+        if !confirmed {
+            println!("Nothing pruned; use --force to prune without asking.");
+            return Ok(());
+        }
+    }
+
+    let mut lock = lockfile::load_lockfile(output_dir)?.unwrap_or_default();
+    let mut removed = 0;
+    for file in &stale {
+        let Some(path) = file.local_path(output_dir) else {
+            eprintln!(
+                "{}",
+                output::status_err(
+                    Status::Warning,
+                    format!(
+                        "{} is outside {}; kept",
+                        file.destination,
+                        output_dir.display()
+                    )
+                )
+            );
+            continue;
+        };
+        if path.exists() {
+            if copier::git_blob_sha_of_file(&path)? != file.sha {
+                eprintln!(
+                    "{}",
+                    output::status_err(
+                        Status::Warning,
+                        format!("{} has local changes; kept", file.destination)
+                    )
+                );
+                continue;
+            }
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            removed += 1;
+        }
+        lock.forget(&file.destination);
+    }
+    lockfile::save_lockfile(output_dir, &lock)?;
+    println!("Pruned {removed} rule(s) and updated {LOCKFILE_NAME}.");
+    Ok(())
+}
+
+/// Rewrite every file in rules.lock with its locked version, leaving files that already
+/// match alone and locally modified ones unless `--force` is given. With `frozen`, first
+/// check that the repository still has the locked versions and stop if any changed.
+async fn handle_sync_command(cli: &Cli, output_dir: &Path, frozen: bool) -> anyhow::Result<()> {
+    use lockfile::{find_drift, load_lockfile, verify_lockfile, VerifyStatus, LOCKFILE_NAME};
+
+    let lock = load_lockfile(output_dir)?
+        .with_context(|| format!("No {LOCKFILE_NAME} found in {}", output_dir.display()))?;
+
+    // Files are fetched per repository they were locked from
+    let mut by_repo: std::collections::BTreeMap<(&str, &str, &str), Vec<&lockfile::LockedFile>> =
+        std::collections::BTreeMap::new();
+    for file in &lock.files {
+        if file.local_path(output_dir).is_none() {
+            anyhow::bail!(
+                "{LOCKFILE_NAME} lists {:?}, which is outside the output directory",
+                file.destination
+            );
+        }
+        by_repo
+            .entry((&file.owner, &file.repo, &file.branch))
+            .or_default()
+            .push(file);
+    }
+
+    if frozen {
+        let mut drift = Vec::new();
+        for (&(owner, repo, branch), files) in &by_repo {
+            let locator = github::RepoLocator {
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+                branch: branch.to_string(),
+            };
+            let mut tree = repo_tree_for(cli, true)?;
+            tree.children(&locator, "", cli.refresh).await?;
+            drift.extend(find_drift(files.iter().copied(), |source| {
+                tree.find_node(source)
+                    .and_then(|node| node.blob_sha.clone())
+            }));
+        }
+        if !drift.is_empty() {
+            for changed in &drift {
+                let now = changed.current_sha.as_deref().unwrap_or("removed");
+                eprintln!(
+                    "  {}",
+                    output::status_err(
+                        Status::Error,
+                        format!(
+                            "{}: locked {}, now {now}",
+                            changed.source, changed.locked_sha
+                        )
+                    )
+                );
+            }
+            anyhow::bail!(
+                "{} locked rule(s) changed in the repository; re-run quick-add to update {LOCKFILE_NAME}",
+                drift.len()
+            );
+        }
+    }
+
+    let local: std::collections::HashMap<String, VerifyStatus> =
+        verify_lockfile(&lock, output_dir)?
+            .into_iter()
+            .map(|entry| (entry.destination, entry.status))
+            .collect();
+    let octocrab = github::client::authenticated()?;
+    let (mut restored, mut unchanged, mut kept) = (0, 0, 0);
+    for (&(owner, repo, branch), files) in &by_repo {
+        let locator = github::RepoLocator {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            branch: branch.to_string(),
+        };
+        for file in files {
+            match local.get(&file.destination) {
+                Some(VerifyStatus::Ok) => {
+                    unchanged += 1;
+                    continue;
+                }
+                Some(VerifyStatus::Modified) if !cli.force => {
+                    kept += 1;
+                    eprintln!(
+                        "{}",
+                        output::status_err(
+                            Status::Warning,
+                            format!(
+                                "{} has local changes; use --force to replace it",
+                                file.destination
+                            )
+                        )
+                    );
+                    continue;
+                }
+                _ => {}
+            }
+
+            let content = copier::fetch_blob(&octocrab, &locator, &file.sha, !cli.no_cache)
+                .await
+                .with_context(|| format!("Failed to fetch {}", file.source))?;
+            // Checked against `..` and absolute destinations above
+            let dest = file
+                .local_path(output_dir)
+                .context("Destination escapes the output directory")?;
+            copier::write_within_output_dir(&dest, output_dir, &content)
+                .await
+                .with_context(|| format!("Failed to write {}", dest.display()))?;
+            restored += 1;
+            if !cli.quiet {
+                println!("  {}", output::status(Status::Ok, &file.destination));
+            }
+        }
+    }
+
+    println!("Restored {restored} file(s), {unchanged} already matched {LOCKFILE_NAME}");
+    if kept > 0 {
+        anyhow::bail!("{kept} locally modified file(s) were left alone");
+    }
+    Ok(())
+}
+
+/// Handle the verify command
+fn handle_verify_command(cli: &Cli, output_dir: &Path) -> anyhow::Result<()> {
+    use lockfile::{load_lockfile, verify_lockfile, VerifyStatus, LOCKFILE_NAME};
+
+    let lock = load_lockfile(output_dir)?
+        .with_context(|| format!("No {LOCKFILE_NAME} found in {}", output_dir.display()))?;
+
+    let results = verify_lockfile(&lock, output_dir)?;
+    let mismatches = results
+        .iter()
+        .filter(|r| r.status != VerifyStatus::Ok)
+        .count();
+
+    let report = serde_json::json!({
+        "output_dir": output_dir.display().to_string(),
+        "files": results,
+        "mismatches": mismatches,
+    });
+    if !output::print_structured(cli.output_format(), &report)? {
+        println!(
+            "Verifying {} locked file(s) in {}",
+            results.len(),
+            output_dir.display()
+        );
+        println!();
+        for result in &results {
+            match result.status {
+                VerifyStatus::Ok => {
+                    println!("  {}", output::status(Status::Ok, &result.destination))
                 }
-                std::process::exit(2);
+                VerifyStatus::Modified => println!(
+                    "  {}",
+                    output::status(Status::Error, format!("{} (modified)", result.destination))
+                ),
+                VerifyStatus::Missing => println!(
+                    "  {}",
+                    output::status(Status::Error, format!("{} (missing)", result.destination))
+                ),
+                VerifyStatus::OutsideOutputDir => println!(
+                    "  {}",
+                    output::status(
+                        Status::Error,
+                        format!("{} (outside the output directory)", result.destination)
+                    )
+                ),
             }
+        }
+        println!();
+        if mismatches == 0 {
+            println!("All files match {LOCKFILE_NAME}.");
+        } else {
+            println!("{mismatches} file(s) differ from {LOCKFILE_NAME}.");
+        }
+    }
+
+    if mismatches > 0 {
+        return Err(Exit(1).into());
+    }
+
+    Ok(())
+}
+
+/// Handle the quick-add command
+async fn handle_quick_add(
+    locator: &github::RepoLocator,
+    manifest_id: &str,
+    cli: &Cli,
+    out_dir: &Path,
+    jobs: usize,
+    telemetry: &telemetry::Telemetry,
+    manifest_out: Option<(&Path, bool)>,
+) -> anyhow::Result<()> {
+    let started = std::time::Instant::now();
+
+    let mut repo_tree = repo_tree_for(cli, true)?;
+
+    // A URL is fetched as is; its entries still refer to the resolved repository
+    // The manifest's path (or URL) is recorded in rules.lock, so --prune only removes
+    // rules this manifest copied
+    let (manifest_format, manifest_content, manifest_source) =
+        if let Some(url) = github::manifests::manifest_url(manifest_id)? {
+            let (format, content) = github::manifests::fetch_remote_manifest(&url).await?;
+            (format, content, url.to_string())
+        } else {
+            // Find available manifests in the quick-add directory
+            let available_manifests =
+                find_manifests_in_quickadd(&mut repo_tree, locator, cli.refresh, cli.all).await?;
+
+            if available_manifests.is_empty() {
+                println!(
+                    "No manifests found in the {}/ directory.",
+                    repo_tree.manifest_dir()
+                );
+                return Ok(());
+            }
+
+            // Try to resolve the manifest ID
+            let (manifest_format, manifest_path) =
+                match resolve_manifest_id(manifest_id, &available_manifests) {
+                    Some(manifest) => manifest,
+                    None => {
+                        eprintln!("Manifest '{manifest_id}' not found.");
+                        eprintln!("Available manifests:");
+                        for (id, (format, _)) in &available_manifests {
+                            eprintln!("  - {} (.{})", id, format_extension(format));
+                        }
+                        return Err(Exit(2).into());
+                    }
+                };
+
+            (
+                manifest_format,
+                download_manifest_content(locator, &manifest_path, !cli.no_cache).await?,
+                manifest_path,
+            )
         };
 
-    // Download and parse the manifest content
-    let manifest_content = download_manifest_content(locator, &manifest_path).await?;
-    let manifest = parse_manifest_content(
-        &manifest_content,
-        manifest_format,
-        manifest_id,
-        &mut repo_tree,
-        locator,
-        cli.refresh,
-    )
-    .await?;
+    // Parse and validate the manifest content
+    let manifest =
+        parse_manifest_content(
+            &manifest_content,
+            manifest_format,
+            manifest_id,
+            &mut repo_tree,
+            locator,
+            cli.refresh,
+            &|path: String| async move {
+                download_manifest_content(locator, &path, !cli.no_cache).await
+            },
+        )
+        .await?;
 
     // Report any validation errors or warnings
-    if !manifest.warnings.is_empty() {
+    if !manifest.warnings.is_empty() && !cli.quiet {
         eprintln!("Warnings:");
         for warning in &manifest.warnings {
-            eprintln!("  ⚠ {warning}");
+            eprintln!("  {}", output::status_err(Status::Warning, warning));
         }
     }
 
+    // With --since, only plan the rules changed recently
+    let entries = entries_changed_since(locator, &manifest.entries, cli).await;
+
+    // Create copy configuration
+    let copy_config = CopyConfig {
+        output_dir: out_dir.to_path_buf(),
+        overwrite_mode: overwrite_mode_for(cli),
+        max_concurrency: jobs,
+        raw_content: cli.raw_content,
+        quiet: cli.quiet,
+        verify: !cli.no_verify,
+        use_cache: !cli.no_cache,
+        skip_unchanged: cli.skip_unchanged,
+        backup: cli.backup,
+        max_file_size: cli.max_file_size,
+        validate_mdc: cli.validate_mdc,
+        strict_mdc: cli.strict,
+        manifest: Some(manifest_source.clone()),
+    };
+
+    // Machine-readable dry-run includes validation problems instead of stopping at them
+    if cli.dry_run && cli.output_format().is_structured() {
+        let copy_plan =
+            create_copy_plan_in(&entries, &copy_config, manifest.target_dir.as_deref())?;
+        let mut report = serde_json::json!({
+            "manifest": manifest.name,
+            "description": manifest.description,
+            "errors": manifest.errors,
+            "warnings": manifest.warnings,
+            "files": render_copy_plan_json(&copy_plan),
+        });
+        if cli.prune {
+            let stale = stale_rules(
+                locator,
+                &manifest_source,
+                &manifest.entries,
+                &copy_config.output_dir,
+            )?;
+            report["prune"] = serde_json::to_value(stale)?;
+        }
+        output::print_structured(cli.output_format(), &report)?;
+        if !manifest.errors.is_empty() {
+            return Err(Exit(2).into());
+        }
+        return Ok(());
+    }
+
     if !manifest.errors.is_empty() {
         eprintln!("Errors:");
         for error in &manifest.errors {
-            eprintln!("  ✗ {error}");
+            eprintln!("  {}", output::status_err(Status::Error, error));
         }
-        std::process::exit(2);
+        return Err(Exit(2).into());
     }
 
     if manifest.entries.is_empty() {
@@ -501,21 +2206,29 @@ async fn handle_quick_add(
         return Ok(());
     }
 
-    // Create copy configuration
-    let copy_config = CopyConfig {
-        output_dir: out_dir
-            .map(PathBuf::from)
-            .unwrap_or_else(|| PathBuf::from("./.cursor/rules")),
-        overwrite_mode: if cli.force {
-            copier::OverwriteMode::Force
+    // Write the materialized manifest if requested
+    if let Some((path, canonical)) = manifest_out {
+        if cli.dry_run {
+            println!("Dry-run mode: Would write manifest to {}", path.display());
         } else {
-            copier::OverwriteMode::Prompt
-        },
-        max_concurrency: 4,
-    };
+            let content = render_materialized_manifest(&manifest, canonical);
+            std::fs::write(path, content)
+                .with_context(|| format!("Failed to write manifest {}", path.display()))?;
+            if !cli.quiet {
+                println!("Wrote manifest to {}", path.display());
+            }
+        }
+    }
+
+    if entries.is_empty() {
+        println!("No rule files in the manifest changed since the --since time.");
+        return Ok(());
+    }
 
     // Create copy plan
-    let copy_plan = create_copy_plan(&manifest.entries, &copy_config)?;
+    let mut copy_plan =
+        create_copy_plan_in(&entries, &copy_config, manifest.target_dir.as_deref())?;
+    copier::attach_blob_shas(&mut copy_plan, &repo_tree);
 
     // Handle dry-run mode
     if cli.dry_run {
@@ -527,103 +2240,177 @@ async fn handle_quick_add(
         }
         println!();
         println!("{}", render_copy_plan_table(&copy_plan));
+        if cli.prune {
+            prune_stale_rules(locator, &copy_config, &manifest.entries, cli)?;
+        }
 
         // Exit with appropriate code
-        let has_validation_errors = !manifest.errors.is_empty();
-        std::process::exit(if has_validation_errors { 2 } else { 0 });
+        if !manifest.errors.is_empty() {
+            return Err(Exit(2).into());
+        }
+        return Ok(());
     }
 
     // Execute the copy plan
-    println!("Applying manifest: {} ({})", manifest.name, manifest_id);
-    if let Some(description) = &manifest.description {
-        println!("Description: {description}");
+    if !cli.output_format().is_structured() && !cli.quiet {
+        println!("Applying manifest: {} ({})", manifest.name, manifest_id);
+        if let Some(description) = &manifest.description {
+            println!("Description: {description}");
+        }
+        println!();
     }
-    println!();
 
     // Create appropriate prompt service based on CLI flags
-    let prompt_service: Box<dyn PromptService> = if cli.force {
-        Box::new(NonInteractivePromptService::overwrite_all())
-    } else {
-        Box::new(InteractivePromptService::new())
-    };
+    let prompt_service = prompt_service_for(cli);
 
     let stats =
         execute_copy_plan(copy_plan, locator, &copy_config, prompt_service.as_ref()).await?;
 
-    println!();
-    println!("Copy operation completed:");
-    println!("  Files copied: {}", stats.files_copied);
-    println!("  Files skipped: {}", stats.files_skipped);
-    println!("  Files failed: {}", stats.files_failed);
+    if !output::print_structured(cli.output_format(), &stats)? {
+        println!();
+        if stats.cancelled {
+            println!("Copy operation cancelled; remaining files were not copied.");
+        }
+        println!("Copy operation completed:");
+        println!("  Files copied: {}", stats.files_copied);
+        println!("  Files renamed: {}", stats.files_renamed);
+        println!("  Files skipped: {}", stats.files_skipped);
+        println!("  Files unchanged: {}", stats.files_unchanged);
+        println!("  Files too large: {}", stats.files_too_large);
+        println!("  Files failed: {}", stats.files_failed);
+        report_copy_warnings(&stats, cli.max_file_size);
+        report_copy_failures(&stats);
+        report_backups(&stats);
+    }
+
+    // Only prune after a complete run, so an interrupted copy never removes anything
+    if cli.prune && !stats.cancelled {
+        prune_stale_rules(locator, &copy_config, &manifest.entries, cli)?;
+    }
+
+    telemetry.record(
+        "quick_add_complete",
+        &[
+            ("command", "quick-add".to_string()),
+            ("files_copied", stats.files_copied.to_string()),
+            ("files_renamed", stats.files_renamed.to_string()),
+            ("files_skipped", stats.files_skipped.to_string()),
+            ("files_unchanged", stats.files_unchanged.to_string()),
+            ("files_too_large", stats.files_too_large.to_string()),
+            ("files_failed", stats.files_failed.to_string()),
+            ("cancelled", stats.cancelled.to_string()),
+            ("duration_ms", started.elapsed().as_millis().to_string()),
+        ],
+    );
 
     if stats.files_failed > 0 {
-        std::process::exit(1);
+        return Err(Exit(1).into());
     }
 
     Ok(())
 }
 
+/// `entries` narrowed to those changed after `--since`, or all of them without the flag.
+/// Entries whose last change is unknown are kept, with a warning.
+async fn entries_changed_since(
+    locator: &github::RepoLocator,
+    entries: &[String],
+    cli: &Cli,
+) -> Vec<String> {
+    let Some(since) = cli.since else {
+        return entries.to_vec();
+    };
+    let (changed, unknown) = github::history::changed_since(locator, entries, since).await;
+    if !cli.quiet {
+        for path in &unknown {
+            eprintln!(
+                "{}",
+                output::status_err(
+                    Status::Warning,
+                    format!("Could not tell when {path} last changed; including it"),
+                )
+            );
+        }
+        let unchanged = entries.len() - changed.len();
+        if unchanged > 0 {
+            eprintln!(
+                "Skipping {unchanged} rule(s) unchanged since {}",
+                since.format("%Y-%m-%d %H:%M UTC")
+            );
+        }
+    }
+    changed
+}
+
 /// Handle file/manifest selection from the interactive browser
 async fn handle_browser_selection(
     locator: &github::RepoLocator,
     file_path: &str,
     cli: &Cli,
-    out_dir: Option<&str>,
+    out_dir: &Path,
+    jobs: usize,
+    telemetry: &telemetry::Telemetry,
 ) -> anyhow::Result<()> {
     use crate::copier::{create_copy_plan, execute_copy_plan, CopyConfig};
-    use crate::ui::prompts::{
-        InteractivePromptService, NonInteractivePromptService, PromptService,
-    };
-
-    use std::path::PathBuf;
 
     // Check if this is a manifest file
-    if file_path.starts_with("quick-add/") && is_manifest_file(file_path) {
+    let manifest_filename = file_path
+        .strip_prefix(manifest_dir(cli).trim_matches('/'))
+        .and_then(|rest| rest.strip_prefix('/'));
+    if let Some(manifest_filename) = manifest_filename.filter(|_| is_manifest_file(file_path)) {
         // Extract manifest ID from path (filename without extension)
-        let manifest_filename = file_path.strip_prefix("quick-add/").unwrap();
         let manifest_id = if let Some(pos) = manifest_filename.rfind('.') {
             &manifest_filename[..pos]
         } else {
             manifest_filename
         };
 
-        println!("Applying manifest: {manifest_id}");
+        if !cli.quiet {
+            println!("Applying manifest: {manifest_id}");
+        }
 
         // Use the existing quick-add logic
-        handle_quick_add(locator, manifest_id, cli, out_dir).await
+        handle_quick_add(locator, manifest_id, cli, out_dir, jobs, telemetry, None).await
     } else if file_path.ends_with(".mdc") {
         // Single file copy
-        println!("Copying file: {file_path}");
+        if !cli.quiet {
+            println!("Copying file: {file_path}");
+        }
 
         let copy_config = CopyConfig {
-            output_dir: out_dir
-                .map(PathBuf::from)
-                .unwrap_or_else(|| PathBuf::from("./.cursor/rules")),
-            overwrite_mode: if cli.force {
-                copier::OverwriteMode::Force
-            } else {
-                copier::OverwriteMode::Prompt
-            },
+            output_dir: out_dir.to_path_buf(),
+            overwrite_mode: overwrite_mode_for(cli),
             max_concurrency: 1,
+            raw_content: cli.raw_content,
+            quiet: cli.quiet,
+            verify: !cli.no_verify,
+            use_cache: !cli.no_cache,
+            skip_unchanged: cli.skip_unchanged,
+            backup: cli.backup,
+            max_file_size: cli.max_file_size,
+            validate_mdc: cli.validate_mdc,
+            strict_mdc: cli.strict,
+            manifest: None,
         };
 
         // Create copy plan for single file
-        let copy_plan = create_copy_plan(&[file_path.to_string()], &copy_config)?;
+        let mut copy_plan = create_copy_plan(&[file_path.to_string()], &copy_config)?;
 
         if cli.dry_run {
             println!("Dry-run mode: Would copy {file_path}");
         } else {
+            attach_tree_blob_shas(locator, cli, &mut copy_plan).await;
+
             // Create appropriate prompt service based on CLI flags
-            let prompt_service: Box<dyn PromptService> = if cli.force {
-                Box::new(NonInteractivePromptService::overwrite_all())
-            } else {
-                Box::new(InteractivePromptService::new())
-            };
+            let prompt_service = prompt_service_for(cli);
 
             let stats =
                 execute_copy_plan(copy_plan, locator, &copy_config, prompt_service.as_ref())
                     .await?;
             println!("Copied {} file(s)", stats.files_copied);
+            report_copy_warnings(&stats, cli.max_file_size);
+            report_copy_failures(&stats);
+            report_backups(&stats);
         }
 
         Ok(())
@@ -634,34 +2421,245 @@ async fn handle_browser_selection(
     }
 }
 
-/// Resolve manifest ID to format and path
-fn resolve_manifest_id(
-    manifest_id: &str,
-    available_manifests: &std::collections::HashMap<String, (ManifestFormat, String)>,
-) -> Option<(ManifestFormat, String)> {
-    // First, try exact ID match (basename without extension)
-    if let Some((format, path)) = available_manifests.get(manifest_id) {
-        return Some((format.clone(), path.clone()));
+/// Handle a batch of marked rule files from the interactive browser
+async fn handle_batch_selection(
+    locator: &github::RepoLocator,
+    paths: &[String],
+    cli: &Cli,
+    out_dir: &Path,
+    jobs: usize,
+) -> anyhow::Result<()> {
+    if !cli.quiet {
+        println!("Copying {} marked file(s)", paths.len());
     }
 
-    // If ID contains extension, try to find exact filename match
-    if manifest_id.contains('.') {
-        for (format, path) in available_manifests.values() {
-            if path.ends_with(manifest_id) {
-                return Some((format.clone(), path.clone()));
-            }
+    let copy_config = CopyConfig {
+        output_dir: out_dir.to_path_buf(),
+        overwrite_mode: overwrite_mode_for(cli),
+        max_concurrency: jobs,
+        raw_content: cli.raw_content,
+        quiet: cli.quiet,
+        verify: !cli.no_verify,
+        use_cache: !cli.no_cache,
+        skip_unchanged: cli.skip_unchanged,
+        backup: cli.backup,
+        max_file_size: cli.max_file_size,
+        validate_mdc: cli.validate_mdc,
+        strict_mdc: cli.strict,
+        manifest: None,
+    };
+
+    let paths = entries_changed_since(locator, paths, cli).await;
+    if paths.is_empty() {
+        println!("No marked file changed since the --since time.");
+        return Ok(());
+    }
+    let mut copy_plan = create_copy_plan(&paths, &copy_config)?;
+
+    if cli.dry_run {
+        println!("Dry-run mode: Showing what would be copied");
+        println!("{}", render_copy_plan_table(&copy_plan));
+        return Ok(());
+    }
+
+    attach_tree_blob_shas(locator, cli, &mut copy_plan).await;
+
+    let prompt_service = prompt_service_for(cli);
+    let stats =
+        execute_copy_plan(copy_plan, locator, &copy_config, prompt_service.as_ref()).await?;
+
+    if stats.cancelled {
+        println!("Copy operation cancelled; remaining files were not copied.");
+    }
+    println!(
+        "Copied {} file(s), skipped {}, unchanged {}, too large {}, failed {}",
+        stats.files_copied,
+        stats.files_skipped,
+        stats.files_unchanged,
+        stats.files_too_large,
+        stats.files_failed
+    );
+    report_copy_warnings(&stats, cli.max_file_size);
+    report_copy_failures(&stats);
+    report_backups(&stats);
+
+    Ok(())
+}
+
+/// List each file that failed to copy, and why, after the copy summary
+fn report_copy_failures(stats: &copier::CopyStats) {
+    let failures = stats.failures();
+    if failures.is_empty() {
+        return;
+    }
+
+    eprintln!();
+    eprintln!("Failed files:");
+    for (source, error) in failures {
+        eprintln!(
+            "  {}",
+            output::status_err(Status::Error, format!("{source}: {error}"))
+        );
+    }
+}
+
+/// Warn about each file refused for exceeding `--max-file-size` and each rule whose front
+/// matter `--validate-mdc` found problems in
+fn report_copy_warnings(stats: &copier::CopyStats, limit: u64) {
+    for (source, warning) in stats.warnings() {
+        eprintln!(
+            "{}",
+            output::status_err(Status::Warning, format!("{source}: {warning}"))
+        );
+    }
+    for result in &stats.results {
+        if let copier::FileOutcome::TooLarge { size } = result.outcome {
+            eprintln!(
+                "{}",
+                output::status_err(
+                    Status::Warning,
+                    format!(
+                        "{}: {size} bytes exceeds --max-file-size {limit}; not copied",
+                        result.source
+                    )
+                )
+            );
         }
     }
+}
 
-    None
+/// List the backups made by `--backup`, with the command that restores each one
+fn report_backups(stats: &copier::CopyStats) {
+    if stats.backups.is_empty() {
+        return;
+    }
+
+    println!();
+    println!(
+        "Backed up {} overwritten file(s). To restore one:",
+        stats.backups.len()
+    );
+    for backup in &stats.backups {
+        println!(
+            "  mv {} {}",
+            backup.backup.display(),
+            backup.destination.display()
+        );
+    }
 }
 
-/// Download manifest content from GitHub
+/// Fill in blob SHAs from the (usually cached) repository tree. Best effort: plans without
+/// a SHA fall back to path-keyed caching.
+async fn attach_tree_blob_shas(
+    locator: &github::RepoLocator,
+    cli: &Cli,
+    plans: &mut [copier::CopyPlan],
+) {
+    let Ok(mut repo_tree) = repo_tree_for(cli, false) else {
+        return;
+    };
+    if repo_tree.children(locator, "", cli.refresh).await.is_ok() {
+        copier::attach_blob_shas(plans, &repo_tree);
+    }
+}
+
+/// Cache TTL from `--cache-ttl` or the `cache_ttl_hours` config key
+fn cache_ttl_hours(cli: &Cli) -> u64 {
+    cli.cache_ttl
+        .unwrap_or(github::cache::DEFAULT_CACHE_TTL_HOURS)
+}
+
+/// Manifest directory from `--manifest-dir` or the `manifest_dir` config key
+fn manifest_dir(cli: &Cli) -> &str {
+    cli.manifest_dir
+        .as_deref()
+        .unwrap_or(github::manifests::DEFAULT_MANIFEST_DIR)
+}
+
+/// Parse `--depth`, which must be at least 1
+fn parse_depth(value: &str) -> Result<usize, String> {
+    match value.trim().parse::<usize>() {
+        Ok(0) => Err("depth must be at least 1".to_string()),
+        Ok(depth) => Ok(depth),
+        Err(_) => Err(format!("Invalid depth: {value}")),
+    }
+}
+
+/// Parse `owner/repo[@branch]` for `list --repos`; the branch defaults to `main`
+fn parse_repo_spec(value: &str) -> Result<github::RepoLocator, String> {
+    let usage = || format!("expected OWNER/REPO[@BRANCH], got {value:?}");
+    let (repo_spec, branch) = match value.trim().split_once('@') {
+        Some((repo_spec, branch)) => (repo_spec, branch),
+        None => (value.trim(), "main"),
+    };
+    let (owner, repo) = repo_spec.split_once('/').ok_or_else(usage)?;
+    if [owner, repo, branch].iter().any(|part| part.is_empty()) || repo.contains('/') {
+        return Err(usage());
+    }
+    Ok(github::RepoLocator {
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+        branch: branch.to_string(),
+    })
+}
+
+/// Open the repository tree backed by the persistent cache with the configured TTL, or
+/// in-memory only for --no-cache. With `bypass_on_refresh`, --refresh also skips the cache;
+/// otherwise callers honour --refresh by passing it as `force_refresh`.
+fn repo_tree_for(cli: &Cli, bypass_on_refresh: bool) -> anyhow::Result<github::RepoTree> {
+    let tree = if cli.no_cache || (bypass_on_refresh && cli.refresh) {
+        github::RepoTree::new()
+    } else {
+        github::RepoTree::with_persistent_cache(cache_ttl_hours(cli))?
+    };
+    Ok(tree.with_manifest_dir(manifest_dir(cli)))
+}
+
+/// Select the overwrite strategy from the conflict-handling CLI flags
+fn overwrite_mode_for(cli: &Cli) -> copier::OverwriteMode {
+    if cli.force {
+        copier::OverwriteMode::Force
+    } else if cli.skip_existing {
+        copier::OverwriteMode::Skip
+    } else if cli.rename {
+        copier::OverwriteMode::Rename
+    } else {
+        copier::OverwriteMode::Prompt
+    }
+}
+
+/// Create the prompt service matching the conflict-handling CLI flags
+fn prompt_service_for(cli: &Cli) -> Box<dyn PromptService> {
+    if cli.force {
+        Box::new(NonInteractivePromptService::overwrite_all())
+    } else if cli.skip_existing {
+        Box::new(NonInteractivePromptService::skip_all())
+    } else if cli.rename {
+        Box::new(NonInteractivePromptService::rename_all())
+    } else {
+        Box::new(InteractivePromptService::new())
+    }
+}
+
+/// Download manifest content from GitHub. With `use_cache`, downloads are kept in the blob
+/// cache under their Git blob SHA, and `--offline` serves the version the cached tree lists.
 async fn download_manifest_content(
     locator: &github::RepoLocator,
     manifest_path: &str,
+    use_cache: bool,
 ) -> anyhow::Result<String> {
-    let octocrab = octocrab::instance();
+    use github::cache::{FileSystemCache, PersistentCache};
+
+    let cache = use_cache.then(FileSystemCache::new).and_then(Result::ok);
+    if github::client::offline() {
+        let cached = match &cache {
+            Some(cache) => cached_manifest_content(cache, locator, manifest_path).await,
+            None => None,
+        };
+        return cached
+            .with_context(|| format!("Manifest {manifest_path} is not cached; run online once"));
+    }
+    let octocrab = github::client::authenticated()?;
 
     let response = octocrab
         .repos(&locator.owner, &locator.repo)
@@ -671,23 +2669,45 @@ async fn download_manifest_content(
         .send()
         .await?;
 
-    match response.items.first() {
+    let bytes = match response.items.first() {
         Some(content) if content.download_url.is_some() => {
             let download_url = content.download_url.as_ref().unwrap();
-            let response = reqwest::get(download_url).await?;
-            let text = response.text().await?;
-            Ok(text)
+            let response = github::client::http()?.get(download_url).send().await?;
+            response.bytes().await?.to_vec()
         }
         Some(content) if content.content.is_some() => {
             // Handle base64 encoded content
             let encoded_content = content.content.as_ref().unwrap();
             let cleaned = encoded_content.replace(['\n', ' '], "");
-            let decoded = base64::engine::general_purpose::STANDARD.decode(cleaned)?;
-            let text = String::from_utf8(decoded)?;
-            Ok(text)
+            base64::engine::general_purpose::STANDARD.decode(cleaned)?
         }
         _ => anyhow::bail!("Manifest content not available"),
+    };
+
+    if let Some(cache) = &cache {
+        let sha = copier::git_blob_sha(&bytes);
+        let _ = cache.store_blob_cache(locator, &sha, &bytes).await;
     }
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// Manifest content from the blob cache, found through the blob SHA the cached tree
+/// records for `manifest_path`
+async fn cached_manifest_content(
+    cache: &github::cache::FileSystemCache,
+    locator: &github::RepoLocator,
+    manifest_path: &str,
+) -> Option<String> {
+    use github::cache::PersistentCache;
+
+    let sha = cache
+        .get_stale_tree_cache(locator)
+        .ok()??
+        .into_iter()
+        .find(|node| node.path == manifest_path)?
+        .blob_sha?;
+    let content = cache.get_blob_cache(locator, &sha).await.ok()??;
+    String::from_utf8(content).ok()
 }
 
 /// Get file extension for manifest format
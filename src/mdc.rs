@@ -0,0 +1,168 @@
+//! Front matter of Cursor `.mdc` rule files, checked before copying with `--validate-mdc`.
+//!
+//! A rule starts with a YAML block between two `---` lines that tells Cursor when to
+//! apply it: `description` for rules the agent picks itself, `globs` for rules attached
+//! to matching files and `alwaysApply` for rules included everywhere.
+
+use serde::Deserialize;
+use thiserror::Error;
+
+/// The fields Cursor reads from a rule's front matter
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontMatter {
+    pub description: Option<String>,
+    pub globs: Option<Globs>,
+    pub always_apply: Option<bool>,
+}
+
+/// File patterns, written either as one comma-separated string or as a list
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Globs {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Globs {
+    /// Every pattern, with surrounding whitespace removed
+    pub fn patterns(&self) -> Vec<&str> {
+        match self {
+            Globs::One(globs) => globs.split(',').map(str::trim).collect(),
+            Globs::Many(globs) => globs.iter().map(|glob| glob.trim()).collect(),
+        }
+    }
+}
+
+/// Why a rule's front matter could not be read
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FrontMatterError {
+    #[error("does not start with a `---` front matter block")]
+    Missing,
+
+    #[error("front matter is not closed by a `---` line")]
+    Unterminated,
+
+    #[error("front matter is not valid YAML: {0}")]
+    InvalidYaml(String),
+}
+
+/// Parse the front matter block at the start of `content`
+pub fn parse_front_matter(content: &str) -> Result<FrontMatter, FrontMatterError> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut lines = content.lines();
+    if lines.next().map(str::trim_end) != Some("---") {
+        return Err(FrontMatterError::Missing);
+    }
+
+    let mut yaml = Vec::new();
+    let mut closed = false;
+    for line in lines {
+        if line.trim_end() == "---" {
+            closed = true;
+            break;
+        }
+        yaml.push(line);
+    }
+    if !closed {
+        return Err(FrontMatterError::Unterminated);
+    }
+
+    let yaml = yaml.join("\n");
+    if yaml.trim().is_empty() {
+        return Ok(FrontMatter::default());
+    }
+    serde_yaml::from_str(&yaml).map_err(|e| FrontMatterError::InvalidYaml(e.to_string()))
+}
+
+/// Problems that would keep Cursor from using the rule in `content`; empty when it is fine
+pub fn problems(content: &str) -> Vec<String> {
+    let front_matter = match parse_front_matter(content) {
+        Ok(front_matter) => front_matter,
+        Err(e) => return vec![e.to_string()],
+    };
+
+    let mut problems = Vec::new();
+    let has_description = front_matter
+        .description
+        .as_deref()
+        .is_some_and(|description| !description.trim().is_empty());
+    let patterns = front_matter
+        .globs
+        .as_ref()
+        .map(Globs::patterns)
+        .unwrap_or_default();
+    if patterns.iter().any(|pattern| pattern.is_empty()) {
+        problems.push("`globs` contains an empty pattern".to_string());
+    }
+    if !has_description && patterns.is_empty() && front_matter.always_apply != Some(true) {
+        problems.push(
+            "has no `description`, `globs` or `alwaysApply: true`, so Cursor never applies it"
+                .to_string(),
+        );
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_front_matter_is_parsed() {
+        let rule = "---\ndescription: React conventions\nglobs: src/**/*.tsx, src/**/*.jsx\nalwaysApply: false\n---\n# React\n";
+        let front_matter = parse_front_matter(rule).unwrap();
+        assert_eq!(
+            front_matter.description.as_deref(),
+            Some("React conventions")
+        );
+        assert_eq!(
+            front_matter.globs.as_ref().unwrap().patterns(),
+            ["src/**/*.tsx", "src/**/*.jsx"]
+        );
+        assert_eq!(front_matter.always_apply, Some(false));
+        assert!(problems(rule).is_empty());
+
+        // Lists of globs, CRLF line endings and empty values are all accepted
+        let rule = "---\r\ndescription:\r\nglobs:\r\n  - \"*.rs\"\r\n---\r\nbody";
+        assert_eq!(
+            parse_front_matter(rule).unwrap().globs,
+            Some(Globs::Many(vec!["*.rs".to_string()]))
+        );
+        assert!(problems(rule).is_empty());
+        assert!(problems("---\nalwaysApply: true\n---\n").is_empty());
+    }
+
+    #[test]
+    fn missing_delimiters_are_reported() {
+        assert_eq!(
+            parse_front_matter("# Just markdown\n"),
+            Err(FrontMatterError::Missing)
+        );
+        assert_eq!(
+            parse_front_matter("---\ndescription: never closed\n# Body\n"),
+            Err(FrontMatterError::Unterminated)
+        );
+        assert_eq!(
+            problems("# Just markdown\n"),
+            ["does not start with a `---` front matter block"]
+        );
+    }
+
+    #[test]
+    fn invalid_yaml_and_unusable_fields_are_reported() {
+        let error = parse_front_matter("---\ndescription: [unclosed\n---\n").unwrap_err();
+        assert!(matches!(error, FrontMatterError::InvalidYaml(_)), "{error}");
+        let error = parse_front_matter("---\nalwaysApply: sometimes\n---\n").unwrap_err();
+        assert!(matches!(error, FrontMatterError::InvalidYaml(_)), "{error}");
+
+        assert_eq!(
+            problems("---\nglobs: \"*.ts,\"\ndescription: TypeScript\n---\n"),
+            ["`globs` contains an empty pattern"]
+        );
+        assert_eq!(
+            problems("---\n---\n# Rule\n"),
+            ["has no `description`, `globs` or `alwaysApply: true`, so Cursor never applies it"]
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Rendering of command output.
+//!
+//! Command results are printed as a human-readable table or serialized as JSON or YAML
+//! according to `--format`. The `✓`/`✗`/`⚠` status markers are coloured only when the
+//! stream is a terminal and `NO_COLOR` is unset or empty (see <https://no-color.org>),
+//! so piped output and `NO_COLOR=1` runs never contain ANSI escape codes.
+
+use std::ffi::OsString;
+use std::fmt::Write as _;
+
+use is_terminal::IsTerminal;
+use serde::Serialize;
+
+use crate::github::RepoNode;
+
+/// How command results are printed, chosen with `--format` (or the deprecated `--json`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    Yaml,
+}
+
+impl OutputFormat {
+    /// Whether this is a machine-readable format, which keeps informational text off stdout
+    pub fn is_structured(self) -> bool {
+        self != OutputFormat::Table
+    }
+}
+
+/// Serialize `value` as JSON or YAML, or `None` for table output, which callers render
+/// themselves
+pub fn render_structured<T: Serialize + ?Sized>(
+    format: OutputFormat,
+    value: &T,
+) -> anyhow::Result<Option<String>> {
+    Ok(match format {
+        OutputFormat::Table => None,
+        OutputFormat::Json => Some(serde_json::to_string_pretty(value)?),
+        OutputFormat::Yaml => Some(serde_yaml::to_string(value)?.trim_end().to_string()),
+    })
+}
+
+/// Render `value` in `format`, using `table` for the human-readable form
+pub fn render<T: Serialize + ?Sized>(
+    format: OutputFormat,
+    value: &T,
+    table: impl FnOnce() -> String,
+) -> anyhow::Result<String> {
+    Ok(render_structured(format, value)?.unwrap_or_else(table))
+}
+
+/// Print `value` as JSON or YAML. Returns `false` without printing for table output so the
+/// caller can print its own text.
+pub fn print_structured<T: Serialize + ?Sized>(
+    format: OutputFormat,
+    value: &T,
+) -> anyhow::Result<bool> {
+    match render_structured(format, value)? {
+        Some(text) => {
+            println!("{text}");
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Indented listing of a nested repository tree, directories suffixed with `/`
+pub fn render_tree(nodes: &[RepoNode]) -> String {
+    fn walk(out: &mut String, nodes: &[RepoNode], depth: usize) {
+        for node in nodes {
+            let indent = "  ".repeat(depth);
+            if node.is_dir() {
+                let _ = writeln!(out, "{indent}{}/", node.name);
+                walk(out, node.children.as_deref().unwrap_or(&[]), depth + 1);
+            } else if let Some(count) = node.manifest_count {
+                let _ = writeln!(out, "{indent}{}  [{count} files]", node.name);
+            } else {
+                let _ = writeln!(out, "{indent}{}", node.name);
+            }
+        }
+    }
+
+    let mut out = String::new();
+    walk(&mut out, nodes, 0);
+    out.trim_end().to_string()
+}
+
+/// `list --long`: one row per entry, like `ls -l`, with its kind, size and blob SHA
+/// followed by the full path
+pub fn render_tree_long(nodes: &[RepoNode]) -> String {
+    fn walk(rows: &mut Vec<String>, nodes: &[RepoNode]) {
+        for node in nodes {
+            rows.push(long_row(node));
+            walk(rows, node.children.as_deref().unwrap_or(&[]));
+        }
+    }
+
+    let mut rows = Vec::new();
+    walk(&mut rows, nodes);
+    rows.join("\n")
+}
+
+/// A single `list --long` row; `-` marks a size or SHA the tree API did not report
+pub fn long_row(node: &RepoNode) -> String {
+    use crate::github::NodeKind;
+
+    let kind = match node.kind {
+        NodeKind::Dir => "dir",
+        NodeKind::RuleFile => "rule",
+        NodeKind::Manifest => "manifest",
+    };
+    let size = node.size.map_or("-".to_string(), |size| size.to_string());
+    let sha = node.blob_sha.as_deref().unwrap_or("-");
+    let mut row = format!("{kind:<8}  {size:>8}  {sha:<40}  {}", node.path);
+    if node.is_dir() {
+        row.push('/');
+    } else if let Some(count) = node.manifest_count {
+        let _ = write!(row, "  [{count} files]");
+    }
+    row
+}
+
+/// Outcome shown by a status marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Error,
+    Warning,
+}
+
+impl Status {
+    fn symbol(self) -> &'static str {
+        match self {
+            Status::Ok => "✓",
+            Status::Error => "✗",
+            Status::Warning => "⚠",
+        }
+    }
+
+    /// ANSI foreground colour code: green, red or yellow
+    fn color_code(self) -> u8 {
+        match self {
+            Status::Ok => 32,
+            Status::Error => 31,
+            Status::Warning => 33,
+        }
+    }
+}
+
+/// Whether the user asked for no colour through a non-empty `NO_COLOR`
+pub fn no_color_requested() -> bool {
+    no_color_set(std::env::var_os("NO_COLOR"))
+}
+
+fn no_color_set(value: Option<OsString>) -> bool {
+    value.is_some_and(|value| !value.is_empty())
+}
+
+/// Whether coloured output should be written to stdout
+pub fn stdout_color() -> bool {
+    !no_color_requested() && std::io::stdout().is_terminal()
+}
+
+/// Whether coloured output should be written to stderr
+pub fn stderr_color() -> bool {
+    !no_color_requested() && std::io::stderr().is_terminal()
+}
+
+/// `text` prefixed with the marker for `status`, for printing to stdout
+pub fn status(status: Status, text: impl std::fmt::Display) -> String {
+    format_status(status, &text.to_string(), stdout_color())
+}
+
+/// `text` prefixed with the marker for `status`, for printing to stderr
+pub fn status_err(status: Status, text: impl std::fmt::Display) -> String {
+    format_status(status, &text.to_string(), stderr_color())
+}
+
+fn format_status(status: Status, text: &str, color: bool) -> String {
+    if color {
+        format!(
+            "\x1b[{}m{}\x1b[0m {text}",
+            status.color_code(),
+            status.symbol()
+        )
+    } else {
+        format!("{} {text}", status.symbol())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_is_plain_without_color() {
+        assert_eq!(format_status(Status::Ok, "done", false), "✓ done");
+        assert_eq!(format_status(Status::Error, "failed", false), "✗ failed");
+        assert_eq!(
+            format_status(Status::Warning, "careful", false),
+            "⚠ careful"
+        );
+    }
+
+    #[test]
+    fn status_is_colored_on_a_terminal() {
+        assert_eq!(
+            format_status(Status::Ok, "done", true),
+            "\x1b[32m✓\x1b[0m done"
+        );
+    }
+
+    #[test]
+    fn empty_no_color_is_ignored() {
+        assert!(no_color_set(Some("1".into())));
+        assert!(!no_color_set(Some("".into())));
+        assert!(!no_color_set(None));
+    }
+
+    #[test]
+    fn output_format_parses_names() {
+        use clap::ValueEnum;
+
+        assert_eq!(
+            OutputFormat::from_str("table", true),
+            Ok(OutputFormat::Table)
+        );
+        assert_eq!(OutputFormat::from_str("json", true), Ok(OutputFormat::Json));
+        assert_eq!(OutputFormat::from_str("YAML", true), Ok(OutputFormat::Yaml));
+        assert!(OutputFormat::from_str("xml", true).is_err());
+        assert_eq!(OutputFormat::default(), OutputFormat::Table);
+        assert!(!OutputFormat::Table.is_structured());
+        assert!(OutputFormat::Yaml.is_structured());
+    }
+
+    fn sample_tree() -> Vec<RepoNode> {
+        let node = |name: &str, path: &str, kind| RepoNode {
+            name: name.into(),
+            path: path.into(),
+            kind,
+            ..Default::default()
+        };
+        let mut frontend = node("frontend", "frontend", crate::github::NodeKind::Dir);
+        frontend.children = Some(vec![node(
+            "react.mdc",
+            "frontend/react.mdc",
+            crate::github::NodeKind::RuleFile,
+        )]);
+        vec![frontend]
+    }
+
+    #[test]
+    fn long_rows_show_kind_size_and_sha() {
+        use crate::github::NodeKind;
+
+        let sha = "3b18e512dba79e4c8300dd08aeb37f8e728b8dad";
+        let node = |path: &str, kind, size, blob_sha: Option<&str>| RepoNode {
+            name: path.rsplit('/').next().unwrap().into(),
+            path: path.into(),
+            kind,
+            blob_sha: blob_sha.map(str::to_string),
+            size,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            long_row(&node("frontend", NodeKind::Dir, None, None)),
+            format!("dir       {:>8}  {:<40}  frontend/", "-", "-")
+        );
+        assert_eq!(
+            long_row(&node(
+                "frontend/react.mdc",
+                NodeKind::RuleFile,
+                Some(1234),
+                Some(sha)
+            )),
+            format!("rule          1234  {sha}  frontend/react.mdc")
+        );
+        let mut manifest = node("quick-add/web.txt", NodeKind::Manifest, Some(56), Some(sha));
+        manifest.manifest_count = Some(3);
+        assert_eq!(
+            long_row(&manifest),
+            format!("manifest        56  {sha}  quick-add/web.txt  [3 files]")
+        );
+        // A file the tree API gave no metadata for
+        assert_eq!(
+            long_row(&node("notes.mdc", NodeKind::RuleFile, None, None)),
+            format!("rule      {:>8}  {:<40}  notes.mdc", "-", "-")
+        );
+
+        assert_eq!(
+            render_tree_long(&sample_tree()),
+            format!(
+                "dir       {0:>8}  {1:<40}  frontend/\nrule      {0:>8}  {1:<40}  frontend/react.mdc",
+                "-", "-"
+            )
+        );
+    }
+
+    #[test]
+    fn tree_renders_in_every_format() {
+        let nodes = sample_tree();
+        let table = || render_tree(&nodes);
+
+        assert_eq!(
+            render(OutputFormat::Table, &nodes, table).unwrap(),
+            "frontend/\n  react.mdc"
+        );
+
+        let json = render(OutputFormat::Json, &nodes, table).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[0]["children"][0]["path"], "frontend/react.mdc");
+
+        let yaml = render(OutputFormat::Yaml, &nodes, table).unwrap();
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed[0]["children"][0]["path"], "frontend/react.mdc");
+        assert!(!yaml.ends_with('\n'));
+    }
+
+    #[test]
+    fn tree_table_shows_manifest_counts() {
+        let mut nodes = sample_tree();
+        nodes[0].children.as_mut().unwrap()[0].manifest_count = Some(3);
+        assert_eq!(render_tree(&nodes), "frontend/\n  react.mdc  [3 files]");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn no_color_env_strips_escape_codes() {
+        let original = std::env::var_os("NO_COLOR");
+        std::env::set_var("NO_COLOR", "1");
+        let line = status(Status::Error, "broken");
+        let err_line = status_err(Status::Warning, "odd");
+        match original {
+            Some(value) => std::env::set_var("NO_COLOR", value),
+            None => std::env::remove_var("NO_COLOR"),
+        }
+
+        assert_eq!(line, "✗ broken");
+        assert_eq!(err_line, "⚠ odd");
+        assert!(!line.contains('\x1b'));
+    }
+}
@@ -0,0 +1,201 @@
+//! Recently used repositories for `cursor-rules recent`.
+//!
+//! Every successfully resolved `owner/repo@branch` is recorded, most recent first, in a
+//! small JSON file next to the config file. The list is a convenience: an unreadable file
+//! is treated as empty and failures to record are only logged.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::github::RepoLocator;
+
+/// Number of repositories remembered
+pub const MAX_RECENT: usize = 10;
+
+/// File in the config directory holding the list
+const RECENT_FILE: &str = "recent.json";
+
+/// A repository that was browsed or copied from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentRepo {
+    pub owner: String,
+    pub repo: String,
+    pub branch: String,
+}
+
+impl From<&RepoLocator> for RecentRepo {
+    fn from(locator: &RepoLocator) -> Self {
+        Self {
+            owner: locator.owner.clone(),
+            repo: locator.repo.clone(),
+            branch: locator.branch.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for RecentRepo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}@{}", self.owner, self.repo, self.branch)
+    }
+}
+
+/// The recent repositories list, most recent first
+#[derive(Debug)]
+pub struct RecentRepos {
+    path: PathBuf,
+    entries: Vec<RecentRepo>,
+    capacity: usize,
+}
+
+impl RecentRepos {
+    /// Load the list from the config directory
+    pub fn load() -> anyhow::Result<Self> {
+        let config_file = crate::config::config_file_path()?;
+        let dir = config_file
+            .parent()
+            .context("config file has no parent directory")?;
+        Ok(Self::load_from(&dir.join(RECENT_FILE), MAX_RECENT))
+    }
+
+    /// Load the list kept at `path`, remembering at most `capacity` entries
+    pub fn load_from(path: &Path, capacity: usize) -> Self {
+        let entries = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| {
+                serde_json::from_str::<Vec<RecentRepo>>(&text)
+                    .map_err(|e| tracing::debug!("Ignoring unreadable {}: {e}", path.display()))
+                    .ok()
+            })
+            .unwrap_or_default();
+        let mut recent = Self {
+            path: path.to_path_buf(),
+            entries,
+            capacity,
+        };
+        recent.entries.truncate(capacity);
+        recent
+    }
+
+    pub fn entries(&self) -> &[RecentRepo] {
+        &self.entries
+    }
+
+    /// The entry shown as `index` by `cursor-rules recent`, counting from 1
+    pub fn get(&self, index: usize) -> Option<&RecentRepo> {
+        index.checked_sub(1).and_then(|i| self.entries.get(i))
+    }
+
+    /// Move `entry` to the front, dropping an earlier copy and the oldest entries beyond
+    /// the capacity
+    pub fn push(&mut self, entry: RecentRepo) {
+        self.entries.retain(|existing| *existing != entry);
+        self.entries.insert(0, entry);
+        self.entries.truncate(self.capacity);
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let text = serde_json::to_string_pretty(&self.entries)?;
+        std::fs::write(&self.path, text).with_context(|| format!("writing {}", self.path.display()))
+    }
+}
+
+/// Record `locator` as the most recently used repository
+pub fn record(locator: &RepoLocator) {
+    let result = RecentRepos::load().and_then(|mut recent| {
+        recent.push(locator.into());
+        recent.save()
+    });
+    if let Err(e) = result {
+        tracing::debug!("Could not record recent repository: {e:#}");
+    }
+}
+
+/// Numbered listing, most recent first
+pub fn render(entries: &[RecentRepo]) -> String {
+    if entries.is_empty() {
+        return "No recent repositories".to_string();
+    }
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| format!("{:>2}  {entry}", i + 1))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(owner: &str, repo: &str) -> RecentRepo {
+        RecentRepo {
+            owner: owner.into(),
+            repo: repo.into(),
+            branch: "main".into(),
+        }
+    }
+
+    #[test]
+    fn push_moves_repeats_to_the_front_and_caps_the_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut recent = RecentRepos::load_from(&dir.path().join("recent.json"), 3);
+        for repo in ["a", "b", "c", "a"] {
+            recent.push(entry("octo", repo));
+        }
+        assert_eq!(
+            recent.entries(),
+            [entry("octo", "a"), entry("octo", "c"), entry("octo", "b")]
+        );
+
+        recent.push(entry("octo", "d"));
+        assert_eq!(
+            recent.entries(),
+            [entry("octo", "d"), entry("octo", "a"), entry("octo", "c")]
+        );
+
+        // Another branch of the same repo is a separate entry
+        recent.push(RecentRepo {
+            branch: "dev".into(),
+            ..entry("octo", "d")
+        });
+        assert_eq!(recent.entries()[1], entry("octo", "d"));
+    }
+
+    #[test]
+    fn saved_list_keeps_its_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("recent.json");
+        let mut recent = RecentRepos::load_from(&path, MAX_RECENT);
+        recent.push(entry("octo", "old"));
+        recent.push(entry("octo", "new"));
+        recent.save().unwrap();
+
+        let reloaded = RecentRepos::load_from(&path, MAX_RECENT);
+        assert_eq!(
+            reloaded.entries(),
+            [entry("octo", "new"), entry("octo", "old")]
+        );
+        assert_eq!(reloaded.get(1), Some(&entry("octo", "new")));
+        assert_eq!(reloaded.get(0), None);
+        assert_eq!(reloaded.get(3), None);
+        assert_eq!(
+            render(reloaded.entries()),
+            " 1  octo/new@main\n 2  octo/old@main"
+        );
+    }
+
+    #[test]
+    fn unreadable_file_starts_an_empty_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recent.json");
+        std::fs::write(&path, "not json").unwrap();
+        let recent = RecentRepos::load_from(&path, MAX_RECENT);
+        assert!(recent.entries().is_empty());
+        assert_eq!(render(recent.entries()), "No recent repositories");
+    }
+}
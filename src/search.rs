@@ -0,0 +1,143 @@
+//! Rule lookup for `cursor-rules search`.
+//!
+//! Paths come from the repository tree; `--content` also scans each rule's text, which is
+//! served from the blob cache whenever it was fetched before.
+
+use serde::Serialize;
+
+use crate::github::{NodeKind, RepoNode};
+
+/// A rule whose path matched, or one line of a rule whose content matched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchMatch {
+    pub path: String,
+    /// 1-based line number of a content match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// The matching line, trimmed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// `.mdc` rule files among `nodes`, sorted by path
+pub fn rule_files<'a>(nodes: impl IntoIterator<Item = &'a RepoNode>) -> Vec<&'a RepoNode> {
+    let mut rules: Vec<&RepoNode> = nodes
+        .into_iter()
+        .filter(|node| node.kind == NodeKind::RuleFile && node.path.ends_with(".mdc"))
+        .collect();
+    rules.sort_by(|a, b| a.path.cmp(&b.path));
+    rules
+}
+
+/// Rules whose path contains `query`, ignoring case
+pub fn path_matches(rules: &[&RepoNode], query: &str) -> Vec<SearchMatch> {
+    let query = query.to_lowercase();
+    rules
+        .iter()
+        .filter(|node| node.path.to_lowercase().contains(&query))
+        .map(|node| SearchMatch {
+            path: node.path.clone(),
+            line: None,
+            text: None,
+        })
+        .collect()
+}
+
+/// Lines of the rule at `path` that contain `query`, ignoring case
+pub fn content_matches(path: &str, content: &str, query: &str) -> Vec<SearchMatch> {
+    let query = query.to_lowercase();
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| line.to_lowercase().contains(&query))
+        .map(|(index, line)| SearchMatch {
+            path: path.to_string(),
+            line: Some(index + 1),
+            text: Some(line.trim().to_string()),
+        })
+        .collect()
+}
+
+/// One match per line: `path` for path matches, `path:line: text` for content matches
+pub fn render(matches: &[SearchMatch], query: &str) -> String {
+    if matches.is_empty() {
+        return format!("No rules match {query:?}");
+    }
+    matches
+        .iter()
+        .map(|found| match (found.line, &found.text) {
+            (Some(line), Some(text)) => format!("{}:{line}: {text}", found.path),
+            _ => found.path.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(path: &str, kind: NodeKind) -> RepoNode {
+        RepoNode {
+            name: path.rsplit('/').next().unwrap().into(),
+            path: path.into(),
+            kind,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn paths_match_rule_files_case_insensitively() {
+        let nodes = vec![
+            node("frontend", NodeKind::Dir),
+            node("frontend/React.mdc", NodeKind::RuleFile),
+            node("frontend/vue.mdc", NodeKind::RuleFile),
+            node("backend/react-native-notes.md", NodeKind::RuleFile),
+            node("quick-add/react.txt", NodeKind::Manifest),
+            node("mobile/react-native.mdc", NodeKind::RuleFile),
+        ];
+        let rules = rule_files(&nodes);
+        assert_eq!(rules.len(), 3);
+
+        let matches = path_matches(&rules, "react");
+        let paths: Vec<&str> = matches.iter().map(|found| found.path.as_str()).collect();
+        assert_eq!(paths, ["frontend/React.mdc", "mobile/react-native.mdc"]);
+        assert!(path_matches(&rules, "angular").is_empty());
+    }
+
+    #[test]
+    fn content_matches_render_with_line_numbers() {
+        let content =
+            "# Hooks\nPrefer useEffect cleanup\n\n  Avoid USEEFFECT for derived state  \n";
+        let matches = content_matches("frontend/react.mdc", content, "useEffect");
+        assert_eq!(
+            matches,
+            [
+                SearchMatch {
+                    path: "frontend/react.mdc".into(),
+                    line: Some(2),
+                    text: Some("Prefer useEffect cleanup".into()),
+                },
+                SearchMatch {
+                    path: "frontend/react.mdc".into(),
+                    line: Some(4),
+                    text: Some("Avoid USEEFFECT for derived state".into()),
+                },
+            ]
+        );
+
+        let mut all = path_matches(&[&node("hooks/effects.mdc", NodeKind::RuleFile)], "effect");
+        all.extend(matches);
+        assert_eq!(
+            render(&all, "effect"),
+            "hooks/effects.mdc\n\
+             frontend/react.mdc:2: Prefer useEffect cleanup\n\
+             frontend/react.mdc:4: Avoid USEEFFECT for derived state"
+        );
+        assert_eq!(render(&[], "nothing"), "No rules match \"nothing\"");
+        assert_eq!(
+            serde_json::to_value(&all[0]).unwrap(),
+            serde_json::json!({"path": "hooks/effects.mdc"})
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Rule inventory summary for `cursor-rules stats`.
+//!
+//! Everything is aggregated from the flat repository tree, using the same
+//! [`NodeKind`] classification as `list` and the browser.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::github::{NodeKind, RepoNode};
+
+/// Number of directories listed under "top directories"
+const TOP_DIRECTORIES: usize = 5;
+
+/// Counts over a repository tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RepoStats {
+    pub directories: usize,
+    /// `.mdc` rule files
+    pub rule_files: usize,
+    pub manifests: usize,
+    /// Files that are neither rules nor manifests, e.g. a README
+    pub other_files: usize,
+    /// Combined size of every file, when GitHub reported all of them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+    /// Directories holding the most rule files directly, most first
+    pub top_directories: Vec<DirectoryRules>,
+}
+
+/// Rule files directly inside one directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DirectoryRules {
+    /// Directory path, `.` for the repository root
+    pub path: String,
+    pub rule_files: usize,
+}
+
+/// Aggregate `nodes`, a flat list of every entry in the tree
+pub fn compute<'a>(nodes: impl IntoIterator<Item = &'a RepoNode>) -> RepoStats {
+    let mut stats = RepoStats {
+        directories: 0,
+        rule_files: 0,
+        manifests: 0,
+        other_files: 0,
+        total_bytes: Some(0),
+        top_directories: Vec::new(),
+    };
+    let mut rules_per_dir: HashMap<&str, usize> = HashMap::new();
+
+    for node in nodes {
+        match node.kind {
+            NodeKind::Dir => {
+                stats.directories += 1;
+                continue;
+            }
+            NodeKind::Manifest => stats.manifests += 1,
+            NodeKind::RuleFile if node.path.ends_with(".mdc") => {
+                stats.rule_files += 1;
+                let dir = node.path.rfind('/').map_or(".", |pos| &node.path[..pos]);
+                *rules_per_dir.entry(dir).or_default() += 1;
+            }
+            NodeKind::RuleFile => stats.other_files += 1,
+        }
+        stats.total_bytes = stats.total_bytes.zip(node.size).map(|(a, b)| a + b);
+    }
+
+    let mut top: Vec<DirectoryRules> = rules_per_dir
+        .into_iter()
+        .map(|(path, rule_files)| DirectoryRules {
+            path: path.to_string(),
+            rule_files,
+        })
+        .collect();
+    top.sort_by(|a, b| b.rule_files.cmp(&a.rule_files).then(a.path.cmp(&b.path)));
+    top.truncate(TOP_DIRECTORIES);
+    stats.top_directories = top;
+    stats
+}
+
+/// Human-readable summary
+pub fn render(stats: &RepoStats) -> String {
+    let mut lines = vec![
+        format!("Directories: {}", stats.directories),
+        format!("Rule files:  {}", stats.rule_files),
+        format!("Manifests:   {}", stats.manifests),
+        format!("Other files: {}", stats.other_files),
+    ];
+    if let Some(bytes) = stats.total_bytes {
+        lines.push(format!("Total size:  {}", format_bytes(bytes)));
+    }
+    if !stats.top_directories.is_empty() {
+        lines.push(String::new());
+        lines.push("Top directories by rule count:".to_string());
+        for dir in &stats.top_directories {
+            lines.push(format!("  {:>4}  {}", dir.rule_files, dir.path));
+        }
+    }
+    lines.join("\n")
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(path: &str, kind: NodeKind, size: Option<u64>) -> RepoNode {
+        RepoNode {
+            name: path.rsplit('/').next().unwrap().into(),
+            path: path.into(),
+            kind,
+            children: None,
+            manifest_count: None,
+            blob_sha: None,
+            size,
+        }
+    }
+
+    fn seeded() -> Vec<RepoNode> {
+        vec![
+            node("frontend", NodeKind::Dir, None),
+            node("frontend/react.mdc", NodeKind::RuleFile, Some(1000)),
+            node("frontend/vue.mdc", NodeKind::RuleFile, Some(500)),
+            node("backend", NodeKind::Dir, None),
+            node("backend/rust.mdc", NodeKind::RuleFile, Some(200)),
+            node("quick-add", NodeKind::Dir, None),
+            node("quick-add/web.txt", NodeKind::Manifest, Some(40)),
+            node("general.mdc", NodeKind::RuleFile, Some(300)),
+            node("README.md", NodeKind::RuleFile, Some(108)),
+        ]
+    }
+
+    #[test]
+    fn counts_kinds_sizes_and_busiest_directories() {
+        let stats = compute(&seeded());
+
+        assert_eq!(stats.directories, 3);
+        assert_eq!(stats.rule_files, 4);
+        assert_eq!(stats.manifests, 1);
+        assert_eq!(stats.other_files, 1);
+        assert_eq!(stats.total_bytes, Some(2148));
+        assert_eq!(
+            stats.top_directories,
+            [
+                DirectoryRules {
+                    path: "frontend".into(),
+                    rule_files: 2
+                },
+                DirectoryRules {
+                    path: ".".into(),
+                    rule_files: 1
+                },
+                DirectoryRules {
+                    path: "backend".into(),
+                    rule_files: 1
+                },
+            ]
+        );
+        assert_eq!(
+            render(&stats),
+            "Directories: 3\nRule files:  4\nManifests:   1\nOther files: 1\nTotal size:  2.1 KB\n\n\
+             Top directories by rule count:\n     2  frontend\n     1  .\n     1  backend"
+        );
+    }
+
+    #[test]
+    fn total_size_is_unknown_when_any_file_lacks_one() {
+        let mut nodes = seeded();
+        nodes.push(node("extra.mdc", NodeKind::RuleFile, None));
+        let stats = compute(&nodes);
+        assert_eq!(stats.total_bytes, None);
+        assert!(!render(&stats).contains("Total size"));
+
+        let empty = compute(&[]);
+        assert_eq!(empty.total_bytes, Some(0));
+        assert!(empty.top_directories.is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+//! Opt-in usage telemetry.
+//!
+//! Events are only recorded when the user has enabled the `telemetry` config key.
+//! Sinks are pluggable through the [`TelemetrySink`] trait; the only sink shipped
+//! today logs events in-process, visible with `--verbose`.
+
+/// Destination for recorded telemetry events
+pub trait TelemetrySink: Send + Sync {
+    /// Record a named event with coarse key/value fields
+    fn record_event(&self, name: &str, fields: &[(&str, String)]);
+}
+
+/// Sink that discards every event
+pub struct NoopSink;
+
+impl TelemetrySink for NoopSink {
+    fn record_event(&self, _name: &str, _fields: &[(&str, String)]) {}
+}
+
+/// Sink that writes events to the debug log
+pub struct LogSink;
+
+impl TelemetrySink for LogSink {
+    fn record_event(&self, name: &str, fields: &[(&str, String)]) {
+        let fields = fields
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        tracing::debug!(target: "telemetry", "{name} {fields}");
+    }
+}
+
+/// Telemetry guard that forwards events to a sink only when the user opted in
+pub struct Telemetry {
+    sink: Box<dyn TelemetrySink>,
+}
+
+impl Telemetry {
+    /// Wrap `sink`, falling back to [`NoopSink`] unless `enabled` is `Some(true)`
+    pub fn new(enabled: Option<bool>, sink: Box<dyn TelemetrySink>) -> Self {
+        let sink = if enabled == Some(true) {
+            sink
+        } else {
+            Box::new(NoopSink)
+        };
+        Self { sink }
+    }
+
+    /// Build telemetry from the `telemetry` config setting, logging events when enabled
+    pub fn from_setting(enabled: Option<bool>) -> Self {
+        Self::new(enabled, Box::new(LogSink))
+    }
+
+    /// Record an event if telemetry is enabled
+    pub fn record(&self, name: &str, fields: &[(&str, String)]) {
+        self.sink.record_event(name, fields);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    type RecordedEvent = (String, Vec<(String, String)>);
+
+    /// Sink that keeps every event for inspection
+    #[derive(Clone, Default)]
+    struct RecordingSink {
+        events: Arc<Mutex<Vec<RecordedEvent>>>,
+    }
+
+    impl TelemetrySink for RecordingSink {
+        fn record_event(&self, name: &str, fields: &[(&str, String)]) {
+            let fields = fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect();
+            self.events.lock().unwrap().push((name.to_string(), fields));
+        }
+    }
+
+    #[test]
+    fn disabled_telemetry_records_nothing() {
+        for setting in [None, Some(false)] {
+            let sink = RecordingSink::default();
+            let telemetry = Telemetry::new(setting, Box::new(sink.clone()));
+            telemetry.record("quick_add_complete", &[("files_copied", "3".into())]);
+            assert!(sink.events.lock().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn enabled_telemetry_forwards_events() {
+        let sink = RecordingSink::default();
+        let telemetry = Telemetry::new(Some(true), Box::new(sink.clone()));
+        telemetry.record("quick_add_complete", &[("files_copied", "3".into())]);
+
+        let events = sink.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].0, "quick_add_complete");
+        assert_eq!(
+            events[0].1,
+            vec![("files_copied".to_string(), "3".to_string())]
+        );
+    }
+}
@@ -1,24 +1,81 @@
 use super::AppAction;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+
+/// Whether `ev` is Ctrl-C or Ctrl-D, which quit the browser from any mode.
+pub fn is_quit_chord(ev: &KeyEvent) -> bool {
+    ev.modifiers.contains(KeyModifiers::CONTROL)
+        && matches!(ev.code, KeyCode::Char('c') | KeyCode::Char('d'))
+}
 
 /// Convert a raw `KeyEvent` from crossterm into a high-level [`AppAction`].
 /// Returns `None` for keys that are not handled by the UI.
 pub fn key_event_to_action(ev: &KeyEvent) -> Option<AppAction> {
     use KeyCode::*;
+    if is_quit_chord(ev) {
+        return Some(AppAction::Quit);
+    }
+    if ev.modifiers.contains(KeyModifiers::CONTROL) {
+        // Other Ctrl chords must not trigger the plain-letter actions
+        return None;
+    }
     match ev.code {
         Char('q') => Some(AppAction::Quit),
         Up | Char('k') => Some(AppAction::Up),
         Down | Char('j') => Some(AppAction::Down),
+        PageUp => Some(AppAction::PageUp),
+        PageDown => Some(AppAction::PageDown),
+        Home | Char('g') => Some(AppAction::Top),
+        End | Char('G') => Some(AppAction::Bottom),
         Left | Char('h') => Some(AppAction::Left),
         Right | Char('l') => Some(AppAction::Right),
         Enter | Char('\r') => Some(AppAction::Select),
         Char(' ') => Some(AppAction::ToggleMark),
+        Char('c') => Some(AppAction::CopyMarked),
+        Char('/') => Some(AppAction::StartFilter),
+        Char('o') => Some(AppAction::SwitchRepo),
+        Char('r') => Some(AppAction::Retry),
+        Char('O') => Some(AppAction::OpenOnGitHub),
         Char('?') => Some(AppAction::Help),
         _ => None,
     }
 }
 
+/// Keystrokes captured while the filter box is active
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterInput {
+    /// Append a character to the query
+    Char(char),
+    /// Remove the last character of the query
+    Backspace,
+    /// Keep the query and return to navigation
+    Apply,
+    /// Clear the query and return to navigation
+    Clear,
+}
+
+/// Convert a raw `KeyEvent` into a [`FilterInput`] while the filter box is active.
+pub fn key_event_to_filter_input(ev: &KeyEvent) -> Option<FilterInput> {
+    match ev.code {
+        KeyCode::Enter => Some(FilterInput::Apply),
+        KeyCode::Esc => Some(FilterInput::Clear),
+        KeyCode::Backspace => Some(FilterInput::Backspace),
+        KeyCode::Char(c) => Some(FilterInput::Char(c)),
+        _ => None,
+    }
+}
+
+/// Convert a raw `KeyEvent` into an answer for a confirmation prompt: `Some(true)` to go
+/// ahead, `Some(false)` to cancel.
+pub fn key_event_to_confirm(ev: &KeyEvent) -> Option<bool> {
+    match ev.code {
+        KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => Some(true),
+        KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Char('q') => Some(false),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -31,11 +88,20 @@ mod tests {
             (KeyCode::Char('k'), AppAction::Up),
             (KeyCode::Down, AppAction::Down),
             (KeyCode::Char('j'), AppAction::Down),
+            (KeyCode::PageUp, AppAction::PageUp),
+            (KeyCode::PageDown, AppAction::PageDown),
+            (KeyCode::Home, AppAction::Top),
+            (KeyCode::End, AppAction::Bottom),
             (KeyCode::Left, AppAction::Left),
             (KeyCode::Char('h'), AppAction::Left),
             (KeyCode::Right, AppAction::Right),
             (KeyCode::Char('l'), AppAction::Right),
             (KeyCode::Char(' '), AppAction::ToggleMark),
+            (KeyCode::Char('c'), AppAction::CopyMarked),
+            (KeyCode::Char('/'), AppAction::StartFilter),
+            (KeyCode::Char('o'), AppAction::SwitchRepo),
+            (KeyCode::Char('r'), AppAction::Retry),
+            (KeyCode::Char('O'), AppAction::OpenOnGitHub),
         ];
 
         for (code, expected) in cases {
@@ -43,4 +109,55 @@ mod tests {
             assert_eq!(key_event_to_action(&ev), Some(expected));
         }
     }
+
+    #[test]
+    fn ctrl_c_and_ctrl_d_quit() {
+        for code in [KeyCode::Char('c'), KeyCode::Char('d')] {
+            let ev = KeyEvent::new(code, KeyModifiers::CONTROL);
+            assert!(is_quit_chord(&ev));
+            assert_eq!(key_event_to_action(&ev), Some(AppAction::Quit));
+        }
+
+        // Without Ctrl, `c` is still the copy action and `d` does nothing
+        let plain_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE);
+        assert!(!is_quit_chord(&plain_c));
+        assert_eq!(key_event_to_action(&plain_c), Some(AppAction::CopyMarked));
+        let plain_d = KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE);
+        assert_eq!(key_event_to_action(&plain_d), None);
+
+        // Other Ctrl chords are ignored rather than treated as letters
+        let ctrl_j = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::CONTROL);
+        assert_eq!(key_event_to_action(&ctrl_j), None);
+    }
+
+    #[test]
+    fn filter_keys_map_correctly() {
+        let cases = vec![
+            (KeyCode::Char('q'), FilterInput::Char('q')),
+            (KeyCode::Backspace, FilterInput::Backspace),
+            (KeyCode::Enter, FilterInput::Apply),
+            (KeyCode::Esc, FilterInput::Clear),
+        ];
+
+        for (code, expected) in cases {
+            let ev = KeyEvent::new(code, KeyModifiers::NONE);
+            assert_eq!(key_event_to_filter_input(&ev), Some(expected));
+        }
+    }
+
+    #[test]
+    fn confirm_keys_map_correctly() {
+        let cases = vec![
+            (KeyCode::Enter, Some(true)),
+            (KeyCode::Char('y'), Some(true)),
+            (KeyCode::Esc, Some(false)),
+            (KeyCode::Char('n'), Some(false)),
+            (KeyCode::Char('x'), None),
+        ];
+
+        for (code, expected) in cases {
+            let ev = KeyEvent::new(code, KeyModifiers::NONE);
+            assert_eq!(key_event_to_confirm(&ev), expected);
+        }
+    }
 }
@@ -5,28 +5,50 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::io::stdout;
+use std::pin::Pin;
 use std::time::{Duration, Instant};
 
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 pub mod inputs;
+pub mod plain;
+pub mod prefetch;
 pub mod prompts;
 pub mod theme;
 pub mod viewport;
 
-use crate::github::{RepoLocator, RepoTree};
+use crate::github::{NodeKind, RepoLocator, RepoNode, RepoTree};
+
+/// Number of lines shown in the preview pane
+const PREVIEW_LINES: usize = 40;
+
+/// How long the selection must rest on a file before its preview is fetched
+const PREVIEW_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// How long a load must run before the spinner appears, so cache hits never flash it
+const SPINNER_DELAY: Duration = Duration::from_millis(150);
 
 /// High-level actions emitted by the UI layer and handled by the application controller.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppAction {
     Up,
     Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
     Left,
     Right,
     Select,
     ToggleMark,
+    CopyMarked,
+    StartFilter,
+    SwitchRepo,
+    Retry,
+    OpenOnGitHub,
     Help,
     Quit,
 }
@@ -38,6 +60,47 @@ pub enum AppMessage {
         #[allow(dead_code)]
         path: String,
     },
+    /// Copy several marked rule files in one batch
+    CopyBatch { paths: Vec<String> },
+    /// The browser switched to another repository; later copies come from it
+    RepoChanged { locator: RepoLocator },
+}
+
+/// Resolves the `owner/repo` typed into the switch-repo box. Tests substitute a stub so
+/// no request is made.
+type ResolveRepo = fn(String) -> Pin<Box<dyn Future<Output = Result<RepoLocator>> + Send>>;
+
+/// Resolve `input` the way `--owner` is: `owner/repo`, a GitHub URL, or an owner alone
+/// for their `cursor-rules` repo
+fn resolve_typed_repo(input: String) -> Pin<Box<dyn Future<Output = Result<RepoLocator>> + Send>> {
+    Box::pin(async move {
+        let token = crate::github::client::token().map(str::to_string);
+        Ok(crate::github::resolve_repo(Some(input), None, None, token).await?)
+    })
+}
+
+/// Content of the preview pane for a rule file or manifest, keyed by path in [`AppState`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Preview {
+    Loading,
+    Ready(String),
+    Unavailable,
+}
+
+/// Rules listed by a manifest, as far as they have been downloaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ManifestEntries {
+    Loading,
+    Loaded(Vec<String>),
+    Failed,
+}
+
+/// A directory listing being fetched in the background. The tree moves into the task and
+/// comes back with the listing.
+struct PendingLoad {
+    locator: RepoLocator,
+    dir_path: String,
+    task: tokio::task::JoinHandle<(RepoTree, Result<Vec<RepoNode>>)>,
 }
 
 struct AppState {
@@ -47,19 +110,40 @@ struct AppState {
     items: Vec<crate::github::RepoNode>,
     viewport: viewport::Viewport,
     breadcrumb: String,
-    marked: HashSet<usize>,
+    marked: HashSet<String>, // node paths
+    filter: String,
+    filter_input: bool, // capturing keystrokes into `filter`
     show_help: bool,
-    loading: bool,
-    last_tick: Instant,
+    loading_since: Option<Instant>, // when the load in progress began
+    load: Option<PendingLoad>,      // directory listing being fetched
+    load_failed: bool,              // the current directory failed to load; only `r` retries
     error: Option<String>,
     show_hidden: bool,
     tx: UnboundedSender<AppMessage>,
+    previews: HashMap<String, Preview>, // node path -> preview
+    preview_tx: UnboundedSender<(String, Preview)>,
+    preview_rx: UnboundedReceiver<(String, Preview)>,
+    selected_path: Option<String>,
+    selected_since: Instant,
+    manifests: HashMap<String, ManifestEntries>, // manifest path -> listed rules
+    manifest_tx: UnboundedSender<(String, Option<Vec<String>>)>,
+    manifest_rx: UnboundedReceiver<(String, Option<Vec<String>>)>,
+    confirm_manifests: bool, // ask before copying a manifest (off with --force)
+    confirm: Option<String>, // manifest path awaiting confirmation
+    palette: theme::Palette,
+    use_cache: bool, // read and write downloaded rules through the on-disk blob cache
+    refresh: bool,   // bypass the cached tree on the next directory load (--refresh)
+    repo_input: Option<String>, // `owner/repo` being typed into the switch-repo box
+    resolve_repo: ResolveRepo,
+    prefetch: prefetch::Prefetcher, // caches the rules of the directory on screen
 }
 
 impl AppState {
     fn new(repo: &RepoLocator, show_hidden: bool, tx: UnboundedSender<AppMessage>) -> Self {
-        let tree = RepoTree::new();
+        let tree = RepoTree::new().lazy();
         let items = Vec::new();
+        let (preview_tx, preview_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (manifest_tx, manifest_rx) = tokio::sync::mpsc::unbounded_channel();
         Self {
             locator: repo.clone(),
             tree,
@@ -68,22 +152,543 @@ impl AppState {
             viewport: viewport::Viewport::new(),
             breadcrumb: format!("{}/{}", repo.owner, repo.repo),
             marked: HashSet::new(),
+            filter: String::new(),
+            filter_input: false,
             show_help: false,
-            loading: false,
-            last_tick: Instant::now(),
+            loading_since: None,
+            load: None,
+            load_failed: false,
             error: None,
             show_hidden,
             tx,
+            previews: HashMap::new(),
+            preview_tx,
+            preview_rx,
+            selected_path: None,
+            selected_since: Instant::now(),
+            manifests: HashMap::new(),
+            manifest_tx,
+            manifest_rx,
+            confirm_manifests: false,
+            confirm: None,
+            palette: theme::Palette::default(),
+            use_cache: true,
+            refresh: false,
+            repo_input: None,
+            resolve_repo: resolve_typed_repo,
+            prefetch: prefetch::Prefetcher::default(),
+        }
+    }
+
+    /// Start loading the children of the current directory in the background, so frames
+    /// (and the spinner) keep being drawn while GitHub is slow. A pending `--refresh`
+    /// applies to the first load only; later directories are read from the tree fetched in
+    /// this session.
+    fn start_load(&mut self) {
+        let mut tree = std::mem::take(&mut self.tree);
+        let locator = self.locator.clone();
+        let dir_path = self.dir_path.clone();
+        let force_refresh = std::mem::take(&mut self.refresh);
+        let task = tokio::spawn({
+            let locator = locator.clone();
+            let dir_path = dir_path.clone();
+            async move {
+                let children = tree
+                    .children(&locator, &dir_path, force_refresh)
+                    .await
+                    .map(<[RepoNode]>::to_vec);
+                (tree, children)
+            }
+        });
+        self.loading_since = Some(Instant::now());
+        self.load = Some(PendingLoad {
+            locator,
+            dir_path,
+            task,
+        });
+    }
+
+    /// Apply the background load if it has finished since the last frame
+    async fn receive_load(&mut self) {
+        if self
+            .load
+            .as_ref()
+            .is_some_and(|load| load.task.is_finished())
+        {
+            self.finish_load().await;
+        }
+    }
+
+    /// Wait for the background load and show its listing, honouring the hidden-file filter.
+    /// A load for a directory or repository left in the meantime only hands the tree back.
+    async fn finish_load(&mut self) {
+        let Some(load) = self.load.take() else {
+            return;
+        };
+        self.loading_since = None;
+        let (tree, children) = match load.task.await {
+            Ok(finished) => finished,
+            Err(e) => {
+                self.error = Some(format!("Fetch error: {e}"));
+                self.load_failed = true;
+                return;
+            }
+        };
+        self.tree = tree;
+        if load.locator != self.locator {
+            self.tree.clear();
+            return;
+        }
+        if load.dir_path != self.dir_path {
+            return;
+        }
+        match children {
+            Ok(children) => {
+                self.items = children
+                    .into_iter()
+                    .filter(|n| crate::github::should_show(n, self.show_hidden))
+                    .collect();
+                self.spawn_manifest_counts();
+                if self.use_cache {
+                    self.prefetch.start(&self.locator, &self.items);
+                }
+            }
+            Err(e) => {
+                self.error = Some(format!("Fetch error: {e}"));
+                self.load_failed = true;
+            }
+        }
+    }
+
+    /// Whether the current directory still has to be loaded. After a failure nothing is
+    /// fetched again until [`AppState::retry_load`], even once the error is dismissed.
+    fn needs_load(&self) -> bool {
+        self.items.is_empty() && self.load.is_none() && !self.load_failed
+    }
+
+    /// Load the children of the current directory and wait for them
+    async fn load_items(&mut self) {
+        self.start_load();
+        self.finish_load().await;
+    }
+
+    /// Load the current directory again after a failure, clearing the error banner. A
+    /// directory that failed to load was never cached, so it is fetched afresh. Does
+    /// nothing unless the last load failed.
+    fn retry_load(&mut self) {
+        if !std::mem::take(&mut self.load_failed) {
+            return;
+        }
+        self.error = None;
+        self.items.clear();
+        if self.load.is_none() {
+            self.start_load();
+        }
+    }
+
+    /// Whether the loading spinner should be drawn: only once a load has run for
+    /// [`SPINNER_DELAY`]
+    fn spinner_due(&self, now: Instant) -> bool {
+        self.loading_since
+            .is_some_and(|since| now.duration_since(since) >= SPINNER_DELAY)
+    }
+
+    /// Fit the viewport to a terminal resized to `width` x `height`, returning the new
+    /// height of the item list so paging uses it before the next frame is drawn
+    fn resize(&mut self, width: u16, height: u16) -> usize {
+        let list_height = screen_chunks(ratatui::layout::Rect::new(0, 0, width, height))[1].height;
+        let list_height = usize::from(list_height).max(1);
+        self.viewport.ensure_visible(list_height);
+        list_height
+    }
+
+    /// Count the rules in each uncounted quick-add manifest of the current listing in the
+    /// background, so the `[N files]` bubble fills in without blocking the render loop.
+    fn spawn_manifest_counts(&mut self) {
+        let pending: Vec<RepoNode> = self
+            .items
+            .iter()
+            .filter(|node| {
+                node.kind == NodeKind::Manifest
+                    && node.manifest_count.is_none()
+                    && is_in_manifest_dir(&node.path, self.tree.manifest_dir())
+            })
+            .cloned()
+            .collect();
+
+        for node in pending {
+            self.spawn_manifest_entries(node);
+        }
+    }
+
+    /// Download and parse `node` in the background unless it was already requested; the
+    /// entries arrive on `manifest_rx`, or `None` when the manifest could not be fetched.
+    fn spawn_manifest_entries(&mut self, node: RepoNode) {
+        if self.manifests.contains_key(&node.path) {
+            return;
+        }
+        self.manifests
+            .insert(node.path.clone(), ManifestEntries::Loading);
+        let locator = self.locator.clone();
+        let tx = self.manifest_tx.clone();
+        let use_cache = self.use_cache;
+        tokio::spawn(async move {
+            let octocrab = octocrab::instance();
+            let entries = crate::copier::fetch_file_content(
+                &octocrab,
+                &locator,
+                &node.path,
+                node.blob_sha.as_deref(),
+                false,
+                use_cache,
+            )
+            .await
+            .ok()
+            .map(|bytes| manifest_entries(&node.name, &String::from_utf8_lossy(&bytes)));
+            let _ = tx.send((node.path, entries));
+        });
+    }
+
+    /// Apply any manifest entries that finished since the last frame. Counts go to the
+    /// listing and the tree, so they survive leaving and re-entering the directory.
+    fn receive_manifest_counts(&mut self) {
+        while let Ok((path, entries)) = self.manifest_rx.try_recv() {
+            let Some(entries) = entries else {
+                self.manifests.insert(path, ManifestEntries::Failed);
+                continue;
+            };
+            let count = entries.len();
+            self.tree.set_manifest_count(&path, count);
+            if let Some(node) = self.items.iter_mut().find(|node| node.path == path) {
+                node.manifest_count = Some(count);
+            }
+            self.manifests
+                .insert(path, ManifestEntries::Loaded(entries));
+        }
+    }
+
+    /// Items in the current directory that match the active filter.
+    fn visible_items(&self) -> Vec<&crate::github::RepoNode> {
+        self.items
+            .iter()
+            .filter(|node| matches_filter(node, &self.filter))
+            .collect()
+    }
+
+    /// Keep the selection inside the visible (filtered) list.
+    fn clamp_selection(&mut self) {
+        let total = self.visible_items().len();
+        if total == 0 {
+            self.viewport.selected_index = 0;
+        } else if self.viewport.selected_index >= total {
+            self.viewport.selected_index = total - 1;
+        }
+        self.viewport.scroll_offset = self
+            .viewport
+            .scroll_offset
+            .min(self.viewport.selected_index);
+    }
+
+    /// Open the visible item at `index`: enter it if it is a directory, otherwise request a copy.
+    fn open(&mut self, index: usize) {
+        let Some(node) = self.visible_items().get(index).map(|node| (*node).clone()) else {
+            return;
+        };
+
+        if node.is_dir() {
+            // Enter directory
+            self.dir_path = if self.dir_path.is_empty() {
+                node.path
+            } else {
+                format!("{}/{}", self.dir_path, node.name)
+            };
+            self.viewport = viewport::Viewport::new();
+            self.items.clear();
+            self.filter.clear();
+            self.load_failed = false;
+        } else if node.kind == NodeKind::Manifest && self.confirm_manifests {
+            // Show what the manifest will copy before doing it
+            self.confirm = Some(node.path.clone());
+            self.spawn_manifest_entries(node);
+        } else {
+            // file or manifest selection triggers copy request event
+            let _ = self.tx.send(AppMessage::CopyRequest { path: node.path });
+        }
+    }
+
+    /// Answer the pending manifest confirmation: copy it when `accepted`, otherwise drop it.
+    fn resolve_confirm(&mut self, accepted: bool) {
+        if let Some(path) = self.confirm.take() {
+            if accepted {
+                let _ = self.tx.send(AppMessage::CopyRequest { path });
+            }
+        }
+    }
+
+    /// Body of the confirmation modal for the pending manifest
+    fn confirm_text(&self) -> Option<String> {
+        let path = self.confirm.as_ref()?;
+        let mut text = format!("Copy the rules listed in {path}?\n\n");
+        match self.manifests.get(path) {
+            Some(ManifestEntries::Loaded(entries)) => {
+                text.push_str(&manifest_summary(entries.len(), entries));
+            }
+            Some(ManifestEntries::Failed) => text.push_str("Could not load the manifest entries"),
+            Some(ManifestEntries::Loading) | None => text.push_str("Loading entries…"),
+        }
+        text.push_str("\n\nEnter copy  Esc cancel");
+        Some(text)
+    }
+
+    /// Navigate to the parent directory. Returns `false` when already at the root.
+    fn go_up(&mut self) -> bool {
+        if self.dir_path.is_empty() {
+            return false;
+        }
+        if let Some(pos) = self.dir_path.rfind('/') {
+            self.dir_path.truncate(pos);
+        } else {
+            self.dir_path.clear();
+        }
+        self.viewport = viewport::Viewport::new();
+        self.items.clear();
+        self.filter.clear();
+        self.load_failed = false;
+        true
+    }
+
+    /// Open the selected entry's github.com page in the default browser, or the current
+    /// directory's when nothing is selected. Without a browser the URL is shown instead.
+    fn open_selected_on_github(&mut self) {
+        let url = match self.selected_node() {
+            Some(node) => self.locator.web_url(&node.path, node.is_dir()),
+            None => self.locator.web_url(&self.dir_path, true),
+        };
+        if let Err(e) = open_in_browser(&url) {
+            self.error = Some(format!("{e}; open {url} yourself"));
+        }
+    }
+
+    /// Toggle the mark on the visible item at `index`. Marks are keyed by node path.
+    fn toggle_mark(&mut self, index: usize) {
+        let Some(path) = self
+            .visible_items()
+            .get(index)
+            .map(|node| node.path.clone())
+        else {
+            return;
+        };
+        if !self.marked.insert(path.clone()) {
+            self.marked.remove(&path);
+        }
+    }
+
+    /// Apply a keystroke captured while the filter box is active.
+    fn handle_filter_input(&mut self, input: inputs::FilterInput) {
+        match input {
+            inputs::FilterInput::Char(c) => self.filter.push(c),
+            inputs::FilterInput::Backspace => {
+                self.filter.pop();
+            }
+            inputs::FilterInput::Apply => self.filter_input = false,
+            inputs::FilterInput::Clear => {
+                self.filter.clear();
+                self.filter_input = false;
+            }
+        }
+        self.clamp_selection();
+    }
+
+    /// Apply a keystroke captured while the switch-repo box is open. Enter resolves the
+    /// typed repository and opens it; a failure is shown in the error banner and the
+    /// current repository stays open.
+    async fn handle_repo_input(&mut self, input: inputs::FilterInput) {
+        let Some(typed) = self.repo_input.as_mut() else {
+            return;
+        };
+        match input {
+            inputs::FilterInput::Char(c) => typed.push(c),
+            inputs::FilterInput::Backspace => {
+                typed.pop();
+            }
+            inputs::FilterInput::Clear => self.repo_input = None,
+            inputs::FilterInput::Apply => {
+                let typed = self.repo_input.take().unwrap_or_default();
+                let typed = typed.trim();
+                if typed.is_empty() {
+                    return;
+                }
+                self.loading_since = Some(Instant::now());
+                let resolved = (self.resolve_repo)(typed.to_string()).await;
+                self.loading_since = None;
+                match resolved {
+                    Ok(locator) => self.switch_repo(locator),
+                    Err(e) => self.error = Some(format!("Cannot open {typed}: {e}")),
+                }
+            }
+        }
+    }
+
+    /// Browse `locator` from its root, dropping everything loaded for the previous one
+    fn switch_repo(&mut self, locator: RepoLocator) {
+        self.prefetch.cancel();
+        self.tree.clear();
+        self.breadcrumb = format!("{}/{}", locator.owner, locator.repo);
+        self.dir_path.clear();
+        self.items.clear();
+        self.viewport = viewport::Viewport::new();
+        self.marked.clear();
+        self.filter.clear();
+        self.previews.clear();
+        self.selected_path = None;
+        self.manifests.clear();
+        self.error = None;
+        self.load_failed = false;
+        self.locator = locator.clone();
+        let _ = self.tx.send(AppMessage::RepoChanged { locator });
+    }
+
+    /// Whether `node` is currently marked
+    fn is_marked(&self, node: &crate::github::RepoNode) -> bool {
+        self.marked.contains(&node.path)
+    }
+
+    /// Send a single batch copy request for every marked `.mdc` file and clear the marks.
+    /// Returns the number of files in the batch.
+    fn copy_marked(&mut self) -> usize {
+        let mut paths: Vec<String> = self
+            .marked
+            .drain()
+            .filter(|path| path.ends_with(".mdc"))
+            .collect();
+        paths.sort();
+
+        let count = paths.len();
+        if count > 0 {
+            let _ = self.tx.send(AppMessage::CopyBatch { paths });
+        }
+        count
+    }
+
+    /// The node under the cursor in the visible list
+    fn selected_node(&self) -> Option<&RepoNode> {
+        self.visible_items()
+            .get(self.viewport.selected_index)
+            .copied()
+    }
+
+    /// Restart the debounce timer whenever the cursor lands on a different node.
+    fn track_selection(&mut self, now: Instant) {
+        let path = self.selected_node().map(|node| node.path.clone());
+        if path != self.selected_path {
+            self.selected_path = path;
+            self.selected_since = now;
+        }
+    }
+
+    /// Return the selected file if its preview should be fetched now, marking it as loading.
+    /// Files are only fetched once the selection has rested for [`PREVIEW_DEBOUNCE`].
+    fn take_due_preview(&mut self, now: Instant) -> Option<RepoNode> {
+        if now.duration_since(self.selected_since) < PREVIEW_DEBOUNCE {
+            return None;
+        }
+        let node = self.selected_node()?.clone();
+        if node.is_dir() || self.previews.contains_key(&node.path) {
+            return None;
+        }
+        // A known manifest count is enough for the summary, no download needed
+        if let (NodeKind::Manifest, Some(count)) = (&node.kind, node.manifest_count) {
+            self.previews.insert(
+                node.path.clone(),
+                Preview::Ready(manifest_summary(count, &[])),
+            );
+            return None;
+        }
+        self.previews.insert(node.path.clone(), Preview::Loading);
+        Some(node)
+    }
+
+    /// Download the preview for `node` in the background; the result arrives on `preview_rx`.
+    fn spawn_preview(&self, node: RepoNode) {
+        let locator = self.locator.clone();
+        let tx = self.preview_tx.clone();
+        let use_cache = self.use_cache;
+        tokio::spawn(async move {
+            let octocrab = octocrab::instance();
+            let preview = match crate::copier::fetch_file_content(
+                &octocrab,
+                &locator,
+                &node.path,
+                node.blob_sha.as_deref(),
+                false,
+                use_cache,
+            )
+            .await
+            {
+                Ok(bytes) => {
+                    Preview::Ready(render_preview(&node, &String::from_utf8_lossy(&bytes)))
+                }
+                Err(_) => Preview::Unavailable,
+            };
+            let _ = tx.send((node.path, preview));
+        });
+    }
+
+    /// Store any previews that finished downloading since the last frame.
+    fn receive_previews(&mut self) {
+        while let Ok((path, preview)) = self.preview_rx.try_recv() {
+            self.previews.insert(path, preview);
+        }
+    }
+
+    /// Text shown in the preview pane for the current selection.
+    fn preview_text(&self) -> String {
+        let Some(node) = self.selected_node() else {
+            return String::new();
+        };
+        if node.is_dir() {
+            // Directories are fetched lazily, so an unvisited one has no count yet
+            return match self.tree.cached_children(&node.path) {
+                Some(children) => {
+                    let count = children
+                        .iter()
+                        .filter(|n| crate::github::should_show(n, self.show_hidden))
+                        .count();
+                    format!("Directory with {count} item(s)")
+                }
+                None => "Directory".to_string(),
+            };
+        }
+        match self.previews.get(&node.path) {
+            Some(Preview::Ready(text)) => text.clone(),
+            Some(Preview::Unavailable) => "preview unavailable".to_string(),
+            Some(Preview::Loading) | None => "Loading preview…".to_string(),
         }
     }
 }
 
+/// Settings the interactive browser starts with
+#[derive(Debug, Clone, Copy)]
+pub struct BrowserOptions {
+    /// List dotfiles and dot-directories (`--all`)
+    pub show_hidden: bool,
+    /// Ask before applying a manifest (off with `--force`)
+    pub confirm_manifests: bool,
+    pub palette: theme::Palette,
+    /// Read previews and manifests through the on-disk blob cache
+    pub use_cache: bool,
+    /// Bypass the cached tree for the initial load
+    pub refresh: bool,
+}
+
 /// Launch the interactive browser UI. This is a blocking call that returns when the user exits.
-/// For now it is a stub that renders a placeholder screen and quits on `q` / Ctrl-C.
+/// Directories are read from `tree`, which is switched to lazy loading; pass a tree backed by
+/// the persistent cache to reuse a tree already on disk.
 pub async fn run(
     _repo: &RepoLocator,
+    tree: RepoTree,
     tx: UnboundedSender<AppMessage>,
-    show_hidden: bool,
+    options: BrowserOptions,
 ) -> Result<()> {
     // 1. Enter alternate screen + raw mode
     enable_raw_mode()?;
@@ -95,7 +700,12 @@ pub async fn run(
     let mut terminal = Terminal::new(backend)?;
 
     // 3. Event loop
-    let mut app = AppState::new(_repo, show_hidden, tx);
+    let mut app = AppState::new(_repo, options.show_hidden, tx);
+    app.confirm_manifests = options.confirm_manifests;
+    app.palette = options.palette;
+    app.tree = tree.lazy();
+    app.use_cache = options.use_cache;
+    app.refresh = options.refresh;
     let res = run_app(&mut terminal, &mut app).await;
 
     // 4. Restore terminal state no matter what
@@ -110,173 +720,348 @@ async fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut AppState,
 ) -> Result<()> {
-    use ratatui::layout::{Constraint, Direction, Layout};
-    use ratatui::style::{Modifier, Style};
-    use ratatui::text::{Line, Span};
-    use ratatui::widgets::{Block, Borders, Paragraph};
+    // Height of the item list in the last frame, used as the page size
+    let mut page_height = 1;
 
     loop {
-        // Ensure children loaded for current dir
-        if app.items.is_empty() {
-            app.loading = true;
-            match app.tree.children(&app.locator, &app.dir_path, false).await {
-                Ok(children) => {
-                    app.items = children
-                        .iter()
-                        .filter(|n| app.show_hidden || !n.name.starts_with('.'))
-                        .cloned()
-                        .collect()
-                }
-                Err(e) => {
-                    app.error = Some(format!("Fetch error: {e}"));
-                }
-            }
-            app.loading = false;
+        // Ensure children loaded for current dir; after a failure wait for a retry
+        // instead of refetching on every pass
+        if app.needs_load() {
+            app.start_load();
         }
+        app.receive_load().await;
 
-        // 1. Draw UI
-        terminal.draw(|f| {
-            let size = f.area();
-
-            // Split layout: breadcrumb (1 line), main list (rest -1), footer (1 line)
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(1),
-                    Constraint::Min(1),
-                    Constraint::Length(1),
-                ])
-                .split(size);
-
-            // Breadcrumb bar
-            let bc = Paragraph::new(Line::from(vec![Span::styled(
-                app.breadcrumb.clone(),
-                Style::default().fg(theme::Palette::BREADCRUMB),
-            )]));
-            f.render_widget(bc, chunks[0]);
-
-            // Determine visible items based on viewport
-            let list_height = chunks[1].height as usize;
-            // Ensure selected index visible
-            app.viewport.ensure_visible(list_height);
-
-            let start = app.viewport.scroll_offset;
-            let end = usize::min(start + list_height, app.items.len());
-
-            let mut styled_lines: Vec<Line> = Vec::with_capacity(end - start);
-            for (idx, node) in app.items[start..end].iter().enumerate() {
-                let absolute_idx = start + idx;
-                if absolute_idx == app.viewport.selected_index {
-                    styled_lines.push(Line::from(Span::styled(
-                        format!("{} {}{}", icon_for(node), node.name, bubble(node)),
-                        Style::default()
-                            .fg(theme::Palette::SELECTED_FG)
-                            .bg(theme::Palette::SELECTED_BG)
-                            .add_modifier(Modifier::BOLD),
-                    )));
-                } else {
-                    styled_lines.push(Line::from(Span::styled(
-                        format!("{} {}{}", icon_for(node), node.name, bubble(node)),
-                        Style::default().fg(fg_color(node)),
-                    )));
-                }
-            }
-
-            let list_widget =
-                Paragraph::new(styled_lines).block(Block::default().borders(Borders::NONE));
-            f.render_widget(list_widget, chunks[1]);
-
-            // Footer hints
-            let footer_text = "↑/↓ move → enter ← back q quit ? help";
-            let footer =
-                Paragraph::new(footer_text).style(Style::default().fg(theme::Palette::FOOTER));
-            f.render_widget(footer, chunks[2]);
-
-            // Help modal overlay
-            if app.show_help {
-                let help_text = "Controls:\n\n↑/k down  ↓/j up\n→/l/Enter expand/select\n←/h back\nSpace mark for copy\nq quit  ? help";
-                let area = centered_rect(60, 40, size);
-                let block = Block::default()
-                    .title("Help")
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(theme::Palette::BREADCRUMB));
-                let help = Paragraph::new(help_text).block(block);
-                f.render_widget(help, area);
-            }
-
-            // Loading spinner overlay
-            if app.loading {
-                let spinner_frames = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
-                let idx = ((Instant::now().duration_since(app.last_tick).as_millis() / 100) % 10) as usize;
-                let area = centered_rect(10, 10, size);
-                let spinner = Paragraph::new(spinner_frames[idx]).block(Block::default().borders(Borders::ALL));
-                f.render_widget(spinner, area);
-            }
+        // Refresh the preview pane without blocking navigation
+        let now = Instant::now();
+        app.track_selection(now);
+        app.receive_previews();
+        app.receive_manifest_counts();
+        if let Some(node) = app.take_due_preview(now) {
+            app.spawn_preview(node);
+        }
 
-            // Error banner
-            if let Some(err) = &app.error {
-                let banner = Paragraph::new(err.as_str())
-                    .style(Style::default().fg(ratatui::style::Color::Red).bg(ratatui::style::Color::Black));
-                let area = ratatui::layout::Rect::new(0, size.height.saturating_sub(2), size.width, 1);
-                f.render_widget(banner, area);
-            }
-        })?;
+        // 1. Draw UI
+        terminal.draw(|f| page_height = draw(f, app))?;
 
         // 2. Handle input
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if let Some(action) = inputs::key_event_to_action(&key) {
+        // Check back sooner while a directory is loading so its listing shows promptly
+        let tick = if app.load.is_some() { 20 } else { 100 };
+        if event::poll(Duration::from_millis(tick))? {
+            let event = event::read()?;
+            if let Event::Resize(width, height) = event {
+                // The next pass draws at the new size straight away rather than after
+                // the next key
+                page_height = app.resize(width, height);
+            } else if let Event::Key(key) = event {
+                // Any key dismisses the error banner; `r` also retries the failed load
+                app.error = None;
+                if inputs::is_quit_chord(&key) {
+                    // Quit from any mode; `run` restores the terminal on the way out
+                    break;
+                } else if app.confirm.is_some() {
+                    if let Some(accepted) = inputs::key_event_to_confirm(&key) {
+                        app.resolve_confirm(accepted);
+                    }
+                } else if app.repo_input.is_some() {
+                    if let Some(input) = inputs::key_event_to_filter_input(&key) {
+                        app.handle_repo_input(input).await;
+                    }
+                } else if app.filter_input {
+                    if let Some(input) = inputs::key_event_to_filter_input(&key) {
+                        app.handle_filter_input(input);
+                    }
+                } else if let Some(action) = inputs::key_event_to_action(&key) {
                     match action {
                         AppAction::Quit => break,
                         AppAction::Up => app.viewport.up(),
-                        AppAction::Down => app.viewport.down(app.items.len()),
+                        AppAction::Down => app.viewport.down(app.visible_items().len()),
+                        AppAction::PageUp => app.viewport.page_up(page_height),
+                        AppAction::PageDown => app
+                            .viewport
+                            .page_down(page_height, app.visible_items().len()),
+                        AppAction::Top => app.viewport.scroll_to_top(),
+                        AppAction::Bottom => {
+                            app.viewport.scroll_to_bottom(app.visible_items().len())
+                        }
                         AppAction::Right | AppAction::Select => {
-                            if let Some(node) = app.items.get(app.viewport.selected_index) {
-                                if node.is_dir() {
-                                    // Enter directory
-                                    app.dir_path = if app.dir_path.is_empty() {
-                                        node.path.clone()
-                                    } else {
-                                        format!("{}/{}", app.dir_path, node.name)
-                                    };
-                                    app.viewport = viewport::Viewport::new();
-                                    app.items.clear();
-                                } else {
-                                    // file or manifest selection triggers copy request event
-                                    let _ = app.tx.send(AppMessage::CopyRequest {
-                                        path: node.path.clone(),
-                                    });
-                                }
-                            }
+                            app.open(app.viewport.selected_index);
                         }
                         AppAction::Left => {
-                            if !app.dir_path.is_empty() {
-                                if let Some(pos) = app.dir_path.rfind('/') {
-                                    app.dir_path.truncate(pos);
-                                } else {
-                                    app.dir_path.clear();
-                                }
-                                app.viewport = viewport::Viewport::new();
-                                app.items.clear();
-                            }
+                            app.go_up();
                         }
                         AppAction::ToggleMark => {
-                            let idx = app.viewport.selected_index;
-                            if !app.marked.insert(idx) {
-                                app.marked.remove(&idx);
-                            }
+                            app.toggle_mark(app.viewport.selected_index);
                         }
+                        AppAction::CopyMarked => {
+                            app.copy_marked();
+                        }
+                        AppAction::StartFilter => app.filter_input = true,
+                        AppAction::SwitchRepo => app.repo_input = Some(String::new()),
+                        AppAction::Retry => app.retry_load(),
+                        AppAction::OpenOnGitHub => app.open_selected_on_github(),
                         AppAction::Help => app.show_help = !app.show_help,
                     }
                 }
             }
         }
-        app.last_tick = Instant::now();
     }
 
     Ok(())
 }
 
+/// Draw one frame of the browser, returning the height of the item list so paging uses
+/// the size that was actually drawn
+fn draw(f: &mut ratatui::Frame, app: &mut AppState) -> usize {
+    use ratatui::layout::{Constraint, Direction, Layout};
+    use ratatui::style::Style;
+    use ratatui::text::{Line, Span};
+    use ratatui::widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation};
+
+    let palette = app.palette;
+    let size = f.area();
+
+    let chunks = screen_chunks(size);
+
+    // Breadcrumb bar
+    let bc = Paragraph::new(Line::from(vec![Span::styled(
+        app.breadcrumb.clone(),
+        Style::default().fg(palette.breadcrumb),
+    )]));
+    f.render_widget(bc, chunks[0]);
+
+    // Split the main area: item list on the left, preview pane on the right
+    let body = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+
+    // Determine visible items based on viewport
+    let list_height = body[0].height as usize;
+    // Ensure selected index visible
+    app.viewport.ensure_visible(list_height);
+
+    let visible = app.visible_items();
+    let start = app.viewport.scroll_offset;
+    let end = usize::min(start + list_height, visible.len());
+
+    let mut styled_lines: Vec<Line> = Vec::with_capacity(end.saturating_sub(start));
+    for (idx, node) in visible[start..end].iter().enumerate() {
+        let absolute_idx = start + idx;
+        let mark = if app.is_marked(node) { "✓ " } else { "  " };
+        if absolute_idx == app.viewport.selected_index {
+            styled_lines.push(Line::from(Span::styled(
+                format!("{mark}{} {}{}", icon_for(node), node.name, bubble(node)),
+                Style::default()
+                    .fg(palette.selected_fg)
+                    .bg(palette.selected_bg)
+                    .add_modifier(palette.selected_modifier),
+            )));
+        } else {
+            styled_lines.push(Line::from(Span::styled(
+                format!("{mark}{} {}{}", icon_for(node), node.name, bubble(node)),
+                Style::default().fg(if app.is_marked(node) {
+                    palette.marked
+                } else {
+                    fg_color(node, &palette)
+                }),
+            )));
+        }
+    }
+
+    let list_widget = Paragraph::new(styled_lines).block(Block::default().borders(Borders::NONE));
+    f.render_widget(list_widget, body[0]);
+
+    // Scrollbar along the right edge of the list, only when it overflows
+    if let Some(mut scroll_state) = list_scrollbar(visible.len(), list_height, start) {
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .style(Style::default().fg(palette.footer));
+        f.render_stateful_widget(scrollbar, body[0], &mut scroll_state);
+    }
+    let position = position_label(app.viewport.selected_index, visible.len());
+
+    // Preview pane
+    let preview = Paragraph::new(app.preview_text()).block(
+        Block::default()
+            .title("Preview")
+            .borders(Borders::LEFT)
+            .border_style(Style::default().fg(palette.footer)),
+    );
+    f.render_widget(preview, body[1]);
+
+    // Footer hints
+    let footer_text = if let Some(typed) = &app.repo_input {
+        format!("open repo: {typed}▏  Enter open  Esc cancel")
+    } else if app.filter_input {
+        format!("/{}▏  Enter keep  Esc clear", app.filter)
+    } else if !app.filter.is_empty() {
+        format!(
+            "{position}filter: {}  / edit  space mark c copy ({} marked) q quit ? help",
+            app.filter,
+            app.marked.len()
+        )
+    } else {
+        format!(
+            "{position}↑/↓ move → enter ← back / filter space mark c copy ({} marked) q quit ? help",
+            app.marked.len()
+        )
+    };
+    let footer = Paragraph::new(footer_text).style(Style::default().fg(palette.footer));
+    f.render_widget(footer, chunks[2]);
+
+    // Help modal overlay
+    if app.show_help {
+        let help_text = "Controls:\n\n↑/k down  ↓/j up\nPgUp/PgDn page  Home/End first/last\n→/l/Enter expand/select\n←/h back\nSpace mark for copy\nc copy marked files\n/ filter by name\no open another repo\nO open on GitHub\nr retry a failed load\nq/Ctrl-C quit  ? help";
+        let area = centered_rect(60, 40, size);
+        let block = Block::default()
+            .title("Help")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.breadcrumb));
+        let help = Paragraph::new(help_text).block(block);
+        f.render_widget(help, area);
+    }
+
+    // Manifest confirmation modal
+    if let Some(text) = app.confirm_text() {
+        let area = centered_rect(60, 60, size);
+        let block = Block::default()
+            .title("Copy manifest")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.breadcrumb));
+        f.render_widget(ratatui::widgets::Clear, area);
+        f.render_widget(Paragraph::new(text).block(block), area);
+    }
+
+    // Loading spinner overlay
+    if let Some(since) = app
+        .loading_since
+        .filter(|_| app.spinner_due(Instant::now()))
+    {
+        let spinner_frames = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+        let idx = ((since.elapsed().as_millis() / 100) % 10) as usize;
+        // A fixed 3x3 box: a percentage of a small terminal leaves no room inside the border
+        let (width, height) = (size.width.min(3), size.height.min(3));
+        let area = ratatui::layout::Rect::new(
+            (size.width - width) / 2,
+            (size.height - height) / 2,
+            width,
+            height,
+        );
+        let spinner =
+            Paragraph::new(spinner_frames[idx]).block(Block::default().borders(Borders::ALL));
+        f.render_widget(spinner, area);
+    }
+
+    // Error banner
+    if let Some(err) = &app.error {
+        let banner = Paragraph::new(format!("{err}  (r retry, any other key dismisses)"))
+            .style(Style::default().fg(palette.error_fg).bg(palette.error_bg));
+        let area = ratatui::layout::Rect::new(0, size.height.saturating_sub(2), size.width, 1);
+        f.render_widget(banner, area);
+    }
+
+    list_height
+}
+
+/// Scrollbar state for a list of `total` items showing `view_height` rows from `offset`,
+/// or `None` when everything fits and no scrollbar is needed. The thumb reaches the bottom
+/// of the track once the last item is on screen.
+fn list_scrollbar(
+    total: usize,
+    view_height: usize,
+    offset: usize,
+) -> Option<ratatui::widgets::ScrollbarState> {
+    if view_height == 0 || total <= view_height {
+        return None;
+    }
+    Some(
+        ratatui::widgets::ScrollbarState::new(total - view_height + 1)
+            .position(offset)
+            .viewport_content_length(view_height),
+    )
+}
+
+/// "item X/N  " prefix for the footer, empty when there is nothing to select
+fn position_label(selected: usize, total: usize) -> String {
+    if total == 0 {
+        String::new()
+    } else {
+        format!("item {}/{}  ", selected.min(total - 1) + 1, total)
+    }
+}
+
+/// Launch the platform's URL opener without waiting for it. Fails when there is no
+/// graphical session to show a browser in, or no opener is installed.
+fn open_in_browser(url: &str) -> Result<()> {
+    use std::process::{Command, Stdio};
+
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        let graphical = ["DISPLAY", "WAYLAND_DISPLAY"]
+            .iter()
+            .any(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()));
+        if !graphical {
+            anyhow::bail!("No browser available in this session");
+        }
+        Command::new("xdg-open")
+    };
+    // The opener's output would corrupt the full-screen UI
+    let url = if cfg!(windows) {
+        cmd_safe_url(url)
+    } else {
+        url.to_string()
+    };
+    let mut child = command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Cannot start a browser: {e}"))?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+/// Percent-encode everything in `url` that `cmd /C` could treat as syntax (`&`, `|`, `^`,
+/// `<`, `>`, quotes, parentheses, delimiters, ...), so a crafted path cannot run a
+/// command. Existing `%XX` escapes are kept; any other `%` is encoded as well.
+fn cmd_safe_url(url: &str) -> String {
+    let bytes = url.as_bytes();
+    let mut safe = String::with_capacity(url.len());
+    for (index, &byte) in bytes.iter().enumerate() {
+        let escape = byte == b'%'
+            && bytes.len() > index + 2
+            && bytes[index + 1].is_ascii_hexdigit()
+            && bytes[index + 2].is_ascii_hexdigit();
+        if escape || byte.is_ascii_alphanumeric() || b"-._~/:?#@[]+*$".contains(&byte) {
+            safe.push(byte as char);
+        } else {
+            safe.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    safe
+}
+
+/// Split the screen into breadcrumb (1 line), main list (the rest) and footer (1 line)
+fn screen_chunks(area: ratatui::layout::Rect) -> std::rc::Rc<[ratatui::layout::Rect]> {
+    use ratatui::layout::{Constraint, Direction, Layout};
+
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(area)
+}
+
 /// Helper to create a centered rect with given percentage width/height
 fn centered_rect(
     percent_x: u16,
@@ -318,12 +1103,70 @@ fn icon_for(node: &crate::github::RepoNode) -> char {
     }
 }
 
-fn fg_color(node: &crate::github::RepoNode) -> ratatui::style::Color {
+/// Text for the preview pane once a file has been downloaded: the first
+/// [`PREVIEW_LINES`] lines of a rule file, or a rule summary for a manifest.
+fn render_preview(node: &RepoNode, content: &str) -> String {
+    match node.kind {
+        NodeKind::Manifest => {
+            let entries = manifest_entries(&node.name, content);
+            manifest_summary(entries.len(), &entries)
+        }
+        _ => content
+            .lines()
+            .take(PREVIEW_LINES)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// Rule paths listed in a manifest, or none when it cannot be parsed
+fn manifest_entries(name: &str, content: &str) -> Vec<String> {
+    use crate::github::manifests::{
+        parse_json_manifest, parse_txt_manifest, parse_yaml_manifest, ManifestFormat,
+    };
+
+    let format = std::path::Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(ManifestFormat::from_extension);
+    match format {
+        Some(ManifestFormat::Yaml) => parse_yaml_manifest(content)
+            .map(|schema| schema.rules)
+            .unwrap_or_default(),
+        Some(ManifestFormat::Json) => parse_json_manifest(content)
+            .map(|schema| schema.rules)
+            .unwrap_or_default(),
+        _ => parse_txt_manifest(content).unwrap_or_default(),
+    }
+}
+
+fn manifest_summary(count: usize, entries: &[String]) -> String {
+    let mut text = format!("Manifest with {count} rule(s)");
+    for entry in entries.iter().take(PREVIEW_LINES) {
+        text.push_str("\n  ");
+        text.push_str(entry);
+    }
+    text
+}
+
+/// Whether `path` sits directly in the quick-add manifest directory `manifest_dir`
+fn is_in_manifest_dir(path: &str, manifest_dir: &str) -> bool {
+    path.strip_prefix(manifest_dir)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .is_some_and(|name| !name.is_empty() && !name.contains('/'))
+}
+
+/// Case-insensitive substring match of `filter` against the node name
+fn matches_filter(node: &crate::github::RepoNode, filter: &str) -> bool {
+    filter.is_empty() || node.name.to_lowercase().contains(&filter.to_lowercase())
+}
+
+fn fg_color(node: &crate::github::RepoNode, palette: &theme::Palette) -> ratatui::style::Color {
     if node.name.starts_with('.') {
         // hidden entry
-        theme::Palette::HIDDEN
+        palette.hidden
     } else {
-        theme::Palette::NORMAL
+        palette.normal
     }
 }
 
@@ -346,18 +1189,722 @@ mod tests {
             name: "file.mdc".into(),
             path: "file.mdc".into(),
             kind: NodeKind::RuleFile,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         };
         let dir = RepoNode {
             name: ".hidden".into(),
             path: ".hidden".into(),
             kind: NodeKind::Dir,
-            children: None,
-            manifest_count: None,
+            ..Default::default()
         };
         assert_eq!(icon_for(&file), '📄');
         assert_eq!(icon_for(&dir), '📁');
-        assert_eq!(fg_color(&dir), theme::Palette::HIDDEN);
+        assert_eq!(
+            fg_color(&dir, &theme::Palette::DEFAULT),
+            theme::Palette::DEFAULT.hidden
+        );
+        assert_eq!(
+            fg_color(&file, &theme::Palette::DEFAULT),
+            theme::Palette::DEFAULT.normal
+        );
+    }
+
+    fn rule(path: &str) -> RepoNode {
+        RepoNode {
+            name: path.rsplit('/').next().unwrap().into(),
+            path: path.into(),
+            kind: NodeKind::RuleFile,
+            ..Default::default()
+        }
+    }
+
+    fn test_state() -> (AppState, tokio::sync::mpsc::UnboundedReceiver<AppMessage>) {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let locator = RepoLocator {
+            owner: "o".into(),
+            repo: "r".into(),
+            branch: "main".into(),
+        };
+        (AppState::new(&locator, false, tx), rx)
+    }
+
+    #[test]
+    fn spinner_waits_for_slow_loads() {
+        let (mut app, _rx) = test_state();
+        let start = Instant::now();
+        assert!(!app.spinner_due(start));
+
+        app.loading_since = Some(start);
+        assert!(!app.spinner_due(start));
+        assert!(!app.spinner_due(start + Duration::from_millis(100)));
+        assert!(app.spinner_due(start + SPINNER_DELAY));
+        assert!(app.spinner_due(start + Duration::from_secs(2)));
+
+        // A finished load hides it again
+        app.loading_since = None;
+        assert!(!app.spinner_due(start + Duration::from_secs(2)));
+    }
+
+    /// Every cell of the last drawn frame, row after row
+    fn screen_text(terminal: &Terminal<ratatui::backend::TestBackend>) -> String {
+        terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn spinner_is_drawn_while_a_slow_load_is_pending() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/repos/o/r/contents")
+            .match_query(mockito::Matcher::UrlEncoded("ref".into(), "main".into()))
+            .with_status(200)
+            .with_body_from_request(|_| {
+                std::thread::sleep(Duration::from_millis(400));
+                serde_json::json!([{"name": "react.mdc", "path": "react.mdc", "type": "file"}])
+                    .to_string()
+                    .into_bytes()
+            })
+            .create_async()
+            .await;
+        std::env::set_var("OCTO_BASE", server.url());
+
+        let (mut app, _rx) = test_state();
+        let mut terminal = Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+        let spinning = |screen: &str| "⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏".chars().any(|c| screen.contains(c));
+
+        // Quick loads draw no spinner, slow ones do while they are still pending
+        app.start_load();
+        terminal
+            .draw(|f| {
+                draw(f, &mut app);
+            })
+            .unwrap();
+        let early = screen_text(&terminal);
+        tokio::time::sleep(SPINNER_DELAY + Duration::from_millis(50)).await;
+        terminal
+            .draw(|f| {
+                draw(f, &mut app);
+            })
+            .unwrap();
+        let slow = screen_text(&terminal);
+        let pending = app.load.is_some();
+
+        app.finish_load().await;
+        std::env::remove_var("OCTO_BASE");
+        terminal
+            .draw(|f| {
+                draw(f, &mut app);
+            })
+            .unwrap();
+        let loaded = screen_text(&terminal);
+
+        assert!(!spinning(&early), "{early}");
+        assert!(pending);
+        assert!(spinning(&slow), "{slow}");
+        assert!(!spinning(&loaded), "{loaded}");
+        assert!(loaded.contains("react.mdc"), "{loaded}");
+    }
+
+    #[test]
+    fn resize_keeps_the_selection_on_screen() {
+        let (mut app, _rx) = test_state();
+        app.viewport.selected_index = 30;
+        app.viewport.ensure_visible(40);
+        assert_eq!(app.viewport.scroll_offset, 0);
+
+        // Shrinking to 12 rows leaves 10 for the list, so it scrolls to keep item 30
+        assert_eq!(app.resize(80, 12), 10);
+        assert_eq!(app.viewport.scroll_offset, 21);
+
+        // Growing again keeps the offset; ensure_visible only scrolls when needed
+        assert_eq!(app.resize(80, 50), 48);
+        assert_eq!(app.viewport.scroll_offset, 21);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn browser_reads_tree_seeded_on_disk_without_fetching() {
+        use crate::github::{FileSystemCache, PersistentCache};
+
+        let cache_home = tempfile::TempDir::new().unwrap();
+        let original_cache = std::env::var_os("XDG_CACHE_HOME");
+        std::env::set_var("XDG_CACHE_HOME", cache_home.path());
+        // Any request would fail: nothing listens here
+        std::env::set_var("OCTO_BASE", "http://127.0.0.1:9");
+
+        let (mut app, _rx) = test_state();
+        FileSystemCache::new()
+            .unwrap()
+            .store_tree_cache(
+                &app.locator,
+                &[
+                    RepoNode {
+                        kind: NodeKind::Dir,
+                        ..rule("frontend")
+                    },
+                    rule("frontend/react.mdc"),
+                ],
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        app.tree = RepoTree::with_persistent_cache(24).unwrap().lazy();
+        app.load_items().await;
+        let root: Vec<String> = app.items.iter().map(|n| n.path.clone()).collect();
+        app.dir_path = "frontend".into();
+        app.load_items().await;
+        let frontend: Vec<String> = app.items.iter().map(|n| n.path.clone()).collect();
+
+        std::env::remove_var("OCTO_BASE");
+        match original_cache {
+            Some(value) => std::env::set_var("XDG_CACHE_HOME", value),
+            None => std::env::remove_var("XDG_CACHE_HOME"),
+        }
+
+        assert_eq!(app.error, None);
+        assert_eq!(root, ["frontend"]);
+        assert_eq!(frontend, ["frontend/react.mdc"]);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn refresh_bypasses_tree_on_disk_for_first_load_only() {
+        use crate::github::{FileSystemCache, PersistentCache};
+
+        let cache_home = tempfile::TempDir::new().unwrap();
+        let original_cache = std::env::var_os("XDG_CACHE_HOME");
+        std::env::set_var("XDG_CACHE_HOME", cache_home.path());
+        let mut server = mockito::Server::new_async().await;
+        let root = server
+            .mock("GET", "/repos/o/r/contents")
+            .match_query(mockito::Matcher::UrlEncoded("ref".into(), "main".into()))
+            .with_status(200)
+            .with_body(
+                serde_json::json!([{"name": "new.mdc", "path": "new.mdc", "type": "file"}])
+                    .to_string(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        std::env::set_var("OCTO_BASE", server.url());
+
+        let (mut app, _rx) = test_state();
+        FileSystemCache::new()
+            .unwrap()
+            .store_tree_cache(&app.locator, &[rule("stale.mdc")], None, None)
+            .await
+            .unwrap();
+        app.tree = RepoTree::with_persistent_cache(24).unwrap().lazy();
+        app.refresh = true;
+        app.load_items().await;
+        let first: Vec<String> = app.items.iter().map(|n| n.path.clone()).collect();
+        app.load_items().await;
+        let second: Vec<String> = app.items.iter().map(|n| n.path.clone()).collect();
+
+        std::env::remove_var("OCTO_BASE");
+        match original_cache {
+            Some(value) => std::env::set_var("XDG_CACHE_HOME", value),
+            None => std::env::remove_var("XDG_CACHE_HOME"),
+        }
+
+        assert_eq!(first, ["new.mdc"]);
+        assert_eq!(second, ["new.mdc"]);
+        assert!(!app.refresh);
+        root.assert_async().await;
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn retry_reloads_a_failed_directory_and_clears_the_error() {
+        let mut server = mockito::Server::new_async().await;
+        let failing = server
+            .mock("GET", "/repos/o/r/contents")
+            .match_query(mockito::Matcher::UrlEncoded("ref".into(), "main".into()))
+            .with_status(404)
+            .with_body(r#"{"message": "Not Found"}"#)
+            .create_async()
+            .await;
+        std::env::set_var("OCTO_BASE", server.url());
+
+        let (mut app, _rx) = test_state();
+        app.load_items().await;
+        let failed = app.error.clone();
+        let items_after_failure = app.items.len();
+
+        // Dismissing the banner does not fetch the directory again
+        app.error = None;
+        let reloads_after_dismiss = app.needs_load();
+
+        failing.remove_async().await;
+        server
+            .mock("GET", "/repos/o/r/contents")
+            .match_query(mockito::Matcher::UrlEncoded("ref".into(), "main".into()))
+            .with_status(200)
+            .with_body(
+                serde_json::json!([{"name": "react.mdc", "path": "react.mdc", "type": "file"}])
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+        app.retry_load();
+        app.finish_load().await;
+        std::env::remove_var("OCTO_BASE");
+
+        assert!(failed.is_some_and(|error| error.starts_with("Fetch error")));
+        assert_eq!(items_after_failure, 0);
+        assert!(!reloads_after_dismiss);
+        assert_eq!(app.error, None);
+        assert!(!app.load_failed);
+        let paths: Vec<String> = app.items.iter().map(|n| n.path.clone()).collect();
+        assert_eq!(paths, ["react.mdc"]);
+    }
+
+    #[tokio::test]
+    async fn retry_does_nothing_unless_a_load_failed() {
+        let (mut app, _rx) = test_state();
+        app.items = vec![rule("react.mdc")];
+        app.error = Some("No browser available in this session".to_string());
+
+        app.retry_load();
+
+        assert!(app.load.is_none());
+        assert_eq!(app.items, [rule("react.mdc")]);
+        assert!(app.error.is_some());
+    }
+
+    fn stub_resolve(input: String) -> Pin<Box<dyn Future<Output = Result<RepoLocator>> + Send>> {
+        Box::pin(async move {
+            match input.split_once('/') {
+                Some((owner, repo)) if owner != "missing" => Ok(RepoLocator {
+                    owner: owner.into(),
+                    repo: repo.into(),
+                    branch: "main".into(),
+                }),
+                _ => anyhow::bail!("repository not found"),
+            }
+        })
+    }
+
+    fn key(code: crossterm::event::KeyCode) -> inputs::FilterInput {
+        let ev = crossterm::event::KeyEvent::new(code, crossterm::event::KeyModifiers::NONE);
+        inputs::key_event_to_filter_input(&ev).unwrap()
+    }
+
+    #[tokio::test]
+    async fn switch_repo_resolves_typed_locator_and_reloads() {
+        use crossterm::event::KeyCode;
+
+        let (mut app, mut rx) = test_state();
+        app.resolve_repo = stub_resolve;
+        app.dir_path = "frontend".into();
+        app.items = vec![rule("frontend/react.mdc")];
+        app.marked.insert("frontend/react.mdc".into());
+
+        app.repo_input = Some(String::new());
+        for c in "acme/rulez".chars() {
+            app.handle_repo_input(key(KeyCode::Char(c))).await;
+        }
+        app.handle_repo_input(key(KeyCode::Backspace)).await;
+        app.handle_repo_input(key(KeyCode::Char('s'))).await;
+        assert_eq!(app.repo_input.as_deref(), Some("acme/rules"));
+        app.handle_repo_input(key(KeyCode::Enter)).await;
+
+        assert_eq!(app.repo_input, None);
+        assert_eq!(app.error, None);
+        assert_eq!(app.breadcrumb, "acme/rules");
+        assert_eq!(
+            (app.locator.owner.as_str(), app.locator.repo.as_str()),
+            ("acme", "rules")
+        );
+        // Back at the root of the new repo with nothing left over from the old one
+        assert!(app.dir_path.is_empty());
+        assert!(app.items.is_empty());
+        assert!(app.marked.is_empty());
+        match rx.try_recv() {
+            Ok(AppMessage::RepoChanged { locator }) => assert_eq!(locator, app.locator),
+            other => panic!("expected RepoChanged, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn switch_repo_failure_shows_banner_and_keeps_repo() {
+        use crossterm::event::KeyCode;
+
+        let (mut app, mut rx) = test_state();
+        app.resolve_repo = stub_resolve;
+        app.dir_path = "frontend".into();
+
+        app.repo_input = Some("missing/repo".into());
+        app.handle_repo_input(key(KeyCode::Enter)).await;
+
+        assert_eq!(app.repo_input, None);
+        let error = app.error.clone().unwrap();
+        assert!(error.contains("missing/repo"), "{error}");
+        assert!(error.contains("repository not found"), "{error}");
+        assert_eq!(app.breadcrumb, "o/r");
+        assert_eq!(app.dir_path, "frontend");
+        assert!(rx.try_recv().is_err());
+
+        // Esc closes the box without resolving anything
+        app.repo_input = Some("acme/rules".into());
+        app.handle_repo_input(key(KeyCode::Esc)).await;
+        assert_eq!(app.repo_input, None);
+        assert_eq!(app.breadcrumb, "o/r");
+    }
+
+    #[test]
+    fn toggle_marks_and_copy_batch() {
+        let (mut app, mut rx) = test_state();
+        app.items = vec![
+            rule("frontend/vue.mdc"),
+            rule("frontend/react.mdc"),
+            rule("frontend/notes.txt"),
+        ];
+
+        app.toggle_mark(0);
+        app.toggle_mark(1);
+        app.toggle_mark(2);
+        app.toggle_mark(0); // unmark again
+        assert!(!app.is_marked(&app.items[0]));
+        assert!(app.is_marked(&app.items[1]));
+
+        // Only marked .mdc files are sent, in a single batch
+        assert_eq!(app.copy_marked(), 1);
+        assert!(app.marked.is_empty());
+        match rx.try_recv().unwrap() {
+            AppMessage::CopyBatch { paths } => {
+                assert_eq!(paths, vec!["frontend/react.mdc".to_string()])
+            }
+            other => panic!("unexpected message: {other:?}"),
+        }
+
+        // Nothing marked, nothing sent
+        assert_eq!(app.copy_marked(), 0);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn filter_matches_case_insensitive_substring() {
+        let node = rule("frontend/React-Hooks.mdc");
+        assert!(matches_filter(&node, ""));
+        assert!(matches_filter(&node, "react"));
+        assert!(matches_filter(&node, "HOOKS"));
+        assert!(!matches_filter(&node, "vue"));
+        // Only the name is matched, not the directory
+        assert!(!matches_filter(&node, "frontend"));
+    }
+
+    #[test]
+    fn filter_clamps_selection_and_targets_visible_items() {
+        let (mut app, _rx) = test_state();
+        app.items = vec![
+            rule("a/react.mdc"),
+            rule("a/vue.mdc"),
+            rule("a/svelte.mdc"),
+            rule("a/preact.mdc"),
+        ];
+        app.viewport.selected_index = 3;
+
+        app.filter_input = true;
+        for c in "react".chars() {
+            app.handle_filter_input(inputs::FilterInput::Char(c));
+        }
+        assert_eq!(app.visible_items().len(), 2);
+        assert_eq!(app.viewport.selected_index, 1);
+
+        // Indices refer to the filtered list
+        app.toggle_mark(1);
+        assert!(app.marked.contains("a/preact.mdc"));
+
+        app.handle_filter_input(inputs::FilterInput::Char('x'));
+        assert!(app.visible_items().is_empty());
+        assert_eq!(app.viewport.selected_index, 0);
+
+        app.handle_filter_input(inputs::FilterInput::Backspace);
+        app.handle_filter_input(inputs::FilterInput::Apply);
+        assert!(!app.filter_input);
+        assert_eq!(app.filter, "react");
+
+        app.filter_input = true;
+        app.handle_filter_input(inputs::FilterInput::Clear);
+        assert!(app.filter.is_empty());
+        assert_eq!(app.visible_items().len(), 4);
+    }
+
+    #[test]
+    fn marks_survive_directory_navigation() {
+        let (mut app, mut rx) = test_state();
+        let frontend = RepoNode {
+            name: "frontend".into(),
+            path: "frontend".into(),
+            kind: NodeKind::Dir,
+            ..Default::default()
+        };
+        app.items = vec![frontend.clone(), rule("root.mdc")];
+
+        // Enter the directory and mark a file there
+        app.open(0);
+        assert_eq!(app.dir_path, "frontend");
+        assert!(app.items.is_empty());
+        app.items = vec![rule("frontend/react.mdc"), rule("frontend/vue.mdc")];
+        app.toggle_mark(0);
+
+        // Back out to the root and mark another file
+        assert!(app.go_up());
+        app.items = vec![frontend, rule("root.mdc")];
+        assert!(!app.is_marked(&app.items[0]));
+        app.toggle_mark(1);
+
+        // Re-entering shows the original mark on the same file
+        app.open(0);
+        app.items = vec![rule("frontend/react.mdc"), rule("frontend/vue.mdc")];
+        assert!(app.is_marked(&app.items[0]));
+        assert!(!app.is_marked(&app.items[1]));
+
+        assert_eq!(app.copy_marked(), 2);
+        match rx.try_recv().unwrap() {
+            AppMessage::CopyBatch { paths } => assert_eq!(
+                paths,
+                vec!["frontend/react.mdc".to_string(), "root.mdc".to_string()]
+            ),
+            other => panic!("unexpected message: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn preview_fetch_is_debounced_and_cached_by_path() {
+        let (mut app, _rx) = test_state();
+        app.items = vec![rule("a/react.mdc"), rule("a/vue.mdc")];
+        let start = Instant::now();
+        app.track_selection(start);
+
+        // Nothing is fetched while the cursor is still moving
+        assert!(app.take_due_preview(start).is_none());
+        assert_eq!(app.preview_text(), "Loading preview…");
+
+        let later = start + PREVIEW_DEBOUNCE;
+        let due = app.take_due_preview(later).expect("preview should be due");
+        assert_eq!(due.path, "a/react.mdc");
+        // Already loading, so it is not requested twice
+        assert!(app.take_due_preview(later).is_none());
+
+        app.preview_tx
+            .send(("a/react.mdc".into(), Preview::Ready("# React".into())))
+            .unwrap();
+        app.receive_previews();
+        assert_eq!(app.preview_text(), "# React");
+
+        // Moving the cursor restarts the debounce timer
+        app.viewport.down(2);
+        app.track_selection(later);
+        assert!(app.take_due_preview(later).is_none());
+        app.preview_tx
+            .send(("a/vue.mdc".into(), Preview::Unavailable))
+            .unwrap();
+        app.receive_previews();
+        assert_eq!(app.preview_text(), "preview unavailable");
+    }
+
+    #[test]
+    fn scrollbar_only_when_items_overflow() {
+        assert_eq!(list_scrollbar(5, 10, 0), None);
+        assert_eq!(list_scrollbar(10, 10, 0), None);
+        assert_eq!(list_scrollbar(3, 0, 0), None);
+
+        // 30 items in 10 rows: offsets 0..=20 map onto the track
+        let expected = ratatui::widgets::ScrollbarState::new(21)
+            .position(20)
+            .viewport_content_length(10);
+        assert_eq!(list_scrollbar(30, 10, 20), Some(expected));
+    }
+
+    #[test]
+    fn position_label_counts_from_one() {
+        assert_eq!(position_label(0, 0), "");
+        assert_eq!(position_label(0, 12), "item 1/12  ");
+        assert_eq!(position_label(11, 12), "item 12/12  ");
+    }
+
+    #[test]
+    fn manifests_are_recognised_directly_in_the_manifest_dir() {
+        assert!(is_in_manifest_dir("quick-add/web.txt", "quick-add"));
+        assert!(is_in_manifest_dir("bundles/web.txt", "bundles"));
+        assert!(!is_in_manifest_dir("quick-add/web.txt", "bundles"));
+        assert!(!is_in_manifest_dir("bundles-old/web.txt", "bundles"));
+        assert!(!is_in_manifest_dir("bundles/nested/web.txt", "bundles"));
+        assert!(!is_in_manifest_dir("bundles", "bundles"));
+    }
+
+    #[test]
+    fn bubble_shows_manifest_count() {
+        let mut manifest = rule("quick-add/frontend.txt");
+        manifest.kind = NodeKind::Manifest;
+        assert_eq!(bubble(&manifest), "");
+
+        manifest.manifest_count = Some(3);
+        assert_eq!(bubble(&manifest), "  [3 files]");
+    }
+
+    #[test]
+    fn manifest_counts_update_listing_and_tree() {
+        let (mut app, _rx) = test_state();
+        let mut manifest = rule("quick-add/frontend.txt");
+        manifest.kind = NodeKind::Manifest;
+        app.tree = RepoTree::from_nodes(vec![manifest.clone()]);
+        app.items = vec![manifest];
+
+        let entries = vec![
+            "a.mdc".to_string(),
+            "b.mdc".to_string(),
+            "c.mdc".to_string(),
+        ];
+        app.manifest_tx
+            .send(("quick-add/frontend.txt".to_string(), Some(entries)))
+            .unwrap();
+        app.receive_manifest_counts();
+
+        assert_eq!(app.items[0].manifest_count, Some(3));
+        assert_eq!(bubble(&app.items[0]), "  [3 files]");
+        let cached = app.tree.cached_children("quick-add").unwrap();
+        assert_eq!(cached[0].manifest_count, Some(3));
+    }
+
+    #[test]
+    fn manifest_copy_waits_for_confirmation() {
+        let (mut app, mut rx) = test_state();
+        app.confirm_manifests = true;
+        let mut manifest = rule("quick-add/frontend.txt");
+        manifest.kind = NodeKind::Manifest;
+        app.items = vec![manifest];
+        // Entries already loaded, so opening does not download anything
+        app.manifests.insert(
+            "quick-add/frontend.txt".to_string(),
+            ManifestEntries::Loaded(vec!["frontend/react.mdc".to_string()]),
+        );
+
+        app.open(0);
+        assert_eq!(app.confirm.as_deref(), Some("quick-add/frontend.txt"));
+        assert!(
+            rx.try_recv().is_err(),
+            "nothing is copied before confirming"
+        );
+        let text = app.confirm_text().unwrap();
+        assert!(text.contains("Manifest with 1 rule(s)"));
+        assert!(text.contains("frontend/react.mdc"));
+
+        // Esc cancels without copying
+        app.resolve_confirm(false);
+        assert!(app.confirm.is_none());
+        assert!(rx.try_recv().is_err());
+
+        // Enter copies
+        app.open(0);
+        app.resolve_confirm(true);
+        assert!(app.confirm.is_none());
+        match rx.try_recv().unwrap() {
+            AppMessage::CopyRequest { path } => assert_eq!(path, "quick-add/frontend.txt"),
+            other => panic!("unexpected message {other:?}"),
+        }
+    }
+
+    #[test]
+    fn manifest_copy_skips_confirmation_when_forced() {
+        let (mut app, mut rx) = test_state();
+        let mut manifest = rule("quick-add/frontend.txt");
+        manifest.kind = NodeKind::Manifest;
+        app.items = vec![manifest];
+
+        app.open(0);
+        assert!(app.confirm.is_none());
+        assert!(matches!(
+            rx.try_recv().unwrap(),
+            AppMessage::CopyRequest { .. }
+        ));
+    }
+
+    #[test]
+    fn preview_summarises_directories_and_manifests() {
+        let (mut app, _rx) = test_state();
+        let manifest = RepoNode {
+            name: "frontend.txt".into(),
+            path: "quick-add/frontend.txt".into(),
+            kind: NodeKind::Manifest,
+            manifest_count: Some(3),
+            ..Default::default()
+        };
+        let dir = RepoNode {
+            name: "rules".into(),
+            path: "rules".into(),
+            kind: NodeKind::Dir,
+            ..Default::default()
+        };
+        app.items = vec![dir, manifest];
+        app.track_selection(Instant::now());
+
+        // Not visited yet, so its contents are unknown
+        assert_eq!(app.preview_text(), "Directory");
+        app.tree = RepoTree::from_nodes(vec![RepoNode {
+            name: "react.mdc".into(),
+            path: "rules/react.mdc".into(),
+            kind: NodeKind::RuleFile,
+            ..Default::default()
+        }]);
+        assert_eq!(app.preview_text(), "Directory with 1 item(s)");
+        assert!(app
+            .take_due_preview(Instant::now() + PREVIEW_DEBOUNCE)
+            .is_none());
+
+        // A manifest with a known count needs no download
+        app.viewport.down(2);
+        let later = Instant::now() + PREVIEW_DEBOUNCE * 2;
+        app.track_selection(Instant::now());
+        assert!(app.take_due_preview(later).is_none());
+        assert_eq!(app.preview_text(), "Manifest with 3 rule(s)");
+    }
+
+    #[test]
+    fn render_preview_truncates_files_and_lists_manifest_rules() {
+        let content: String = (1..=100).map(|i| format!("line {i}\n")).collect();
+        let preview = render_preview(&rule("a/long.mdc"), &content);
+        assert_eq!(preview.lines().count(), PREVIEW_LINES);
+        assert!(preview.ends_with("line 40"));
+
+        let manifest = RepoNode {
+            name: "backend.yaml".into(),
+            path: "quick-add/backend.yaml".into(),
+            kind: NodeKind::Manifest,
+            ..Default::default()
+        };
+        let preview = render_preview(
+            &manifest,
+            "name: backend\nrules:\n  - rust.mdc\n  - go.mdc\n",
+        );
+        assert_eq!(preview, "Manifest with 2 rule(s)\n  rust.mdc\n  go.mdc");
+    }
+
+    #[test]
+    fn cmd_safe_url_leaves_no_shell_syntax() {
+        let locator = RepoLocator {
+            owner: "octo".into(),
+            repo: "rules".into(),
+            branch: "main".into(),
+        };
+        let url = cmd_safe_url(&locator.web_url("a&calc|b^c(d)<e>f%g.mdc", false));
+        assert_eq!(
+            url,
+            "https://github.com/octo/rules/blob/main/a%26calc%7Cb%5Ec%28d%29%3Ce%3Ef%25g.mdc"
+        );
+        assert_eq!(cmd_safe_url("https://x/100%"), "https://x/100%25");
+        assert_eq!(
+            cmd_safe_url("https://x/a=b,c;d!"),
+            "https://x/a%3Db%2Cc%3Bd%21"
+        );
     }
 }
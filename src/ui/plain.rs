@@ -0,0 +1,321 @@
+//! Line-based browser for screen readers and minimal terminals.
+//!
+//! Presents the current directory as a numbered, paginated list and reads commands from
+//! standard input instead of raw-mode key events, so no alternate screen is required.
+
+use anyhow::Result;
+use std::io::Write;
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Notify;
+
+use super::{AppMessage, AppState};
+use crate::github::{NodeKind, RepoLocator, RepoNode, RepoTree};
+
+/// Number of items listed per page
+const PAGE_SIZE: usize = 20;
+
+/// Help text listing the supported commands
+const HELP_TEXT: &str = "Commands:
+  enter N   open directory N or copy file N (a bare number also works)
+  up        go to the parent directory
+  mark N    mark or unmark item N for copying
+  copy      copy all marked rule files
+  next      show the next page
+  prev      show the previous page
+  list      show the current page again
+  help      show this help
+  quit      exit the browser";
+
+/// A command entered at the plain browser prompt. Item numbers are 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlainCommand {
+    Enter(usize),
+    Up,
+    Mark(usize),
+    Copy,
+    Next,
+    Prev,
+    List,
+    Help,
+    Quit,
+}
+
+/// Launch the plain browser reading commands from stdin and writing to stdout.
+///
+/// `copied` is notified once the receiver of `tx` has handled a copy request. The copier
+/// may prompt on stdin before overwriting a file, so no command is read until then.
+pub async fn run(
+    repo: &RepoLocator,
+    tree: RepoTree,
+    tx: UnboundedSender<AppMessage>,
+    copied: Arc<Notify>,
+    show_hidden: bool,
+    use_cache: bool,
+    refresh: bool,
+) -> Result<()> {
+    let mut app = AppState::new(repo, show_hidden, tx);
+    app.tree = tree.lazy();
+    app.use_cache = use_cache;
+    app.refresh = refresh;
+    let output = std::io::stdout();
+    run_with_io(app, copied, output).await
+}
+
+/// Read one line from stdin on the blocking pool, or `None` at end of input.
+///
+/// Nothing holds stdin between calls, so prompts shown during a copy get their input.
+async fn read_command_line() -> Result<Option<String>> {
+    let line = tokio::task::spawn_blocking(|| {
+        let mut line = String::new();
+        std::io::stdin()
+            .read_line(&mut line)
+            .map(|read| (read > 0).then_some(line))
+    })
+    .await??;
+    Ok(line)
+}
+
+/// Run the plain browser loop against stdin and an arbitrary output stream.
+async fn run_with_io<W: Write>(
+    mut app: AppState,
+    copied: Arc<Notify>,
+    mut output: W,
+) -> Result<()> {
+    let mut page = 0;
+
+    app.load_items().await;
+    render_page(&mut output, &app, page)?;
+
+    loop {
+        // Manifest counts load in the background; show them from the next listing on
+        app.receive_manifest_counts();
+        write!(output, "> ")?;
+        output.flush()?;
+
+        let Some(line) = read_command_line().await? else {
+            // End of input behaves like quit
+            writeln!(output)?;
+            break;
+        };
+
+        let command = match parse_command(&line) {
+            Ok(Some(command)) => command,
+            Ok(None) => continue,
+            Err(message) => {
+                writeln!(output, "{message}")?;
+                continue;
+            }
+        };
+
+        match command {
+            PlainCommand::Quit => break,
+            PlainCommand::Help => writeln!(output, "{HELP_TEXT}")?,
+            PlainCommand::List => render_page(&mut output, &app, page)?,
+            PlainCommand::Next => {
+                if (page + 1) * PAGE_SIZE < app.items.len() {
+                    page += 1;
+                    render_page(&mut output, &app, page)?;
+                } else {
+                    writeln!(output, "Already on the last page.")?;
+                }
+            }
+            PlainCommand::Prev => {
+                if page > 0 {
+                    page -= 1;
+                    render_page(&mut output, &app, page)?;
+                } else {
+                    writeln!(output, "Already on the first page.")?;
+                }
+            }
+            PlainCommand::Up => {
+                if app.go_up() {
+                    page = 0;
+                    app.load_items().await;
+                    render_page(&mut output, &app, page)?;
+                } else {
+                    writeln!(output, "Already at the repository root.")?;
+                }
+            }
+            PlainCommand::Enter(number) => {
+                let Some(node) = item_at(&app, number) else {
+                    writeln!(output, "No item {number} in this directory.")?;
+                    continue;
+                };
+
+                if node.is_dir() {
+                    app.open(number - 1);
+                    page = 0;
+                    app.load_items().await;
+                    render_page(&mut output, &app, page)?;
+                } else {
+                    writeln!(output, "Copy requested: {}", node.path)?;
+                    output.flush()?;
+                    app.open(number - 1);
+                    copied.notified().await;
+                }
+            }
+            PlainCommand::Mark(number) => {
+                let Some(node) = item_at(&app, number) else {
+                    writeln!(output, "No item {number} in this directory.")?;
+                    continue;
+                };
+
+                let name = node.name.clone();
+                let path = node.path.clone();
+                app.toggle_mark(number - 1);
+                if app.marked.contains(&path) {
+                    writeln!(output, "Marked {number}: {name}")?;
+                } else {
+                    writeln!(output, "Unmarked {number}: {name}")?;
+                }
+            }
+            PlainCommand::Copy => {
+                if app.marked.is_empty() {
+                    writeln!(output, "No items marked. Use 'mark N' first.")?;
+                } else {
+                    let sent = app.copy_marked();
+                    writeln!(output, "Copy requested for {sent} rule file(s).")?;
+                    output.flush()?;
+                    if sent > 0 {
+                        copied.notified().await;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Look up an item by its 1-based number
+fn item_at(app: &AppState, number: usize) -> Option<&RepoNode> {
+    number.checked_sub(1).and_then(|index| app.items.get(index))
+}
+
+/// Parse a line of user input. Blank lines yield `Ok(None)`.
+fn parse_command(line: &str) -> Result<Option<PlainCommand>, String> {
+    let mut parts = line.split_whitespace();
+    let Some(word) = parts.next() else {
+        return Ok(None);
+    };
+    let argument = parts.next();
+
+    let parse_number = |arg: Option<&str>| -> Result<usize, String> {
+        arg.and_then(|a| a.parse::<usize>().ok())
+            .filter(|n| *n > 0)
+            .ok_or_else(|| format!("'{word}' needs an item number, e.g. '{word} 1'."))
+    };
+
+    let command = match word.to_lowercase().as_str() {
+        "enter" | "open" => PlainCommand::Enter(parse_number(argument)?),
+        "mark" => PlainCommand::Mark(parse_number(argument)?),
+        "up" | "back" => PlainCommand::Up,
+        "copy" => PlainCommand::Copy,
+        "next" | "n" => PlainCommand::Next,
+        "prev" | "p" => PlainCommand::Prev,
+        "list" | "ls" => PlainCommand::List,
+        "help" | "?" => PlainCommand::Help,
+        "quit" | "q" | "exit" => PlainCommand::Quit,
+        other => match other.parse::<usize>() {
+            Ok(number) if number > 0 => PlainCommand::Enter(number),
+            _ => {
+                return Err(format!(
+                    "Unknown command '{word}'. Type 'help' for commands."
+                ))
+            }
+        },
+    };
+
+    Ok(Some(command))
+}
+
+/// Write the current directory listing for `page`
+fn render_page<W: Write>(output: &mut W, app: &AppState, page: usize) -> Result<()> {
+    if app.dir_path.is_empty() {
+        writeln!(output, "{}", app.breadcrumb)?;
+    } else {
+        writeln!(output, "{}/{}", app.breadcrumb, app.dir_path)?;
+    }
+
+    if let Some(error) = &app.error {
+        writeln!(output, "Error: {error}")?;
+    }
+
+    if app.items.is_empty() {
+        writeln!(output, "(empty directory)")?;
+        return Ok(());
+    }
+
+    let total_pages = app.items.len().div_ceil(PAGE_SIZE);
+    let start = page * PAGE_SIZE;
+    let end = usize::min(start + PAGE_SIZE, app.items.len());
+
+    writeln!(
+        output,
+        "Page {} of {} ({} items)",
+        page + 1,
+        total_pages,
+        app.items.len()
+    )?;
+
+    for (index, node) in app.items[start..end].iter().enumerate() {
+        let absolute = start + index;
+        let mark = if app.is_marked(node) { "marked " } else { "" };
+        writeln!(
+            output,
+            "{:>3}. {mark}{}: {}{}",
+            absolute + 1,
+            kind_label(node),
+            node.name,
+            super::bubble(node)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Text label used instead of an icon so screen readers announce the item type
+fn kind_label(node: &RepoNode) -> &'static str {
+    match node.kind {
+        NodeKind::Dir => "directory",
+        NodeKind::RuleFile => "rule",
+        NodeKind::Manifest => "manifest",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands_with_numbers() {
+        assert_eq!(parse_command("enter 3"), Ok(Some(PlainCommand::Enter(3))));
+        assert_eq!(parse_command("  2 \n"), Ok(Some(PlainCommand::Enter(2))));
+        assert_eq!(parse_command("mark 1"), Ok(Some(PlainCommand::Mark(1))));
+        assert_eq!(parse_command("UP"), Ok(Some(PlainCommand::Up)));
+        assert_eq!(parse_command("copy"), Ok(Some(PlainCommand::Copy)));
+        assert_eq!(parse_command("q"), Ok(Some(PlainCommand::Quit)));
+        assert_eq!(parse_command("   "), Ok(None));
+    }
+
+    #[test]
+    fn parse_commands_rejects_bad_input() {
+        assert!(parse_command("mark").is_err());
+        assert!(parse_command("enter zero").is_err());
+        assert!(parse_command("enter 0").is_err());
+        assert!(parse_command("dance").is_err());
+    }
+
+    #[test]
+    fn kind_labels_are_textual() {
+        let node = RepoNode {
+            name: "all.txt".into(),
+            path: "quick-add/all.txt".into(),
+            kind: NodeKind::Manifest,
+            manifest_count: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(kind_label(&node), "manifest");
+    }
+}
@@ -0,0 +1,181 @@
+//! Background download of the rules in the directory being browsed.
+//!
+//! Each rule is fetched through [`crate::copier::fetch_file_content`], which stores it in
+//! the blob cache, so copying or previewing it afterwards needs no network round trip.
+//! Only a few downloads run at once, and leaving the directory cancels the rest.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+use tokio::task::{JoinHandle, JoinSet};
+
+use crate::github::{NodeKind, RepoLocator, RepoNode};
+
+/// Downloads in flight at once for one directory
+pub const PREFETCH_CONCURRENCY: usize = 3;
+
+/// Run `fetch` for every item with at most `limit` futures in flight. Cancelling the
+/// returned future drops the downloads that already started.
+pub async fn prefetch_all<T, F, Fut>(items: Vec<T>, limit: usize, fetch: F)
+where
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(limit.max(1)));
+    let mut downloads = JoinSet::new();
+    for item in items {
+        let Ok(permit) = semaphore.clone().acquire_owned().await else {
+            break;
+        };
+        let download = fetch(item);
+        downloads.spawn(async move {
+            download.await;
+            drop(permit);
+        });
+    }
+    while downloads.join_next().await.is_some() {}
+}
+
+/// Rules in `nodes` worth prefetching: `.mdc` files whose blob SHA is known, since the
+/// copier looks cached content up by that SHA
+pub fn prefetchable(nodes: &[RepoNode]) -> Vec<RepoNode> {
+    nodes
+        .iter()
+        .filter(|node| {
+            node.kind == NodeKind::RuleFile
+                && node.path.ends_with(".mdc")
+                && node.blob_sha.is_some()
+        })
+        .cloned()
+        .collect()
+}
+
+/// The prefetch for the directory on screen; starting another one or dropping this
+/// cancels it
+#[derive(Debug, Default)]
+pub struct Prefetcher {
+    task: Option<JoinHandle<()>>,
+}
+
+impl Prefetcher {
+    /// Cancel the running prefetch and start caching the rules among `nodes`
+    pub fn start(&mut self, locator: &RepoLocator, nodes: &[RepoNode]) {
+        self.cancel();
+        let rules = prefetchable(nodes);
+        if rules.is_empty() || crate::github::client::offline() {
+            return;
+        }
+        let locator = locator.clone();
+        self.task = Some(tokio::spawn(async move {
+            let octocrab = octocrab::instance();
+            prefetch_all(rules, PREFETCH_CONCURRENCY, |node| {
+                let octocrab = octocrab.clone();
+                let locator = locator.clone();
+                async move {
+                    if let Err(e) = crate::copier::fetch_file_content(
+                        &octocrab,
+                        &locator,
+                        &node.path,
+                        node.blob_sha.as_deref(),
+                        false,
+                        true,
+                    )
+                    .await
+                    {
+                        tracing::debug!("Prefetch of {} failed: {e:#}", node.path);
+                    }
+                }
+            })
+            .await;
+        }));
+    }
+
+    /// Stop downloading; content already cached stays cached
+    pub fn cancel(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for Prefetcher {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn node(path: &str, kind: NodeKind, blob_sha: Option<&str>) -> RepoNode {
+        RepoNode {
+            name: path.rsplit('/').next().unwrap().into(),
+            path: path.into(),
+            kind,
+            blob_sha: blob_sha.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn prefetch_keeps_no_more_than_the_limit_in_flight() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicUsize::new(0));
+
+        prefetch_all((0..12).collect(), 3, |_: usize| {
+            let (in_flight, peak, done) = (in_flight.clone(), peak.clone(), done.clone());
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                done.fetch_add(1, Ordering::SeqCst);
+            }
+        })
+        .await;
+
+        assert_eq!(done.load(Ordering::SeqCst), 12);
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn cancelled_prefetch_stops_started_downloads() {
+        let finished = Arc::new(AtomicUsize::new(0));
+        let prefetch = tokio::spawn(prefetch_all((0..4).collect(), 2, {
+            let finished = finished.clone();
+            move |_: usize| {
+                let finished = finished.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    finished.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        prefetch.abort();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        assert_eq!(finished.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn only_rules_with_a_known_sha_are_prefetched() {
+        let nodes = vec![
+            node("web", NodeKind::Dir, None),
+            node("web/react.mdc", NodeKind::RuleFile, Some("abc")),
+            node("web/vue.mdc", NodeKind::RuleFile, None),
+            node("web/README.md", NodeKind::RuleFile, Some("def")),
+            node("quick-add/web.txt", NodeKind::Manifest, Some("123")),
+        ];
+        let paths: Vec<String> = prefetchable(&nodes)
+            .into_iter()
+            .map(|node| node.path)
+            .collect();
+        assert_eq!(paths, ["web/react.mdc"]);
+    }
+}
@@ -10,7 +10,6 @@ use is_terminal::IsTerminal;
 
 /// Represents the user's choice for handling a file conflict
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[allow(dead_code)] // Variants are forward-looking; interactive prompting not yet wired into execution
 pub enum ConflictChoice {
     /// Overwrite the existing file
     Overwrite,
@@ -42,7 +41,6 @@ pub trait PromptService: Send + Sync {
     ///
     /// # Returns
     /// The user's choice for handling the conflict
-    #[allow(dead_code)] // Forward-looking feature for CLI integration
     fn prompt_conflict(
         &self,
         filename: &str,
@@ -51,7 +49,6 @@ pub trait PromptService: Send + Sync {
     ) -> Result<ConflictChoice>;
 
     /// Check if prompting is available (e.g., terminal is interactive)
-    #[allow(dead_code)] // Forward-looking feature for CLI integration
     fn can_prompt(&self) -> bool;
 }
 
@@ -122,8 +119,6 @@ impl PromptService for InteractivePromptService {
 
 /// Non-interactive prompt service that always returns a default choice
 pub struct NonInteractivePromptService {
-    #[allow(dead_code)]
-    // Read in prompt_conflict, but prompting path not yet exercised in production code
     default_choice: ConflictChoice,
 }
 
@@ -134,7 +129,6 @@ impl NonInteractivePromptService {
     }
 
     /// Create a service that always skips conflicts
-    #[allow(dead_code)] // Forward-looking feature for CLI integration
     pub fn skip_all() -> Self {
         Self::new(ConflictChoice::SkipAll)
     }
@@ -145,7 +139,6 @@ impl NonInteractivePromptService {
     }
 
     /// Create a service that always renames
-    #[allow(dead_code)] // Forward-looking feature for CLI integration
     pub fn rename_all() -> Self {
         Self::new(ConflictChoice::RenameAll)
     }
@@ -167,7 +160,7 @@ impl PromptService for NonInteractivePromptService {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
     #[test]
@@ -1,23 +1,159 @@
-use ratatui::style::Color;
+use std::fmt;
+use std::str::FromStr;
 
-/// Global colour palette used across the TUI.
-/// The values are chosen to be accessible and work in both light & dark terminals.
-pub struct Palette;
+use ratatui::style::{Color, Modifier};
+use serde::{Deserialize, Serialize};
 
-impl Palette {
+/// Colour palette used across the TUI, chosen at startup with `--theme` or the `theme`
+/// config key and carried by the browser state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
     /// Normal (unselected) text colour.
-    pub const NORMAL: Color = Color::White;
+    pub normal: Color,
 
-    /// Colour for the currently selected row.
-    pub const SELECTED_BG: Color = Color::Indexed(25); // blue
-    pub const SELECTED_FG: Color = Color::White;
+    /// Colours and emphasis for the currently selected row.
+    pub selected_bg: Color,
+    pub selected_fg: Color,
+    pub selected_modifier: Modifier,
 
     /// Dimmed colour for hidden/greyed entries.
-    pub const HIDDEN: Color = Color::Indexed(241);
+    pub hidden: Color,
+
+    /// Foreground colour for rows marked for copying.
+    pub marked: Color,
 
     /// Breadcrumb foreground colour.
-    pub const BREADCRUMB: Color = Color::Yellow;
+    pub breadcrumb: Color,
 
     /// Footer hint bar foreground.
-    pub const FOOTER: Color = Color::Indexed(244);
+    pub footer: Color,
+
+    /// Error banner colours.
+    pub error_fg: Color,
+    pub error_bg: Color,
+}
+
+impl Palette {
+    /// The original palette, chosen to be accessible and work in both light & dark terminals.
+    pub const DEFAULT: Palette = Palette {
+        normal: Color::White,
+        selected_bg: Color::Indexed(25), // blue
+        selected_fg: Color::White,
+        selected_modifier: Modifier::BOLD,
+        hidden: Color::Indexed(241),
+        marked: Color::Green,
+        breadcrumb: Color::Yellow,
+        footer: Color::Indexed(244),
+        error_fg: Color::Red,
+        error_bg: Color::Black,
+    };
+
+    /// Bright colours on black for terminals where the default greys are hard to read.
+    pub const HIGH_CONTRAST: Palette = Palette {
+        normal: Color::White,
+        selected_bg: Color::White,
+        selected_fg: Color::Black,
+        selected_modifier: Modifier::BOLD,
+        hidden: Color::Gray,
+        marked: Color::LightGreen,
+        breadcrumb: Color::LightYellow,
+        footer: Color::White,
+        error_fg: Color::White,
+        error_bg: Color::Red,
+    };
+
+    /// No colours at all; the selection is shown in reverse video instead.
+    pub const NO_COLOR: Palette = Palette {
+        normal: Color::Reset,
+        selected_bg: Color::Reset,
+        selected_fg: Color::Reset,
+        selected_modifier: Modifier::BOLD.union(Modifier::REVERSED),
+        hidden: Color::Reset,
+        marked: Color::Reset,
+        breadcrumb: Color::Reset,
+        footer: Color::Reset,
+        error_fg: Color::Reset,
+        error_bg: Color::Reset,
+    };
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Named palettes selectable with `--theme` or the `theme` config key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Default,
+    HighContrast,
+    NoColor,
+}
+
+impl Theme {
+    /// The palette this theme draws with
+    pub fn palette(self) -> Palette {
+        match self {
+            Theme::Default => Palette::DEFAULT,
+            Theme::HighContrast => Palette::HIGH_CONTRAST,
+            Theme::NoColor => Palette::NO_COLOR,
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Theme::Default => "default",
+            Theme::HighContrast => "high-contrast",
+            Theme::NoColor => "no-color",
+        })
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "default" => Ok(Theme::Default),
+            "high-contrast" => Ok(Theme::HighContrast),
+            "no-color" => Ok(Theme::NoColor),
+            other => Err(format!(
+                "Unknown theme: {other} (expected default, high-contrast or no-color)"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternative_palettes_differ_from_default() {
+        let default = Theme::Default.palette();
+        assert_eq!(default, Palette::default());
+
+        let high_contrast = Theme::HighContrast.palette();
+        assert_ne!(high_contrast, default);
+        assert_ne!(high_contrast.selected_bg, default.selected_bg);
+        assert_ne!(high_contrast.footer, default.footer);
+
+        let no_color = Theme::NoColor.palette();
+        assert_ne!(no_color, default);
+        assert_eq!(no_color.normal, Color::Reset);
+        assert!(no_color.selected_modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn theme_names_round_trip() {
+        for theme in [Theme::Default, Theme::HighContrast, Theme::NoColor] {
+            assert_eq!(theme.to_string().parse::<Theme>(), Ok(theme));
+        }
+        assert!("solarized".parse::<Theme>().is_err());
+    }
 }
@@ -28,6 +28,27 @@ impl Viewport {
         }
     }
 
+    /// Move selection up by one page of `view_height` items, stopping at the first item.
+    pub fn page_up(&mut self, view_height: usize) {
+        self.selected_index = self.selected_index.saturating_sub(view_height.max(1));
+    }
+
+    /// Move selection down by one page of `view_height` items, stopping at the last item.
+    pub fn page_down(&mut self, view_height: usize, total_items: usize) {
+        let last = total_items.saturating_sub(1);
+        self.selected_index = (self.selected_index + view_height.max(1)).min(last);
+    }
+
+    /// Jump to the first item.
+    pub fn scroll_to_top(&mut self) {
+        self.selected_index = 0;
+    }
+
+    /// Jump to the last item.
+    pub fn scroll_to_bottom(&mut self, total_items: usize) {
+        self.selected_index = total_items.saturating_sub(1);
+    }
+
     /// Adjusts scroll offset so that the selected item stays within visible range.
     pub fn ensure_visible(&mut self, view_height: usize) {
         if self.selected_index < self.scroll_offset {
@@ -57,4 +78,62 @@ mod tests {
         assert!(vp.scroll_offset <= vp.selected_index);
         assert!(vp.selected_index < vp.scroll_offset + 5);
     }
+
+    #[test]
+    fn page_up_stops_at_top() {
+        let mut vp = Viewport::new();
+        vp.selected_index = 12;
+        vp.page_up(5);
+        assert_eq!(vp.selected_index, 7);
+        vp.page_up(5);
+        assert_eq!(vp.selected_index, 2);
+        vp.page_up(5);
+        assert_eq!(vp.selected_index, 0);
+        vp.page_up(5);
+        assert_eq!(vp.selected_index, 0);
+    }
+
+    #[test]
+    fn page_down_stops_at_bottom() {
+        let mut vp = Viewport::new();
+        vp.page_down(5, 12);
+        assert_eq!(vp.selected_index, 5);
+        vp.page_down(5, 12);
+        assert_eq!(vp.selected_index, 10);
+        vp.page_down(5, 12);
+        assert_eq!(vp.selected_index, 11);
+        vp.page_down(5, 12);
+        assert_eq!(vp.selected_index, 11);
+
+        // Nothing to select in an empty directory
+        let mut empty = Viewport::new();
+        empty.page_down(5, 0);
+        assert_eq!(empty.selected_index, 0);
+    }
+
+    #[test]
+    fn paging_with_zero_height_still_moves() {
+        let mut vp = Viewport::new();
+        vp.page_down(0, 3);
+        assert_eq!(vp.selected_index, 1);
+        vp.page_up(0);
+        assert_eq!(vp.selected_index, 0);
+    }
+
+    #[test]
+    fn top_and_bottom_jump_to_ends() {
+        let mut vp = Viewport::new();
+        vp.scroll_to_bottom(20);
+        assert_eq!(vp.selected_index, 19);
+        vp.ensure_visible(5);
+        assert_eq!(vp.scroll_offset, 15);
+
+        vp.scroll_to_top();
+        assert_eq!(vp.selected_index, 0);
+        vp.ensure_visible(5);
+        assert_eq!(vp.scroll_offset, 0);
+
+        vp.scroll_to_bottom(0);
+        assert_eq!(vp.selected_index, 0);
+    }
 }
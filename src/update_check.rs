@@ -0,0 +1,258 @@
+//! Opt-in notification of newer releases.
+//!
+//! When the `update_check` config key is true, or `--version-check` is given, the latest
+//! GitHub release is looked up in the background while the command runs and a one-line
+//! notice is printed to stderr once it finishes. Checks happen at most once a day; the
+//! time of the last one is kept in a file next to the config file.
+
+use std::cmp::Ordering;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::task::JoinHandle;
+
+/// Latest published release of this tool
+const LATEST_RELEASE_ENDPOINT: &str = "/repos/tkozzer/cursor-rules-cli/releases/latest";
+
+/// Minimum time between two checks, unless `--version-check` forces one
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long to wait for a check still running when the command has finished
+const REPORT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// File in the config directory holding the Unix time of the last check
+const STAMP_FILE: &str = "last-update-check";
+
+/// A running check that resolves to the newer version, if there is one
+pub struct UpdateCheck(JoinHandle<Option<String>>);
+
+/// Start a background check when `enabled` and the last one was over a day ago.
+/// `forced` checks regardless of the config key and the daily limit, but nothing is
+/// checked `offline`.
+pub fn start(enabled: bool, forced: bool, offline: bool) -> Option<UpdateCheck> {
+    if offline || (!enabled && !forced) {
+        return None;
+    }
+    let stamp = stamp_path()?;
+    let now = unix_now();
+    if !forced && !is_due(read_stamp(&stamp), now) {
+        return None;
+    }
+
+    Some(UpdateCheck(tokio::spawn(async move {
+        let latest = latest_release_tag().await?;
+        if let Some(dir) = stamp.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let _ = std::fs::write(&stamp, now.to_string());
+        newer_version(env!("CARGO_PKG_VERSION"), &latest)
+    })))
+}
+
+/// Print the notice for a finished check, waiting briefly for one still in flight
+pub async fn report(check: Option<UpdateCheck>) {
+    let Some(UpdateCheck(handle)) = check else {
+        return;
+    };
+    if let Ok(Ok(Some(version))) = tokio::time::timeout(REPORT_TIMEOUT, handle).await {
+        eprintln!("update available: {version}");
+    }
+}
+
+/// `latest` as `vX.Y.Z` when it is a newer version than `current`. Either may carry a
+/// leading `v`; tags that are not versions are never reported.
+pub fn newer_version(current: &str, latest: &str) -> Option<String> {
+    let current_version = Version::parse(current)?;
+    let latest_version = Version::parse(latest)?;
+    (latest_version.compare(&current_version) == Ordering::Greater)
+        .then(|| format!("v{}", strip_v(latest)))
+}
+
+/// Whether a check is due given the Unix time of the last one
+fn is_due(last_check: Option<u64>, now: u64) -> bool {
+    last_check.is_none_or(|last| now.saturating_sub(last) >= CHECK_INTERVAL.as_secs())
+}
+
+async fn latest_release_tag() -> Option<String> {
+    let octocrab = crate::github::client::octocrab(None).ok()?;
+    let release: serde_json::Value = octocrab
+        .get(LATEST_RELEASE_ENDPOINT, None::<&()>)
+        .await
+        .map_err(|e| tracing::debug!("Update check failed: {e}"))
+        .ok()?;
+    release["tag_name"].as_str().map(str::to_string)
+}
+
+fn stamp_path() -> Option<PathBuf> {
+    let config_file = crate::config::config_file_path().ok()?;
+    Some(config_file.parent()?.join(STAMP_FILE))
+}
+
+fn read_stamp(path: &std::path::Path) -> Option<u64> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn strip_v(tag: &str) -> &str {
+    let tag = tag.trim();
+    tag.strip_prefix(['v', 'V']).unwrap_or(tag)
+}
+
+/// A semantic version: `MAJOR.MINOR.PATCH` with optional pre-release identifiers.
+/// Build metadata after `+` is ignored.
+#[derive(Debug, PartialEq, Eq)]
+struct Version {
+    core: [u64; 3],
+    pre: Vec<String>,
+}
+
+impl Version {
+    fn parse(text: &str) -> Option<Self> {
+        let text = strip_v(text);
+        let text = text.split_once('+').map_or(text, |(version, _)| version);
+        let (core_text, pre_text) = match text.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (text, None),
+        };
+
+        let mut core = [0; 3];
+        let mut parts = core_text.split('.');
+        for slot in &mut core {
+            *slot = parts.next()?.parse().ok()?;
+        }
+        if parts.next().is_some() {
+            return None;
+        }
+
+        let pre = match pre_text {
+            Some("") => return None,
+            Some(pre) => pre.split('.').map(str::to_string).collect(),
+            None => Vec::new(),
+        };
+        Some(Self { core, pre })
+    }
+
+    /// Semantic version precedence: a pre-release sorts before its release, numeric
+    /// identifiers compare numerically and before alphanumeric ones
+    fn compare(&self, other: &Self) -> Ordering {
+        self.core
+            .cmp(&other.core)
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => {
+                    for (ours, theirs) in self.pre.iter().zip(&other.pre) {
+                        let order = match (ours.parse::<u64>(), theirs.parse::<u64>()) {
+                            (Ok(a), Ok(b)) => a.cmp(&b),
+                            (Ok(_), Err(_)) => Ordering::Less,
+                            (Err(_), Ok(_)) => Ordering::Greater,
+                            (Err(_), Err(_)) => ours.cmp(theirs),
+                        };
+                        if order != Ordering::Equal {
+                            return order;
+                        }
+                    }
+                    self.pre.len().cmp(&other.pre.len())
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_release_tags_are_reported() {
+        assert_eq!(newer_version("0.2.0", "v0.3.0"), Some("v0.3.0".into()));
+        assert_eq!(newer_version("0.2.0", "0.2.1"), Some("v0.2.1".into()));
+        assert_eq!(newer_version("0.9.9", "v1.0.0"), Some("v1.0.0".into()));
+        assert_eq!(newer_version("0.2.0", "v0.10.0"), Some("v0.10.0".into()));
+        assert_eq!(newer_version("0.2.0", "v0.2.0"), None);
+        assert_eq!(newer_version("0.3.0", "v0.2.9"), None);
+    }
+
+    #[test]
+    fn pre_releases_sort_before_their_release() {
+        // A release is newer than its own pre-releases, never the other way round
+        assert_eq!(
+            newer_version("1.0.0-beta.2", "v1.0.0"),
+            Some("v1.0.0".into())
+        );
+        assert_eq!(newer_version("1.0.0", "v1.0.0-rc.1"), None);
+        assert_eq!(
+            newer_version("1.0.0", "v1.1.0-alpha"),
+            Some("v1.1.0-alpha".into())
+        );
+
+        // Identifiers compare numerically, numbers before words, shorter first
+        assert_eq!(
+            newer_version("1.0.0-beta.2", "v1.0.0-beta.11"),
+            Some("v1.0.0-beta.11".into())
+        );
+        assert_eq!(
+            newer_version("1.0.0-alpha", "v1.0.0-alpha.1"),
+            Some("v1.0.0-alpha.1".into())
+        );
+        assert_eq!(
+            newer_version("1.0.0-1", "v1.0.0-alpha"),
+            Some("v1.0.0-alpha".into())
+        );
+        assert_eq!(newer_version("1.0.0-rc.1", "v1.0.0-beta.5"), None);
+    }
+
+    #[test]
+    fn malformed_tags_are_ignored() {
+        assert_eq!(newer_version("0.2.0", "nightly"), None);
+        assert_eq!(newer_version("0.2.0", "v1.0"), None);
+        assert_eq!(newer_version("0.2.0", "v1.0.0.0"), None);
+        assert_eq!(newer_version("0.2.0", "v1.0.0-"), None);
+        assert_eq!(
+            newer_version("0.2.0", "v1.0.0+build.5"),
+            Some("v1.0.0+build.5".into())
+        );
+    }
+
+    #[test]
+    fn checks_run_at_most_daily() {
+        let day = CHECK_INTERVAL.as_secs();
+        assert!(is_due(None, 1_000));
+        assert!(!is_due(Some(1_000), 1_000 + day - 1));
+        assert!(is_due(Some(1_000), 1_000 + day));
+        // A clock that went backwards does not trigger a check
+        assert!(!is_due(Some(1_000), 500));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn offline_forced_check_makes_no_request() {
+        let config_home = tempfile::tempdir().unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let release = server
+            .mock("GET", LATEST_RELEASE_ENDPOINT)
+            .with_status(200)
+            .with_body(r#"{"tag_name": "v99.0.0"}"#)
+            .expect(0)
+            .create_async()
+            .await;
+        let original = std::env::var("XDG_CONFIG_HOME").ok();
+        std::env::set_var("XDG_CONFIG_HOME", config_home.path());
+        std::env::set_var("OCTO_BASE", server.url());
+
+        let check = start(true, true, true);
+        report(check).await;
+
+        std::env::remove_var("OCTO_BASE");
+        match original {
+            Some(value) => std::env::set_var("XDG_CONFIG_HOME", value),
+            None => std::env::remove_var("XDG_CONFIG_HOME"),
+        }
+        release.assert_async().await;
+    }
+}
@@ -0,0 +1,141 @@
+use assert_cmd::Command;
+use mockito::{Matcher, Server, ServerGuard};
+use predicates::str::contains;
+use serde_json::json;
+
+/// Start a mock GitHub API serving a small repository tree
+fn mock_github() -> ServerGuard {
+    let tree_resp = json!({
+        "tree": [
+            {"path": "frontend", "type": "tree"},
+            {"path": "frontend/react.mdc", "type": "blob"},
+            {"path": "frontend/vue.mdc", "type": "blob"},
+            {"path": "backend", "type": "tree"},
+            {"path": "backend/rust.mdc", "type": "blob"}
+        ]
+    });
+
+    let mut server = Server::new();
+
+    server
+        .mock("GET", "/repos/test/cursor-rules")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({"name": "cursor-rules"}).to_string())
+        .create();
+
+    server
+        .mock("GET", "/repos/test/cursor-rules/git/trees/main")
+        .match_query(Matcher::UrlEncoded("recursive".into(), "1".into()))
+        .with_status(200)
+        .with_body(tree_resp.to_string())
+        .create();
+
+    // The browser lists one directory at a time through the contents API
+    let listings = [
+        (
+            "",
+            json!([entry("frontend", "dir"), entry("backend", "dir")]),
+        ),
+        (
+            "/frontend",
+            json!([
+                entry("frontend/react.mdc", "file"),
+                entry("frontend/vue.mdc", "file")
+            ]),
+        ),
+        ("/backend", json!([entry("backend/rust.mdc", "file")])),
+    ];
+    for (dir, listing) in listings {
+        server
+            .mock(
+                "GET",
+                format!("/repos/test/cursor-rules/contents{dir}").as_str(),
+            )
+            .match_query(Matcher::UrlEncoded("ref".into(), "main".into()))
+            .with_status(200)
+            .with_body(listing.to_string())
+            .create();
+    }
+
+    server
+}
+
+/// One entry of a contents API directory listing
+fn entry(path: &str, kind: &str) -> serde_json::Value {
+    json!({
+        "name": path.rsplit('/').next().unwrap(),
+        "path": path,
+        "type": kind,
+    })
+}
+
+/// Build a `cursor-rules --owner test --dry-run browse --plain` command against the mock
+fn plain_browser(server: &ServerGuard, home: &tempfile::TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("cursor-rules").unwrap();
+    cmd.env("OCTO_BASE", server.url())
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path())
+        .env("XDG_CACHE_HOME", home.path())
+        .env_remove("GITHUB_TOKEN")
+        .env_remove("GH_TOKEN")
+        .args(["--owner", "test", "--dry-run", "browse", "--plain"]);
+    cmd
+}
+
+#[test]
+fn plain_browser_lists_and_navigates() {
+    let server = mock_github();
+    let home = tempfile::tempdir().unwrap();
+
+    plain_browser(&server, &home)
+        .write_stdin("enter 1\nup\nquit\n")
+        .assert()
+        .success()
+        .stdout(contains("1. directory: frontend"))
+        .stdout(contains("2. directory: backend"))
+        .stdout(contains("test/cursor-rules/frontend"))
+        .stdout(contains("1. rule: react.mdc"));
+}
+
+#[test]
+fn plain_browser_copies_selected_file() {
+    let server = mock_github();
+    let home = tempfile::tempdir().unwrap();
+
+    plain_browser(&server, &home)
+        .write_stdin("1\n1\nquit\n")
+        .assert()
+        .success()
+        .stdout(contains("Copy requested: frontend/react.mdc"))
+        .stdout(contains("Dry-run mode: Would copy frontend/react.mdc"));
+}
+
+#[test]
+fn plain_browser_copies_marked_files() {
+    let server = mock_github();
+    let home = tempfile::tempdir().unwrap();
+
+    plain_browser(&server, &home)
+        .write_stdin("enter 1\nmark 1\nmark 2\ncopy\n")
+        .assert()
+        .success()
+        .stdout(contains("Marked 1: react.mdc"))
+        .stdout(contains("Copy requested for 2 rule file(s)."))
+        .stdout(contains("Total files: 2"))
+        .stdout(contains("frontend/react.mdc"))
+        .stdout(contains("frontend/vue.mdc"));
+}
+
+#[test]
+fn plain_browser_reports_unknown_commands() {
+    let server = mock_github();
+    let home = tempfile::tempdir().unwrap();
+
+    plain_browser(&server, &home)
+        .write_stdin("dance\nenter 9\nquit\n")
+        .assert()
+        .success()
+        .stdout(contains("Unknown command 'dance'"))
+        .stdout(contains("No item 9 in this directory."));
+}
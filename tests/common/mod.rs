@@ -0,0 +1,62 @@
+//! Mock GitHub API shared by the integration tests
+
+use base64::Engine;
+use mockito::{Matcher, Server, ServerGuard};
+use serde_json::{json, Value};
+
+/// Contents API response for a file, with the body inlined as base64
+fn content_item(server: &ServerGuard, path: &str, body: &str) -> String {
+    let name = path.rsplit('/').next().unwrap();
+    let url = format!("{}/repos/test/cursor-rules/contents/{path}", server.url());
+    json!({
+        "type": "file",
+        "encoding": "base64",
+        "size": body.len(),
+        "name": name,
+        "path": path,
+        "content": base64::engine::general_purpose::STANDARD.encode(body),
+        "sha": "0000000000000000000000000000000000000000",
+        "url": url,
+        "git_url": null,
+        "html_url": null,
+        "download_url": null,
+        "_links": {"self": url, "git": null, "html": null}
+    })
+    .to_string()
+}
+
+/// Start a mock GitHub API for `test/cursor-rules` whose `main` branch has the entries in
+/// `tree`, serving each `(path, body)` in `files` from the contents API
+pub fn mock_github(tree: Value, files: &[(&str, &str)]) -> ServerGuard {
+    let mut server = Server::new();
+
+    server
+        .mock("GET", "/repos/test/cursor-rules")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(json!({"name": "cursor-rules"}).to_string())
+        .create();
+
+    server
+        .mock("GET", "/repos/test/cursor-rules/git/trees/main")
+        .match_query(Matcher::UrlEncoded("recursive".into(), "1".into()))
+        .with_status(200)
+        .with_body(json!({ "tree": tree }).to_string())
+        .create();
+
+    for (path, body) in files {
+        let item = content_item(&server, path, body);
+        server
+            .mock(
+                "GET",
+                format!("/repos/test/cursor-rules/contents/{path}").as_str(),
+            )
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(item)
+            .create();
+    }
+
+    server
+}
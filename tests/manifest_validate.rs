@@ -0,0 +1,121 @@
+mod common;
+
+use assert_cmd::Command;
+use mockito::ServerGuard;
+use predicates::str::contains;
+use serde_json::{json, Value};
+
+/// Start a mock GitHub API with a valid `good` manifest and a `broken` one with a typo
+fn mock_github() -> ServerGuard {
+    common::mock_github(
+        json!([
+            {"path": "frontend", "type": "tree"},
+            {"path": "frontend/react.mdc", "type": "blob"},
+            {"path": "quick-add", "type": "tree"},
+            {"path": "quick-add/good.txt", "type": "blob"},
+            {"path": "quick-add/broken.yaml", "type": "blob"}
+        ]),
+        &[
+            ("quick-add/good.txt", "frontend/react.mdc\n"),
+            (
+                "quick-add/broken.yaml",
+                "name: Broken\nrules:\n  - frontend/reakt.mdc\n  - notes.md\n",
+            ),
+        ],
+    )
+}
+
+fn validate(server: &ServerGuard, home: &tempfile::TempDir, args: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("cursor-rules").unwrap();
+    cmd.env("OCTO_BASE", server.url())
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join("config"))
+        .env("XDG_CACHE_HOME", home.path().join("cache"))
+        .env_remove("GITHUB_TOKEN")
+        .env_remove("GH_TOKEN")
+        .args(["--owner", "test"])
+        .args(args);
+    cmd
+}
+
+#[test]
+fn manifest_validate_reports_broken_manifests() {
+    let server = mock_github();
+    let home = tempfile::tempdir().unwrap();
+
+    validate(&server, &home, &["manifest", "validate", "good"])
+        .assert()
+        .success()
+        .stdout(contains("good: 1 rule(s), 0 error(s), 0 warning(s)"));
+
+    validate(&server, &home, &["manifest", "validate", "all"])
+        .assert()
+        .code(2)
+        .stdout(contains(
+            "broken: File not found in repository: frontend/reakt.mdc (did you mean frontend/react.mdc?)",
+        ))
+        .stdout(contains("broken: Non-.mdc file ignored: notes.md"))
+        .stdout(contains("good: 1 rule(s)"));
+}
+
+#[test]
+fn manifest_validate_json_report() {
+    let server = mock_github();
+    let home = tempfile::tempdir().unwrap();
+
+    let output = validate(&server, &home, &["--json", "manifest", "validate", "all"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+
+    let reports: Value = serde_json::from_slice(&output).unwrap();
+    let reports = reports.as_array().unwrap();
+    assert_eq!(reports.len(), 2);
+
+    assert_eq!(reports[0]["manifest"], "broken");
+    assert_eq!(reports[0]["entry_count"], 0);
+    assert_eq!(reports[0]["errors"].as_array().unwrap().len(), 1);
+    assert_eq!(reports[0]["warnings"].as_array().unwrap().len(), 1);
+
+    assert_eq!(reports[1]["manifest"], "good");
+    assert_eq!(reports[1]["entry_count"], 1);
+    assert!(reports[1]["errors"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn manifest_validate_yaml_report() {
+    let server = mock_github();
+    let home = tempfile::tempdir().unwrap();
+
+    let output = validate(
+        &server,
+        &home,
+        &["--format", "yaml", "manifest", "validate", "good"],
+    )
+    .assert()
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+
+    let reports: serde_yaml::Value = serde_yaml::from_slice(&output).unwrap();
+    assert_eq!(reports[0]["manifest"], "good");
+    assert_eq!(reports[0]["entry_count"], 1);
+}
+
+#[test]
+fn json_flag_conflicts_with_format() {
+    let server = mock_github();
+    let home = tempfile::tempdir().unwrap();
+
+    validate(
+        &server,
+        &home,
+        &["--json", "--format", "yaml", "manifest", "validate", "good"],
+    )
+    .assert()
+    .failure()
+    .stderr(contains("cannot be used with"));
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use assert_cmd::Command;
+use mockito::ServerGuard;
+use predicates::str::contains;
+use serde_json::json;
+
+const MANIFEST: &str = "frontend/file.mdc\n";
+const RULE: &str = "# Frontend rules\n";
+
+/// Git blob SHA of `content`, as the tree API reports it
+fn blob_sha(content: &str) -> String {
+    use sha1::{Digest, Sha1};
+    let mut hasher = Sha1::new();
+    hasher.update(format!("blob {}\0", content.len()).as_bytes());
+    hasher.update(content.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Start a mock GitHub API with a `frontend` manifest listing one rule file
+fn mock_github() -> ServerGuard {
+    common::mock_github(
+        json!([
+            {"path": "frontend", "type": "tree"},
+            {"path": "frontend/file.mdc", "type": "blob", "sha": blob_sha(RULE)},
+            {"path": "quick-add", "type": "tree"},
+            {"path": "quick-add/frontend.txt", "type": "blob", "sha": blob_sha(MANIFEST)}
+        ]),
+        &[
+            ("quick-add/frontend.txt", MANIFEST),
+            ("frontend/file.mdc", RULE),
+        ],
+    )
+}
+
+#[test]
+fn quick_add_twice_with_rename_keeps_both_files() {
+    let server = mock_github();
+    let home = tempfile::tempdir().unwrap();
+    let out_dir = home.path().join("rules");
+
+    let run = |extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("cursor-rules").unwrap();
+        cmd.env("OCTO_BASE", server.url())
+            .env("HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path().join("config"))
+            .env("XDG_CACHE_HOME", home.path().join("cache"))
+            .env_remove("GITHUB_TOKEN")
+            .env_remove("GH_TOKEN")
+            .arg("--owner")
+            .arg("test")
+            .arg("--out")
+            .arg(&out_dir)
+            .arg("--rename")
+            .args(extra)
+            .args(["quick-add", "frontend"]);
+        cmd.assert()
+    };
+
+    run(&[]).success().stdout(contains("Files renamed: 0"));
+    assert!(out_dir.join("file.mdc").exists());
+    assert!(!out_dir.join("file(1).mdc").exists());
+
+    run(&[]).success().stdout(contains("Files renamed: 1"));
+    assert!(out_dir.join("file.mdc").exists());
+    assert_eq!(
+        std::fs::read_to_string(out_dir.join("file(1).mdc")).unwrap(),
+        "# Frontend rules\n"
+    );
+
+    // Dry-run previews the next generated name without writing it
+    run(&["--dry-run"])
+        .success()
+        .stdout(contains("Rename → file(2).mdc"));
+    assert!(!out_dir.join("file(2).mdc").exists());
+}
+
+#[test]
+fn quick_add_dry_run_json_describes_plan() {
+    let server = mock_github();
+    let home = tempfile::tempdir().unwrap();
+    let out_dir = home.path().join("rules");
+    std::fs::create_dir_all(&out_dir).unwrap();
+    std::fs::write(out_dir.join("file.mdc"), "local").unwrap();
+
+    let output = Command::cargo_bin("cursor-rules")
+        .unwrap()
+        .env("OCTO_BASE", server.url())
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join("config"))
+        .env("XDG_CACHE_HOME", home.path().join("cache"))
+        .env_remove("GITHUB_TOKEN")
+        .env_remove("GH_TOKEN")
+        .args(["--owner", "test", "--out"])
+        .arg(&out_dir)
+        .args(["--rename", "--dry-run", "--json", "quick-add", "frontend"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["manifest"], "frontend");
+    assert!(report["errors"].as_array().unwrap().is_empty());
+
+    let files = report["files"].as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0]["source"], "frontend/file.mdc");
+    assert_eq!(files[0]["would_overwrite"], true);
+    assert_eq!(files[0]["action"], "rename");
+    assert_eq!(files[0]["renamed_to"], "file(1).mdc");
+    assert!(!out_dir.join("file(1).mdc").exists());
+}
+
+#[test]
+fn quick_add_offline_applies_a_manifest_from_a_warm_cache() {
+    let server = mock_github();
+    let home = tempfile::tempdir().unwrap();
+
+    let run = |out_dir: &std::path::Path, extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("cursor-rules").unwrap();
+        cmd.env("OCTO_BASE", server.url())
+            .env("HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path().join("config"))
+            .env("XDG_CACHE_HOME", home.path().join("cache"))
+            .env_remove("GITHUB_TOKEN")
+            .env_remove("GH_TOKEN")
+            .args(["--owner", "test", "--out"])
+            .arg(out_dir)
+            .args(extra)
+            .args(["quick-add", "frontend"]);
+        cmd.assert()
+    };
+
+    // Nothing is cached yet, so the manifest cannot be read offline
+    let cold = home.path().join("cold");
+    run(&cold, &["--offline"]).failure();
+
+    let online = home.path().join("online");
+    run(&online, &[]).success();
+
+    // The online run cached the tree, the manifest and its rule
+    let offline = home.path().join("offline");
+    run(&offline, &["--offline"])
+        .success()
+        .stdout(contains("Files copied: 1"));
+    assert_eq!(
+        std::fs::read_to_string(offline.join("file.mdc")).unwrap(),
+        RULE
+    );
+}